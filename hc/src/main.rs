@@ -3,7 +3,6 @@
  * do we? */
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 #![cfg_attr(debug_assertions, windows_subsystem = "console")]
-use crate::window::bitmap::BitmapError;
 use crate::window::NoTabletConnector;
 
 /// Utility structures for interpolating curved paths from ordered collections
@@ -19,8 +18,161 @@ mod robot;
 /// Strings used in the UI.
 mod strings;
 
+/// The command line flag that selects importing a signature from an image.
+const IMAGE_FLAG: &'static str = "--image";
+
+/// The command line flag that selects capturing a signature from the first
+/// tablet attached to the system straight into the PNG file named by the
+/// argument following it, without showing any windows.
+///
+/// A capture ends once Enter is pressed in the console, or once something has
+/// been signed and the pen then stays off the screen for
+/// [`CAPTURE_IDLE_TIMEOUT`]. A capture in which nothing gets signed gives up
+/// after [`CAPTURE_START_TIMEOUT`]. Besides success, it may exit with
+/// [`EXIT_NO_DEVICE`], [`EXIT_CONNECTION_FAILED`] or [`EXIT_CAPTURE_FAILED`].
+const CAPTURE_FLAG: &'static str = "--capture";
+
+/// The command line flag that makes the management window close by itself once
+/// the pen has not touched the screen for the number of seconds given by the
+/// argument following it.
+const IDLE_TIMEOUT_FLAG: &'static str = "--idle-timeout";
+
+/// The exit code of a capture that found no tablet to connect to.
+const EXIT_NO_DEVICE: i32 = 2;
+
+/// The exit code of a capture that failed after having connected to a tablet.
+const EXIT_CAPTURE_FAILED: i32 = 3;
+
+/// The exit code of a capture that found a tablet but could not connect to it.
+const EXIT_CONNECTION_FAILED: i32 = 4;
+
+/// How long the pen must stay off the screen for a capture to be over.
+const CAPTURE_IDLE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// How long a capture waits for the pen to first touch the screen before
+/// giving up.
+const CAPTURE_START_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
+
 fn main() {
+	let mut args = std::env::args_os().skip(1);
+	if args.by_ref().any(|arg| arg == CAPTURE_FLAG) {
+		match args.next() {
+			Some(target) => capture(std::path::Path::new(&target)),
+			None => std::process::exit(EXIT_CAPTURE_FAILED)
+		}
+	}
+
+	strings::set_language(strings::Language::from_system());
 	window::init();
+
+	if std::env::args_os().skip(1).any(|arg| arg == IMAGE_FLAG) {
+		image()
+	} else {
+		live()
+	}
+}
+
+/// The idle timeout given in the command line, if any.
+fn idle_timeout() -> Option<std::time::Duration> {
+	let mut args = std::env::args_os().skip(1);
+	args.by_ref().find(|arg| arg == IDLE_TIMEOUT_FLAG)?;
+	args.next()?
+		.to_str()?
+		.parse::<u64>()
+		.ok()
+		.map(std::time::Duration::from_secs)
+}
+
+/// Captures a signature from the first tablet attached to the system into the
+/// given file, then exits with the code describing the outcome.
+fn capture(target: &std::path::Path) -> ! {
+	let connector = stu::list_devices()
+		.ok()
+		.and_then(|mut devices| devices.next());
+	let device = match connector {
+		Some(connector) => connector.connect_with(Default::default()),
+		None => std::process::exit(EXIT_NO_DEVICE)
+	};
+	let device = match device {
+		Ok(device) => device,
+		Err(_) => std::process::exit(EXIT_CONNECTION_FAILED)
+	};
+
+	let png = capture_png(&device);
+	let _ = device.try_inking(false);
+
+	match png.map(|png| std::fs::write(target, png)) {
+		Some(Ok(_)) => std::process::exit(0),
+		_ => std::process::exit(EXIT_CAPTURE_FAILED)
+	}
+}
+
+/// Collects a signature from the given tablet and encodes it as a PNG image.
+fn capture_png(device: &stu::Tablet) -> Option<Box<[u8]>> {
+	let caps = device.capability().ok()?;
+	let mut queue = device.queue().ok()?;
+	let mut canvas = path::EventCanvas::new(caps.width(), caps.height()).ok()?;
+	canvas.set_grid_mapping(path::GridMapping::from_capability(&caps));
+
+	device.try_clear().ok()?;
+	device.try_inking(true).ok()?;
+
+	/* Let the capture be finished early by pressing Enter. Reading nothing at
+	 * all means there is no console to read from, so that is ignored. */
+	eprintln!("{}", strings::capture::finish_prompt());
+	let (finish, finished) = std::sync::mpsc::channel();
+	std::thread::spawn(move || {
+		let mut line = String::new();
+		let read = std::io::stdin().read_line(&mut line);
+		if matches!(read, Ok(read) if read > 0) {
+			let _ = finish.send(());
+		}
+	});
+
+	let start = std::time::Instant::now();
+	let mut last_touch = None;
+	loop {
+		match queue.try_recv() {
+			Ok(event) => {
+				if event.touching() {
+					last_touch = Some(std::time::Instant::now());
+				}
+				canvas.process(event);
+			},
+			Err(stu::TryRecvError::Empty) =>
+				std::thread::sleep(std::time::Duration::from_millis(5)),
+			Err(stu::TryRecvError::Failed(_)) => return None
+		}
+
+		let idle = match last_touch {
+			Some(last_touch) => last_touch.elapsed() >= CAPTURE_IDLE_TIMEOUT,
+			None => start.elapsed() >= CAPTURE_START_TIMEOUT
+		};
+		if idle || finished.try_recv().is_ok() {
+			break
+		}
+	}
+
+	canvas.to_png_cropped_scaled(window::EXPORT_SCALE)
+}
+
+/// Shows the given error message to the user and exits with a failure code.
+fn fail(message: &str) -> ! {
+	nwg::error_message(&crate::strings::errors::title(), message);
+	std::process::exit(1);
+}
+
+/// Imports a signature from an image and paints it, without any tablet.
+fn image() {
+	if let Err(what) = window::bitmap::run(None) {
+		if let Some(message) = what.message() {
+			fail(&message);
+		}
+	}
+}
+
+/// Connects to a tablet picked by the user and collects a signature from it.
+fn live() {
 	let information = match window::pick_tablet() {
 		Ok(information) => information,
 		Err(what) => {
@@ -32,46 +184,33 @@ fn main() {
 						&crate::strings::errors::no_tablets_available());
 					0
 				}
-				NoTabletConnector::WindowCreationError(what) => {
-					nwg::error_message(
-						&crate::strings::errors::title(),
-						&crate::strings::errors::device_prompt_creation_failed(what));
-					1
-				}
+				NoTabletConnector::DeviceListingFailed(what) =>
+					fail(&crate::strings::errors::device_listing_failed(what)),
+				NoTabletConnector::WindowCreationError(what) =>
+					fail(&crate::strings::errors::device_prompt_creation_failed(what)),
 			};
 
 			std::process::exit(exit);
 		}
 	};
 
-	let device = stu::list_devices()
-		.find(|connector| connector.info() == information);
+	let device = match stu::find_devices(|info| *info == information) {
+		Ok(mut devices) => devices.next(),
+		Err(what) =>
+			fail(&crate::strings::errors::device_listing_failed(what))
+	};
 	let device = match device {
 		Some(device) => device,
-		None => {
-			nwg::error_message(
-				&crate::strings::errors::title(),
-				&crate::strings::errors::tablet_not_found(information));
-
-			std::process::exit(1);
-		}
+		None =>
+			fail(&crate::strings::errors::tablet_not_found(information))
 	};
-	let device = match device.connect() {
+	let device = match device.connect_with(Default::default()) {
 		Ok(device) => device,
-		Err(what) => {
-			nwg::error_message(
-				&crate::strings::errors::title(),
-				&crate::strings::errors::tablet_connection_failed(information, what));
-
-			std::process::exit(1);
-		}
+		Err(what) =>
+			fail(&crate::strings::errors::tablet_connection_failed(information, what))
 	};
 
-	if let Err(what) = window::manage(device) {
-		nwg::error_message(
-			&crate::strings::errors::title(),
-			&crate::strings::errors::management_failed(what));
-
-		std::process::exit(1);
+	if let Err(what) = window::manage(device, idle_timeout()) {
+		fail(&crate::strings::errors::management_failed(what));
 	}
 }
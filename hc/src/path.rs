@@ -1,7 +1,11 @@
 use std::collections::btree_map::BTreeMap;
-use std::time::Instant;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+use std::io::{self, Read, Write};
+use std::convert::TryFrom;
 use image::Luma;
-use stu::Event;
+use stu::{Event, SerializableEvent};
 
 /// Trait for structures that can produce a [`Trace`].
 ///
@@ -35,39 +39,94 @@ pub struct Point {
 	pub y: f64,
 	/// Whether the pen is touching the screen at this point.
 	pub touch: bool,
+	/// The pressure being applied to the screen with the pen at this point,
+	/// normalized so that `0.0` is the minimum and `1.0` is the maximum value.
+	pub pressure: f64,
 }
 
+/// The default width of a stroke drawn with no pressure, in pixels.
+const DEFAULT_MIN_STROKE_WIDTH: f32 = 1.0;
+
+/// The default width of a stroke drawn with full pressure, in pixels.
+const DEFAULT_MAX_STROKE_WIDTH: f32 = 4.0;
+
+/// The default distance between two consecutive touching events past which
+/// the pen is taken to have been lifted between them, normalized to the
+/// dimensions of the screen.
+const DEFAULT_STROKE_GAP: f64 = 0.2;
+
+/// The length of each of the arms of the marker drawn where the pen is
+/// hovering, in pixels.
+const HOVER_MARKER_RADIUS: i64 = 4;
 
 /// A structure for generating pictures from events.
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct EventCanvas {
-	/// A monochrome pixel data buffer.
+	/// A grayscale pixel data buffer, holding the intensity of every pixel.
 	buffer: Box<[u8]>,
 	/// The width of the canvas, in pixels.
 	width: u32,
 	/// The height of the canvas, in pixels.
 	height: u32,
-	/// The last point the pen stroke.
-	last: Option<(u32, u32)>,
+	/// The last point of the pen stroke, along with the pressure it had.
+	last: Option<(f64, f64, f64)>,
+	/// The width of the stroke at the minimum and maximum pressures.
+	stroke_width: (f32, f32),
+	/// The distance past which consecutive touching events are not connected.
+	stroke_gap: Option<f64>,
+	/// The layer of the pixel data buffer, holding the layer of the stroke
+	/// that last painted over every pixel.
+	layers: Box<[u8]>,
+	/// The layer new strokes are drawn on.
+	layer: u8,
+	/// The layer the stroke being drawn is on.
+	drawing: u8,
+	/// The layers and the events making up each of the strokes drawn on this
+	/// canvas, in the order in which they were drawn.
+	strokes: Vec<(u8, Vec<Event>)>,
+	/// The strokes that have been undone, with the most recent one last.
+	undone: Vec<(u8, Vec<Event>)>,
+	/// The position of the pen, if it is hovering over the screen without
+	/// touching it.
+	hover: Option<(u32, u32)>,
+	/// The smallest number of events a stroke must have in order to be
+	/// exported, if short strokes are to be left out of exports at all.
+	min_stroke_events: Option<usize>,
+	/// How the position of the pen maps on to the pixels of this canvas.
+	mapping: GridMapping,
 }
 impl EventCanvas {
 	/// Creates a new, blank canvas on with the given dimensions.
-	pub fn new(width: u32, height: u32) -> Self {
-		if width == 0 {
-			panic!("Tried to create a canvas with no width.")
-		}
-		if height == 0 {
-			panic!("Tried to create a canvas with no height.")
+	///
+	/// Devices have been seen briefly reporting screens with no width or no
+	/// height while they are still being set up, so rather than panicking, this
+	/// function fails when given such dimensions.
+	pub fn new(width: u32, height: u32) -> Result<Self, CanvasError> {
+		if width == 0 || height == 0 {
+			return Err(CanvasError::Empty { width, height })
 		}
 
-		let bits = u64::from(width) * u64::from(height);
-
-		let bytes = bits / 8 + if bits % 8 == 0 { 0 } else { 1 };
-		let bytes = std::convert::TryFrom::try_from(bytes)
-			.expect("Canvas size does not fit in a usize");
+		let bytes = u64::from(width) * u64::from(height);
+		let bytes = usize::try_from(bytes)
+			.map_err(|_| CanvasError::TooLarge { width, height })?;
 
 		let buffer = vec![0u8; bytes].into_boxed_slice();
-		Self { buffer, width, height, last: None }
+		Ok(Self {
+			layers: buffer.clone(),
+			layer: 0,
+			drawing: 0,
+			buffer,
+			width,
+			height,
+			last: None,
+			stroke_width: (DEFAULT_MIN_STROKE_WIDTH, DEFAULT_MAX_STROKE_WIDTH),
+			stroke_gap: Some(DEFAULT_STROKE_GAP),
+			strokes: Vec::new(),
+			undone: Vec::new(),
+			hover: None,
+			min_stroke_events: None,
+			mapping: GridMapping::STRETCH
+		})
 	}
 
 	/// The width of this canvas, in pixels.
@@ -80,162 +139,1171 @@ impl EventCanvas {
 		self.height
 	}
 
+	/// The widths of the strokes drawn at the minimum and at the maximum pen
+	/// pressures, in pixels.
+	pub fn pressure_width(&self) -> (f32, f32) {
+		self.stroke_width
+	}
+
+	/// Changes the widths of the strokes drawn at the minimum and at the
+	/// maximum pen pressures, in pixels.
+	///
+	/// Strokes drawn at any pressure in between these two extremes will have
+	/// their width linearly interpolated between these two values.
+	pub fn set_pressure_width(&mut self, min: f32, max: f32) {
+		if !(min >= 0.0) {
+			panic!("Tried to use an invalid minimum stroke width: {}", min)
+		}
+		if !(max >= min) {
+			panic!("Tried to use a maximum stroke width ({}) smaller than the \
+				minimum stroke width ({})", max, min)
+		}
+
+		self.stroke_width = (min, max);
+	}
+
+	/// The distance between two consecutive touching events past which they
+	/// are drawn as separate strokes, if there is such a distance.
+	pub fn stroke_gap(&self) -> Option<f64> {
+		self.stroke_gap
+	}
+
+	/// Changes the distance between two consecutive touching events past which
+	/// they are drawn as separate strokes, or turns this off, with [`None`].
+	///
+	/// Devices sometimes miss the report of the pen leaving the screen, in
+	/// which case the next stroke would otherwise be connected to the previous
+	/// one by a straight line slashing across the signature. The distance is
+	/// normalized just like the position of the pen in an [event].
+	///
+	/// [event]: Event
+	pub fn set_stroke_gap(&mut self, gap: Option<f64>) {
+		if let Some(gap) = gap {
+			if !(gap > 0.0) {
+				panic!("Tried to use an invalid stroke gap: {}", gap)
+			}
+		}
+
+		self.stroke_gap = gap;
+	}
+
+	/// How the position of the pen maps on to the pixels of this canvas.
+	pub fn grid_mapping(&self) -> GridMapping {
+		self.mapping
+	}
+
+	/// Changes how the position of the pen maps on to the pixels of this
+	/// canvas, for the events processed from now on.
+	///
+	/// Canvases stretch the position of the pen across all of their pixels by
+	/// default, which is only right for devices whose input grid has the same
+	/// aspect ratio as their screen. The mapping for any other device is given
+	/// by [`GridMapping::from_capability()`].
+	pub fn set_grid_mapping(&mut self, mapping: GridMapping) {
+		self.mapping = mapping
+	}
+
+	/// The smallest number of events a stroke must have in order to be
+	/// exported, if short strokes are being left out of exports.
+	pub fn min_stroke_events(&self) -> Option<usize> {
+		self.min_stroke_events
+	}
+
+	/// Changes the smallest number of events a stroke must have in order to be
+	/// exported, or stops leaving short strokes out of exports if `None`.
+	///
+	/// This works the same way as [`EventPath::set_min_stroke_events()`].
+	/// Strokes that are left out are only skipped when rendering the images
+	/// and blobs exported from this canvas, and are kept everywhere else,
+	/// including in [`to_preview_bitmap()`]. By default, every stroke is
+	/// exported.
+	///
+	/// [`EventPath::set_min_stroke_events()`]: EventPath::set_min_stroke_events
+	/// [`to_preview_bitmap()`]: Self::to_preview_bitmap
+	pub fn set_min_stroke_events(&mut self, min: Option<usize>) {
+		self.min_stroke_events = min
+	}
+
+	/// This canvas with the strokes that are too short to be exported left
+	/// out of it, if there are any.
+	fn filtered(&self) -> Cow<Self> {
+		let min = match self.min_stroke_events {
+			Some(min) => min,
+			None => return Cow::Borrowed(self)
+		};
+		if self.strokes.iter().all(|(_, stroke)| stroke.len() >= min) {
+			return Cow::Borrowed(self)
+		}
+
+		let mut canvas = self.clone();
+		canvas.strokes.retain(|(_, stroke)| stroke.len() >= min);
+		canvas.redraw();
+
+		Cow::Owned(canvas)
+	}
+
 	/// Copies the image data in this canvas into a memory blob encoded as a
 	/// bitmap.
 	///
 	/// The format the bitmap will be in is full color 24-bpp RGB, in which
-	/// pixels marked as active will be painted black and pixels that are not
-	/// will be painted white.
+	/// pixels at full intensity will be painted black, pixels at no intensity
+	/// will be painted white, and pixels in between will be painted in shades
+	/// of gray.
 	pub fn to_bitmap(&self) -> Box<[u8]> {
-		let image = image::ImageBuffer::from_fn(
-			self.width,
-			self.height,
-			|x, y| {
-				let pixel = self.get(x, y).unwrap();
-				if pixel {
-					image::Rgb([0u8, 0u8, 0u8])
-				} else {
-					image::Rgb([255u8, 255u8, 255u8])
+		encode_bitmap(&self.to_image())
+	}
+
+	/// Copies the image data in this canvas into a memory blob encoded as a
+	/// bitmap, with a marker drawn where the pen is hovering, if it is.
+	///
+	/// The format of the bitmap is the same as the one used by
+	/// [`to_bitmap()`], and is meant for live previews of the canvas, rather
+	/// than for exporting its contents.
+	///
+	/// [`to_bitmap()`]: Self::to_bitmap
+	pub fn to_preview_bitmap(&self) -> Box<[u8]> {
+		let mut image = self.render(0, 0, self.width, self.height);
+		if let Some((x, y)) = self.hover {
+			/* Draw a small crosshair centered on the pen. */
+			for i in -HOVER_MARKER_RADIUS..=HOVER_MARKER_RADIUS {
+				for (px, py) in [(x as i64 + i, y as i64), (x as i64, y as i64 + i)] {
+					if px < 0 || py < 0 { continue }
+					if px >= i64::from(self.width) || py >= i64::from(self.height) {
+						continue
+					}
+
+					image.put_pixel(px as u32, py as u32, image::Luma([128]));
 				}
-			});
+			}
+		}
 
-		let mut buffer = Vec::new();
-		let mut encoder = image::codecs::bmp::BmpEncoder::new(&mut buffer);
+		encode_bitmap(&image)
+	}
 
-		encoder.encode(
-			image.as_raw(),
-			image.width(),
-			image.height(),
-			image::ColorType::Rgb8)
-			.unwrap();
+	/// The position of the pen over this canvas, in pixels, if it is hovering
+	/// over the screen without touching it.
+	pub fn hover_position(&self) -> Option<(u32, u32)> {
+		self.hover
+	}
 
-		buffer.into_boxed_slice()
+	/// Copies the image data in this canvas into a grayscale image.
+	///
+	/// Pixels at full intensity will be painted black, pixels at no intensity
+	/// will be painted white, and pixels in between will be painted in shades
+	/// of gray.
+	pub fn to_image(&self) -> image::GrayImage {
+		self.filtered().render(0, 0, self.width, self.height)
+	}
+
+	/// Copies the image data in this canvas into a grayscale image, enlarged
+	/// by the given factor in both dimensions.
+	///
+	/// The canvas only has as many pixels as the screen of the device, which
+	/// makes for a blocky signature once it gets printed. Rather than just
+	/// repeating pixels, the enlarged image is resampled with a smooth filter,
+	/// so that the edges of the strokes come out anti-aliased. A factor of
+	/// zero is treated as a factor of one.
+	pub fn to_image_scaled(&self, factor: u32) -> image::GrayImage {
+		upscale(&self.to_image(), factor)
+	}
+
+	/// Copies the image data in the [bounding box] of this canvas into a
+	/// memory blob encoded as a PNG image, enlarged by the given factor in
+	/// both dimensions.
+	///
+	/// The image is resampled the same way [`to_image_scaled()`] does it. If
+	/// this canvas is blank, this function returns [`None`].
+	///
+	/// [bounding box]: Self::bounding_box
+	/// [`to_image_scaled()`]: Self::to_image_scaled
+	pub fn to_png_cropped_scaled(&self, factor: u32) -> Option<Box<[u8]>> {
+		let canvas = self.filtered();
+		let (x, y, width, height) = canvas.bounding_box()?;
+
+		let image = canvas.render(x, y, width, height);
+		Some(encode_png(&upscale(&image, factor)))
+	}
+
+	/// Copies the image data in the [bounding box] of this canvas into a
+	/// memory blob encoded as a bitmap.
+	///
+	/// The format of the bitmap is the same as the one used by
+	/// [`to_bitmap()`]. If this canvas is blank, this function returns
+	/// [`None`].
+	///
+	/// [bounding box]: Self::bounding_box
+	/// [`to_bitmap()`]: Self::to_bitmap
+	pub fn to_bitmap_cropped(&self) -> Option<Box<[u8]>> {
+		let canvas = self.filtered();
+		let (x, y, width, height) = canvas.bounding_box()?;
+
+		let image = canvas.render(x, y, width, height);
+		Some(encode_bitmap(&image))
+	}
+
+	/// Copies the image data in this canvas into a memory blob encoded as a
+	/// PNG image.
+	///
+	/// The image will be in 8-bit grayscale, in which pixels at full intensity
+	/// will be painted black and pixels at no intensity will be painted white.
+	pub fn to_png(&self) -> Box<[u8]> {
+		encode_png(&self.to_image())
+	}
+
+	/// Copies the image data in the [bounding box] of this canvas into a
+	/// memory blob encoded as a PNG image.
+	///
+	/// The format of the image is the same as the one used by [`to_png()`].
+	/// If this canvas is blank, this function returns [`None`].
+	///
+	/// [bounding box]: Self::bounding_box
+	/// [`to_png()`]: Self::to_png
+	pub fn to_png_cropped(&self) -> Option<Box<[u8]>> {
+		let canvas = self.filtered();
+		let (x, y, width, height) = canvas.bounding_box()?;
+
+		let image = canvas.render(x, y, width, height);
+		Some(encode_png(&image))
+	}
+
+	/// The smallest rectangle containing all of the painted pixels in this
+	/// canvas, as a tuple of the position of its top left corner, its width
+	/// and its height, in that order.
+	///
+	/// If this canvas is blank, this function returns [`None`].
+	pub fn bounding_box(&self) -> Option<(u32, u32, u32, u32)> {
+		let mut bounds: Option<(u32, u32, u32, u32)> = None;
+		for y in 0..self.height {
+			for x in 0..self.width {
+				if self.intensity(x, y).unwrap() == 0 { continue }
+
+				bounds = Some(match bounds {
+					Some((x0, y0, x1, y1)) =>
+						(x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+					None => (x, y, x, y)
+				});
+			}
+		}
+
+		bounds.map(|(x0, y0, x1, y1)| (x0, y0, x1 - x0 + 1, y1 - y0 + 1))
+	}
+
+	/// Renders the given rectangular region of this canvas into a grayscale
+	/// image, in which painted pixels are dark and blank pixels are light.
+	fn render(&self, x: u32, y: u32, width: u32, height: u32) -> image::GrayImage {
+		image::ImageBuffer::from_fn(
+			width,
+			height,
+			|i, j| image::Luma([255 - self.intensity(x + i, y + j).unwrap()]))
 	}
 
 	/// Clears this canvas back into an unset state.
 	pub fn clear(&mut self) {
 		for byte in &mut self.buffer[..] { *byte = 0; }
+		for byte in &mut self.layers[..] { *byte = 0; }
+		self.strokes.clear();
+		self.undone.clear();
+		self.hover = None;
+		self.last = None;
+	}
+
+	/// Removes the last stroke drawn on this canvas, returning whether there
+	/// was a stroke to be removed.
+	///
+	/// A stroke is a sequence of events in which the pen is touching the
+	/// screen. Strokes that have been undone may be brought back with
+	/// [`redo_stroke()`], up until a new stroke is started.
+	///
+	/// [`redo_stroke()`]: Self::redo_stroke
+	pub fn undo_stroke(&mut self) -> bool {
+		let stroke = match self.strokes.pop() {
+			Some(stroke) => stroke,
+			None => return false
+		};
+		self.undone.push(stroke);
+		self.redraw();
+
+		true
+	}
+
+	/// Draws the strokes of this canvas back on to a blank canvas.
+	fn redraw(&mut self) {
+		for byte in &mut self.buffer[..] { *byte = 0; }
+		for byte in &mut self.layers[..] { *byte = 0; }
+		for i in 0..self.strokes.len() {
+			self.last = None;
+			self.drawing = self.strokes[i].0;
+			for j in 0..self.strokes[i].1.len() {
+				let event = self.strokes[i].1[j];
+				self.draw(event);
+			}
+		}
+		self.last = None;
+	}
+
+	/// Brings back the last stroke removed by [`undo_stroke()`], returning
+	/// whether there was a stroke to be brought back.
+	///
+	/// [`undo_stroke()`]: Self::undo_stroke
+	pub fn redo_stroke(&mut self) -> bool {
+		let stroke = match self.undone.pop() {
+			Some(stroke) => stroke,
+			None => return false
+		};
+
+		self.last = None;
+		self.drawing = stroke.0;
+		for event in &stroke.1 {
+			self.draw(*event);
+		}
+		self.last = None;
+		self.strokes.push(stroke);
+
+		true
+	}
+
+	/// The layer new strokes are drawn on.
+	pub fn layer(&self) -> u8 {
+		self.layer
+	}
+
+	/// Has the strokes started from now on be drawn on the given layer.
+	///
+	/// Layers tell apart the strokes of different people signing on the same
+	/// canvas, such as a patient and a witness, which can then be told apart by
+	/// their colors in [`to_color_image()`]. Everything is drawn on layer `0`
+	/// by default, and layers make no difference anywhere else.
+	///
+	/// [`to_color_image()`]: Self::to_color_image
+	pub fn begin_layer(&mut self, layer: u8) {
+		self.layer = layer;
+		self.last = None;
+	}
+
+	/// Copies the image data in this canvas into a color image, in which the
+	/// strokes on each layer are painted with the color at the index of their
+	/// layer in the given palette, over a white background.
+	///
+	/// Strokes on layers with no color in the palette are painted black.
+	pub fn to_color_image(&self, palette: &[[u8; 3]]) -> image::RgbImage {
+		let canvas = self.filtered();
+		image::RgbImage::from_fn(self.width, self.height, |x, y| {
+			let index = canvas.index(x, y).unwrap();
+			let intensity = f64::from(canvas.buffer[index]) / 255.0;
+			let color = palette
+				.get(usize::from(canvas.layers[index]))
+				.copied()
+				.unwrap_or([0, 0, 0]);
+
+			image::Rgb(color.map(|channel|
+				lerp(intensity, 255.0, f64::from(channel)).round() as u8))
+		})
 	}
 
 	/// Process the given event altering the canvas if needed.
+	///
+	/// The position of the pen is mapped on to the pixels of this canvas
+	/// according to its [grid mapping].
+	///
+	/// [grid mapping]: Self::grid_mapping
 	pub fn process(&mut self, event: Event) {
+		if event.touching() && self.is_gap(event) {
+			/* Treat the jump as the pen having been lifted. */
+			self.last = None;
+		}
 		if event.touching() {
-			let x = f64::from(self.width - 1) * event.x();
-			let y = f64::from(self.height - 1) * event.y();
-
-			let x = x.round() as u32;
-			let y = y.round() as u32;
-
-			self.set(x, y, true);
-			if let Some((last_x, last_y)) = self.last {
-				let mut ix = f64::from(last_x);
-				let mut iy = f64::from(last_y);
-
-				let dx = i64::from(x) - i64::from(last_x);
-				let dy = i64::from(y) - i64::from(last_y);
-
-				if dx != 0 || dy != 0 {
-					/* Trace a line to this point from the last point. */
-					if dx.abs() > dy.abs() {
-						/* Trace along X. */
-						let slope = dy as f64 / dx as f64;
-						for ax in 0..dx.abs() {
-							let x = i64::from(last_x) + ax * dx.signum();
-							let y = iy.round();
-
-							self.set(x as u32, y as u32, true);
-							iy += slope * dx.signum() as f64;
-						}
-					} else {
-						/* Trace along Y. */
-						let slope = dx as f64 / dy as f64;
-						for ay in 0..dy.abs() {
-							let x = ix.round();
-							let y = i64::from(last_y) + ay * dy.signum();
-
-							self.set(x as u32, y as u32, true);
-							ix += slope * dy.signum() as f64;
-						}
+			if self.last.is_none() {
+				/* This event starts a new stroke. */
+				self.strokes.push((self.layer, Vec::new()));
+				self.undone.clear();
+				self.drawing = self.layer;
+			}
+			if let Some((_, stroke)) = self.strokes.last_mut() {
+				stroke.push(event);
+			}
+		}
+
+		/* Only keep track of the pen while it is close but not touching, and
+		 * forget about it once it leaves proximity. */
+		self.hover = if event.hovering() && !event.touching() {
+			let (x, y) = self.position(&event);
+
+			Some((x.round() as u32, y.round() as u32))
+		} else {
+			None
+		};
+
+		self.draw(event);
+	}
+
+	/// Whether the given event is too far away from the last point of the
+	/// stroke being drawn for the two to be connected.
+	fn is_gap(&self, event: Event) -> bool {
+		if self.last.is_none() {
+			return false
+		}
+
+		match self.strokes.last().and_then(|(_, stroke)| stroke.last()) {
+			Some(last) => is_stroke_gap(last, &event, self.stroke_gap),
+			None => false
+		}
+	}
+
+	/// Draws the given event on to the canvas, connecting it to the last point
+	/// of the stroke, if there is one.
+	fn draw(&mut self, event: Event) {
+		if event.touching() {
+			let (x, y) = self.position(&event);
+			let pressure = event.pressure();
+
+			match self.last {
+				Some((last_x, last_y, last_pressure)) => {
+					/* Trace a line to this point from the last point, ramping
+					 * the pressure along the way so that strokes taper. */
+					let dx = x - last_x;
+					let dy = y - last_y;
+
+					let steps = dx.abs().max(dy.abs()).ceil().max(1.0) as u32;
+					for i in 0..=steps {
+						let s = f64::from(i) / f64::from(steps);
+						let radius = self.stroke_radius(
+							lerp(s, last_pressure, pressure));
+
+						self.stamp(
+							lerp(s, last_x, x),
+							lerp(s, last_y, y),
+							radius);
 					}
+				},
+				None => {
+					let radius = self.stroke_radius(pressure);
+					self.stamp(x, y, radius);
 				}
 			}
 
-			self.last = Some((x, y));
+			self.last = Some((x, y, pressure));
 		} else {
 			self.last = None
 		}
 	}
 
-	/// Gets the index of the byte and offset of the bit corresponding to the
-	/// pixel at the given coordinates.
-	fn index_offset(&self, x: u32, y: u32) -> Option<(usize, u8)> {
+	/// The position of the pen in the given event on this canvas, in pixels.
+	fn position(&self, event: &Event) -> (f64, f64) {
+		let (x, y) = self.mapping.map(event.x(), event.y());
+		(f64::from(self.width - 1) * x, f64::from(self.height - 1) * y)
+	}
+
+	/// The radius of the stroke at the given normalized pressure, in pixels.
+	fn stroke_radius(&self, pressure: f64) -> f64 {
+		let (min, max) = self.stroke_width;
+		lerp(
+			pressure.clamp(0.0, 1.0),
+			f64::from(min),
+			f64::from(max)) / 2.0
+	}
+
+	/// Paints the disc with the given center and radius on to the canvas.
+	///
+	/// The intensity of each pixel is given by how much of it is covered by
+	/// the disc, which smooths out the edges of the strokes. The pixel closest
+	/// to the center of the disc is always painted at full intensity, so that
+	/// even strokes thinner than a pixel leave a visible trace.
+	fn stamp(&mut self, x: f64, y: f64, radius: f64) {
+		let max_x = f64::from(self.width - 1);
+		let max_y = f64::from(self.height - 1);
+
+		self.paint(
+			x.round().clamp(0.0, max_x) as u32,
+			y.round().clamp(0.0, max_y) as u32,
+			255);
+
+		/* Pixels are treated as having unit area, such that a pixel whose
+		 * center lies right on the edge of the disc is about half covered. */
+		let x0 = (x - radius - 0.5).floor().clamp(0.0, max_x) as u32;
+		let x1 = (x + radius + 0.5).ceil().clamp(0.0, max_x) as u32;
+		let y0 = (y - radius - 0.5).floor().clamp(0.0, max_y) as u32;
+		let y1 = (y + radius + 0.5).ceil().clamp(0.0, max_y) as u32;
+
+		for py in y0..=y1 {
+			for px in x0..=x1 {
+				let dx = f64::from(px) - x;
+				let dy = f64::from(py) - y;
+
+				let distance = (dx * dx + dy * dy).sqrt();
+				let coverage = (radius + 0.5 - distance).clamp(0.0, 1.0);
+				if coverage > 0.0 {
+					self.paint(px, py, (coverage * 255.0).round() as u8);
+				}
+			}
+		}
+	}
+
+	/// Gets the index of the byte corresponding to the pixel at the given
+	/// coordinates.
+	fn index(&self, x: u32, y: u32) -> Option<usize> {
 		if x >= self.width || y >= self.height {
 			return None
 		}
 
 		let pixel = u128::from(y) * u128::from(self.width) + u128::from(x);
-		let index = pixel / 8;
-		let offset = pixel % 8;
+		let index = std::convert::TryFrom::try_from(pixel).unwrap();
 
-		let index = std::convert::TryFrom::try_from(index).unwrap();
-		let offset = offset as u8;
+		Some(index)
+	}
 
-		Some((index, offset))
+	/// Gets the intensity of the pixel at the given position.
+	///
+	/// A value of `0` indicates a pixel that is not painted at all, and a
+	/// value of `255` indicates a pixel that is painted at full intensity.
+	pub fn intensity(&self, x: u32, y: u32) -> Option<u8> {
+		let index = self.index(x, y)?;
+		Some(self.buffer[index])
 	}
 
 	/// Gets whether the pixel at the given position is set.
+	///
+	/// A pixel is considered to be set when it is painted with at least half
+	/// of the full intensity.
 	pub fn get(&self, x: u32, y: u32) -> Option<bool> {
-		let (index, offset) = self.index_offset(x, y)?;
-		Some(self.buffer[index] & (1u8 << offset) != 0)
+		Some(self.intensity(x, y)? >= 128)
 	}
 
 	/// Defines whether the pixel at the given position is set.
+	///
+	/// Set pixels are painted at full intensity, and pixels that are not set
+	/// are cleared.
 	pub fn set(&mut self, x: u32, y: u32, val: bool) {
-		let (index, offset) = self.index_offset(x, y).unwrap();
+		let index = self.index(x, y).unwrap();
+		self.buffer[index] = if val { 255 } else { 0 };
+	}
+
+	/// Paints the pixel at the given position with the given intensity, if it
+	/// is not already painted with a greater intensity.
+	///
+	/// Pixels painted over take on the layer of the stroke being drawn.
+	fn paint(&mut self, x: u32, y: u32, intensity: u8) {
+		let index = self.index(x, y).unwrap();
+		if intensity > self.buffer[index] {
+			self.buffer[index] = intensity;
+			self.layers[index] = self.drawing;
+		}
+	}
+}
+
+/// How the normalized position of the pen maps on to the pixels of a canvas.
+///
+/// The position of the pen is normalized separately along each axis of the
+/// input grid, so stretching it across the whole canvas only keeps strokes from
+/// being squashed when the input grid and the screen have the same aspect
+/// ratio. When they don't, the cells of the input grid are taken to be square,
+/// and the grid is fitted into the canvas along its longer side and centered
+/// along the other one.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GridMapping {
+	/// The fraction of each of the dimensions of the canvas the grid spans.
+	scale: (f64, f64),
+	/// The fraction of each of the dimensions of the canvas left before the
+	/// start of the grid.
+	offset: (f64, f64),
+}
+impl GridMapping {
+	/// The mapping that stretches the position of the pen across the whole
+	/// canvas.
+	pub const STRETCH: Self = Self { scale: (1.0, 1.0), offset: (0.0, 0.0) };
+
+	/// How far apart the aspect ratios of the input grid and of the screen may
+	/// be, relative to each other, for the grid to still be stretched.
+	const ASPECT_TOLERANCE: f64 = 0.01;
+
+	/// The mapping on to a canvas with the dimensions of the screen of a
+	/// device with the given capabilities.
+	pub fn from_capability(caps: &stu::Capability) -> Self {
+		Self::fit(
+			(caps.input_grid_width(), caps.input_grid_height()),
+			(caps.width(), caps.height()))
+	}
+
+	/// The mapping of an input grid with the given dimensions on to a screen
+	/// with the given dimensions.
+	fn fit(grid: (u32, u32), screen: (u32, u32)) -> Self {
+		if grid.0 == 0 || grid.1 == 0 || screen.0 == 0 || screen.1 == 0 {
+			return Self::STRETCH
+		}
+
+		let grid = f64::from(grid.0) / f64::from(grid.1);
+		let screen = f64::from(screen.0) / f64::from(screen.1);
+		if (grid / screen - 1.0).abs() <= Self::ASPECT_TOLERANCE {
+			return Self::STRETCH
+		}
 
-		if val {
-			self.buffer[index] |= 1u8 << offset;
+		if grid > screen {
+			let scale = screen / grid;
+			Self { scale: (1.0, scale), offset: (0.0, (1.0 - scale) / 2.0) }
 		} else {
-			self.buffer[index] &= !(1u8 << offset);
+			let scale = grid / screen;
+			Self { scale: (scale, 1.0), offset: ((1.0 - scale) / 2.0, 0.0) }
 		}
 	}
+
+	/// Maps the given normalized position of the pen to fractions of each of
+	/// the dimensions of the canvas.
+	fn map(&self, x: f64, y: f64) -> (f64, f64) {
+		(
+			self.offset.0 + x * self.scale.0,
+			self.offset.1 + y * self.scale.1
+		)
+	}
+}
+
+/// Whether the pen is taken to have been lifted between the two given events,
+/// for both of them touching the screen but lying further apart than the given
+/// distance, if there is one.
+fn is_stroke_gap(last: &Event, event: &Event, gap: Option<f64>) -> bool {
+	let gap = match gap {
+		Some(gap) => gap,
+		None => return false
+	};
+	if !last.touching() || !event.touching() {
+		return false
+	}
+
+	let dx = event.x() - last.x();
+	let dy = event.y() - last.y();
+
+	(dx * dx + dy * dy).sqrt() > gap
+}
+
+/// The reasons for which a [canvas] may fail to be created.
+///
+/// [canvas]: EventCanvas
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, thiserror::Error)]
+pub enum CanvasError {
+	/// The canvas was given no width or no height.
+	#[error("a canvas of {width}x{height} pixels is empty")]
+	Empty {
+		/// The width the canvas was given.
+		width: u32,
+		/// The height the canvas was given.
+		height: u32,
+	},
+	/// The canvas was given dimensions too large for it to fit in memory.
+	#[error("a canvas of {width}x{height} pixels is too large")]
+	TooLarge {
+		/// The width the canvas was given.
+		width: u32,
+		/// The height the canvas was given.
+		height: u32,
+	},
+}
+
+/// Enlarges the given grayscale image by the given factor, with a smooth filter.
+fn upscale(image: &image::GrayImage, factor: u32) -> image::GrayImage {
+	let factor = factor.max(1);
+	if factor == 1 {
+		return image.clone()
+	}
+
+	image::imageops::resize(
+		image,
+		image.width().saturating_mul(factor),
+		image.height().saturating_mul(factor),
+		image::imageops::FilterType::CatmullRom)
+}
+
+/// Encodes the given grayscale image as a full color 24-bpp RGB bitmap.
+fn encode_bitmap(image: &image::GrayImage) -> Box<[u8]> {
+	let image = image::ImageBuffer::from_fn(
+		image.width(),
+		image.height(),
+		|x, y| {
+			let pixel = image.get_pixel(x, y).0[0];
+			image::Rgb([pixel, pixel, pixel])
+		});
+
+	let mut buffer = Vec::new();
+	let mut encoder = image::codecs::bmp::BmpEncoder::new(&mut buffer);
+
+	encoder.encode(
+		image.as_raw(),
+		image.width(),
+		image.height(),
+		image::ColorType::Rgb8)
+		.unwrap();
+
+	buffer.into_boxed_slice()
 }
 
+/// Encodes the given grayscale image as a PNG image.
+fn encode_png(image: &image::GrayImage) -> Box<[u8]> {
+	use image::ImageEncoder;
+
+	let mut buffer = Vec::new();
+	let encoder = image::codecs::png::PngEncoder::new(&mut buffer);
+
+	encoder.write_image(
+		image.as_raw(),
+		image.width(),
+		image.height(),
+		image::ColorType::L8)
+		.unwrap();
+
+	buffer.into_boxed_slice()
+}
+
+/// The bytes every recording of an [`EventPath`] starts with.
+///
+/// [`EventPath`]: EventPath
+const RECORDING_MAGIC: [u8; 4] = *b"HCSG";
+
+/// The version of the format of the recordings of an [`EventPath`] written by
+/// this version of the program.
+///
+/// [`EventPath`]: EventPath
+const RECORDING_VERSION: u8 = 2;
+
 /// A structure for generating paths from events.
 #[derive(Debug, Clone, PartialEq)]
 pub struct EventPath {
 	/// Ordered list of events in this path, sorted by the time in which they
 	/// happened and were reported by the underlying API.
 	events: BTreeMap<Instant, Event>,
+	/// The strokes that have been undone, with the most recent one last.
+	undone: Vec<Vec<Event>>,
+	/// The method used to interpolate between the events when tracing.
+	interpolation: Interpolation,
+	/// The smallest number of events a stroke must have in order to be traced,
+	/// if short strokes are to be left out of the trace at all.
+	min_stroke_events: Option<usize>,
+	/// The distance past which consecutive touching events belong to separate
+	/// strokes.
+	stroke_gap: Option<f64>,
 }
 impl EventPath {
 	/// Creates a new, empty path.
 	pub fn new() -> Self {
 		Self {
-			events: Default::default()
+			events: Default::default(),
+			undone: Default::default(),
+			interpolation: Interpolation::Linear,
+			min_stroke_events: None,
+			stroke_gap: Some(DEFAULT_STROKE_GAP)
+		}
+	}
+
+	/// The method used to interpolate between the events when tracing.
+	pub fn interpolation(&self) -> Interpolation {
+		self.interpolation
+	}
+
+	/// Changes the method used to interpolate between the events when tracing.
+	pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+		self.interpolation = interpolation
+	}
+
+	/// The smallest number of events a stroke must have in order to be traced,
+	/// if short strokes are being left out of the trace.
+	pub fn min_stroke_events(&self) -> Option<usize> {
+		self.min_stroke_events
+	}
+
+	/// Changes the smallest number of events a stroke must have in order to be
+	/// traced, or stops leaving short strokes out of the trace if `None`.
+	///
+	/// This helps keep brief, accidental taps of the pen from showing up as
+	/// stray dots. Strokes that are left out are only skipped when tracing,
+	/// and are kept in the path, so this may be changed at any time. By
+	/// default, every stroke is traced.
+	pub fn set_min_stroke_events(&mut self, min: Option<usize>) {
+		self.min_stroke_events = min
+	}
+
+	/// The distance between two consecutive touching events past which they
+	/// belong to separate strokes, if there is such a distance.
+	pub fn stroke_gap(&self) -> Option<f64> {
+		self.stroke_gap
+	}
+
+	/// Changes the distance between two consecutive touching events past which
+	/// they belong to separate strokes, or turns this off, with [`None`].
+	///
+	/// This follows the same rule as [`EventCanvas::set_stroke_gap()`], and
+	/// both must be given the same distance for the strokes undone in one to
+	/// match the ones undone in the other. The pen is traced as having been
+	/// lifted between the two events on either side of a gap.
+	///
+	/// [`EventCanvas::set_stroke_gap()`]: EventCanvas::set_stroke_gap
+	pub fn set_stroke_gap(&mut self, gap: Option<f64>) {
+		if let Some(gap) = gap {
+			if !(gap > 0.0) {
+				panic!("Tried to use an invalid stroke gap: {}", gap)
+			}
+		}
+
+		self.stroke_gap = gap;
+	}
+
+	/// Inserts a new event into this path.
+	///
+	/// If this path had already registered an event that happened at the same
+	/// time as the given event, this event will replace it in the path and
+	/// this function will return the event that was replaced.
+	pub fn process(&mut self, event: Event) -> Option<Event> {
+		let starts_stroke = event.touching() && self.events
+			.values()
+			.next_back()
+			.map(|last| !last.touching() || is_stroke_gap(last, &event, self.stroke_gap))
+			.unwrap_or(true);
+		if starts_stroke {
+			self.undone.clear();
+		}
+
+		self.events.insert(event.time(), event)
+	}
+
+	/// The number of events in this path.
+	pub fn len(&self) -> usize {
+		self.events.len()
+	}
+
+	/// Whether there are no events in this path.
+	pub fn is_empty(&self) -> bool {
+		self.events.is_empty()
+	}
+
+	/// Iterates over the events in this path, in the order in which they
+	/// happened.
+	pub fn iter(&self) -> impl Iterator<Item = &Event> + '_ {
+		self.events.values()
+	}
+
+	/// Iterates over the events in this path in which the pen was hovering
+	/// over the screen without touching it, in the order in which they
+	/// happened.
+	///
+	/// These events are kept in the path but never contribute to its trace,
+	/// which only ever follows the pen while it is touching the screen.
+	pub fn hovering(&self) -> impl Iterator<Item = &Event> + '_ {
+		self.events.values()
+			.filter(|event| event.hovering() && !event.touching())
+	}
+
+	/// The normalized position of the pen, if the last event in this path
+	/// has it hovering over the screen without touching it.
+	pub fn hover_position(&self) -> Option<(f64, f64)> {
+		self.events.values()
+			.next_back()
+			.filter(|event| event.hovering() && !event.touching())
+			.map(|event| (event.x(), event.y()))
+	}
+
+	/// Clears all of the events in this path.
+	pub fn clear(&mut self) {
+		self.events.clear();
+		self.undone.clear();
+	}
+
+	/// Removes the last stroke in this path, returning whether there was a
+	/// stroke to be removed.
+	///
+	/// A stroke is a sequence of events in which the pen is touching the
+	/// screen, split wherever the pen jumps further than the [stroke gap].
+	/// Along with the stroke, all of the events that came after it are
+	/// removed as well. Strokes that have been undone may be brought back with
+	/// [`redo_stroke()`], up until a new stroke is started.
+	///
+	/// [stroke gap]: Self::stroke_gap
+	/// [`redo_stroke()`]: Self::redo_stroke
+	pub fn undo_stroke(&mut self) -> bool {
+		/* Find the event that starts the last stroke. */
+		let mut start = None;
+		let mut next: Option<&Event> = None;
+		for (time, event) in self.events.iter().rev() {
+			if event.touching() {
+				let gap = next
+					.map(|next| is_stroke_gap(event, next, self.stroke_gap))
+					.unwrap_or(false);
+				if start.is_some() && gap {
+					break
+				}
+
+				start = Some(*time);
+			} else if start.is_some() {
+				break
+			}
+			next = Some(event);
+		}
+		let start = match start {
+			Some(start) => start,
+			None => return false
+		};
+
+		let removed = self.events.split_off(&start);
+		self.undone.push(removed.into_values().collect());
+
+		true
+	}
+
+	/// Brings back the last stroke removed by [`undo_stroke()`], returning
+	/// whether there was a stroke to be brought back.
+	///
+	/// [`undo_stroke()`]: Self::undo_stroke
+	pub fn redo_stroke(&mut self) -> bool {
+		match self.undone.pop() {
+			Some(stroke) => {
+				for event in stroke {
+					self.events.insert(event.time(), event);
+				}
+				true
+			},
+			None => false
+		}
+	}
+
+	/// Computes aggregate statistics over the strokes in this path.
+	///
+	/// Only events in which the pen is touching the screen are taken into
+	/// account. Refer to [`SignatureStats`] for the units of each statistic.
+	///
+	/// [`SignatureStats`]: SignatureStats
+	pub fn statistics(&self) -> SignatureStats {
+		let mut stats = SignatureStats::default();
+		let mut pressure_sum = 0.0;
+		let mut touches = 0usize;
+		let mut last: Option<&Event> = None;
+
+		for event in self.events.values() {
+			let gap = last
+				.map(|last| is_stroke_gap(last, event, self.stroke_gap))
+				.unwrap_or(false);
+			if !event.touching() || gap {
+				if last.is_some() {
+					stats.pen_lifts += 1;
+				}
+				last = None;
+				if !event.touching() { continue }
+			}
+
+			match last {
+				Some(last) => {
+					let dx = f64::from(event.raw_x()) - f64::from(last.raw_x());
+					let dy = f64::from(event.raw_y()) - f64::from(last.raw_y());
+					stats.length += (dx * dx + dy * dy).sqrt();
+				},
+				None => stats.strokes += 1
+			}
+			last = Some(event);
+
+			touches += 1;
+			pressure_sum += event.pressure();
+			stats.peak_pressure = stats.peak_pressure.max(event.pressure());
+
+			let (x, y) = (event.x(), event.y());
+			stats.bounds = Some(match stats.bounds {
+				Some((left, top, right, bottom)) =>
+					(left.min(x), top.min(y), right.max(x), bottom.max(y)),
+				None => (x, y, x, y)
+			});
+		}
+
+		if touches > 0 {
+			stats.mean_pressure = pressure_sum / touches as f64;
+		}
+		stats
+	}
+
+	/// Writes a recording of the events in this path to the given writer, from
+	/// which it may be brought back later with [`load()`].
+	///
+	/// # Format
+	/// Recordings start with the four bytes `HCSG`, followed by a byte with
+	/// the version of the format, which is currently `2`, followed by the
+	/// number of events as a 32-bit integer. Every event is then written out
+	/// in order, as its time offset from the first event in nanoseconds as a
+	/// 64-bit integer, its normalized position and pressure as three 64-bit
+	/// floats, its raw position and pressure as three 16-bit integers, the
+	/// resolution of the device as three 32-bit integers, a byte of flags and
+	/// the [option], [time count] and [sequence number] of the event as three
+	/// 16-bit integers. The lowest bit of the flags is set if the pen was
+	/// touching the screen, the second lowest bit is set if the pen was
+	/// hovering over it, and the next three bits are set if the event has an
+	/// option, a time count and a sequence number, respectively, which are
+	/// written out as zero when they are missing. All of the numbers are
+	/// little endian.
+	///
+	/// Version `1` of the format is the same, except that it has no option,
+	/// time count or sequence number, nor any of the flags for them.
+	///
+	/// [option]: stu::Event::option
+	/// [time count]: stu::Event::time_count
+	/// [sequence number]: stu::Event::sequence
+	///
+	/// [`load()`]: Self::load
+	pub fn save<W: Write>(&self, mut writer: W) -> io::Result<()> {
+		let count = u32::try_from(self.events.len())
+			.map_err(|_| io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"too many events in the path to be recorded"))?;
+
+		writer.write_all(&RECORDING_MAGIC)?;
+		writer.write_all(&[RECORDING_VERSION])?;
+		writer.write_all(&count.to_le_bytes())?;
+
+		let epoch = match self.events.keys().next() {
+			Some(epoch) => *epoch,
+			None => return Ok(())
+		};
+		for event in self.events.values() {
+			let event = event.to_serializable(epoch);
+			let offset = u64::try_from(event.offset.as_nanos())
+				.unwrap_or(u64::MAX);
+
+			writer.write_all(&offset.to_le_bytes())?;
+			writer.write_all(&event.position.0.to_le_bytes())?;
+			writer.write_all(&event.position.1.to_le_bytes())?;
+			writer.write_all(&event.position.2.to_le_bytes())?;
+			writer.write_all(&event.raw.0.to_le_bytes())?;
+			writer.write_all(&event.raw.1.to_le_bytes())?;
+			writer.write_all(&event.raw.2.to_le_bytes())?;
+			writer.write_all(&event.resolution.0.to_le_bytes())?;
+			writer.write_all(&event.resolution.1.to_le_bytes())?;
+			writer.write_all(&event.resolution.2.to_le_bytes())?;
+			writer.write_all(&[
+				u8::from(event.touching)
+					| u8::from(event.close) << 1
+					| u8::from(event.option.is_some()) << 2
+					| u8::from(event.time_count.is_some()) << 3
+					| u8::from(event.sequence.is_some()) << 4])?;
+			writer.write_all(&event.option.unwrap_or(0).to_le_bytes())?;
+			writer.write_all(&event.time_count.unwrap_or(0).to_le_bytes())?;
+			writer.write_all(&event.sequence.unwrap_or(0).to_le_bytes())?;
 		}
+
+		Ok(())
 	}
-	/// Inserts a new event into this path.
+
+	/// Reads a path back from a recording written by [`save()`].
 	///
-	/// If this path had already registered an event that happened at the same
-	/// time as the given event, this event will replace it in the path and
-	/// this function will return the event that was replaced.
-	pub fn process(&mut self, event: Event) -> Option<Event> {
-		self.events.insert(event.time(), event)
-	}
-	/// Clears all of the events in this path.
-	pub fn clear(&mut self) {
-		self.events.clear()
+	/// The events in the path keep the same timing they had when they were
+	/// recorded, relative to each other, so that tracing the loaded path
+	/// yields the same results as tracing the original one. The first event
+	/// is placed at the time the path is loaded.
+	///
+	/// Recordings whose events are not in strictly increasing order of time
+	/// are rejected as invalid.
+	///
+	/// [`save()`]: Self::save
+	pub fn load<R: Read>(mut reader: R) -> io::Result<Self> {
+		let invalid = |what| io::Error::new(io::ErrorKind::InvalidData, what);
+
+		let mut magic = [0; 4];
+		reader.read_exact(&mut magic)?;
+		if magic != RECORDING_MAGIC {
+			return Err(invalid("not a signature recording"))
+		}
+		let [version] = read_bytes(&mut reader)?;
+		if version != 1 && version != RECORDING_VERSION {
+			return Err(invalid("unsupported signature recording version"))
+		}
+		let count = u32::from_le_bytes(read_bytes(&mut reader)?);
+
+		let epoch = Instant::now();
+		let mut path = Self::new();
+		let mut previous = None;
+		for _ in 0..count {
+			let offset = u64::from_le_bytes(read_bytes(&mut reader)?);
+			if previous.map(|previous| offset <= previous).unwrap_or(false) {
+				return Err(invalid("signature recording events are out of order"))
+			}
+			previous = Some(offset);
+			let event = SerializableEvent {
+				offset: Duration::from_nanos(offset),
+				position: (
+					f64::from_le_bytes(read_bytes(&mut reader)?),
+					f64::from_le_bytes(read_bytes(&mut reader)?),
+					f64::from_le_bytes(read_bytes(&mut reader)?)),
+				raw: (
+					u16::from_le_bytes(read_bytes(&mut reader)?),
+					u16::from_le_bytes(read_bytes(&mut reader)?),
+					u16::from_le_bytes(read_bytes(&mut reader)?)),
+				resolution: (
+					u32::from_le_bytes(read_bytes(&mut reader)?),
+					u32::from_le_bytes(read_bytes(&mut reader)?),
+					u32::from_le_bytes(read_bytes(&mut reader)?)),
+				touching: false,
+				close: false,
+				option: None,
+				time_count: None,
+				sequence: None
+			};
+			let [flags] = read_bytes(&mut reader)?;
+			let mut extra = [None; 3];
+			if version >= 2 {
+				for (i, extra) in extra.iter_mut().enumerate() {
+					let value = u16::from_le_bytes(read_bytes(&mut reader)?);
+					if flags & (4 << i) != 0 {
+						*extra = Some(value);
+					}
+				}
+			}
+			let event = SerializableEvent {
+				touching: flags & 1 != 0,
+				close: flags & 2 != 0,
+				option: extra[0],
+				time_count: extra[1],
+				sequence: extra[2],
+				..event
+			};
+
+			path.events.insert(
+				epoch + event.offset,
+				Event::from_serializable(&event, epoch));
+		}
+
+		Ok(path)
 	}
 }
 impl IntoTrace for EventPath {
 	type Trace<'a> = EventTrace<'a>;
 	fn trace(&self) -> EventTrace {
+		let min = self.min_stroke_events.unwrap_or(0);
+
+		/* Hold the events of the current stroke back until it ends, so that
+		 * it can be left out if it turns out to be too short. */
+		let mut events = Vec::with_capacity(self.events.len());
+		let mut stroke: Vec<&Event> = Vec::new();
+		for event in self.events.values() {
+			let gap = stroke.last()
+				.map(|last| is_stroke_gap(last, event, self.stroke_gap))
+				.unwrap_or(false);
+			if event.touching() && !gap {
+				stroke.push(event);
+				continue
+			}
+
+			if stroke.len() >= min {
+				events.append(&mut stroke);
+			}
+			stroke.clear();
+			if event.touching() {
+				stroke.push(event);
+			} else {
+				events.push(event);
+			}
+		}
+		if stroke.len() >= min {
+			events.append(&mut stroke);
+		}
+
+		/* The pen is lifted across the gaps between strokes. */
+		let lifts = events.windows(2)
+			.map(|pair| is_stroke_gap(pair[0], pair[1], self.stroke_gap))
+			.collect::<Vec<_>>()
+			.into_boxed_slice();
+
 		EventTrace {
-			events: self.events
-				.values()
-				.collect::<Vec<_>>()
-				.into_boxed_slice()
+			events: events.into_boxed_slice(),
+			lifts,
+			interpolation: self.interpolation
 		}
 	}
 }
@@ -245,6 +1313,48 @@ impl Default for EventPath {
 	}
 }
 
+/// Aggregate statistics over the strokes of a signature, as computed by
+/// [`EventPath::statistics()`].
+///
+/// [`EventPath::statistics()`]: EventPath::statistics
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct SignatureStats {
+	/// The number of strokes, that is, of times the pen touched the screen.
+	///
+	/// The pen jumping farther than the [stroke gap] between two events is
+	/// counted as it having been lifted and having touched the screen again.
+	///
+	/// [stroke gap]: EventPath::stroke_gap
+	pub strokes: usize,
+	/// The number of times the pen was lifted off the screen after a stroke.
+	///
+	/// This is either the same as the number of strokes or one less than it,
+	/// depending on whether the pen was still touching the screen when the
+	/// last event was recorded.
+	pub pen_lifts: usize,
+	/// The total length of all of the strokes, in raw units of the input grid
+	/// of the device, in which both axes share the same scale.
+	pub length: f64,
+	/// The average pressure over all of the events in the strokes, normalized
+	/// to the range from `0.0` to `1.0`.
+	pub mean_pressure: f64,
+	/// The highest pressure over all of the events in the strokes, normalized
+	/// to the range from `0.0` to `1.0`.
+	pub peak_pressure: f64,
+	/// The smallest rectangle containing all of the events in the strokes, as
+	/// its left, top, right and bottom edges, normalized to the range from
+	/// `0.0` to `1.0`, or [`None`] if there are no strokes.
+	pub bounds: Option<(f64, f64, f64, f64)>,
+}
+
+/// Reads exactly as many bytes as fit in the returned array.
+fn read_bytes<R: Read, const N: usize>(reader: &mut R) -> io::Result<[u8; N]> {
+	let mut bytes = [0; N];
+	reader.read_exact(&mut bytes)?;
+
+	Ok(bytes)
+}
+
 /// A tracing along a path generated by [`EventPath`].
 ///
 /// [`EventPath`]: EventPath
@@ -253,7 +1363,12 @@ pub struct EventTrace<'a> {
 	/// A list of events, sorted by the time they happened. This is a list
 	/// rather than other kinds of sorted containers because it allows for us to
 	/// uniformly access its elements, which avoids the clustering of events.
-	events: Box<[&'a Event]>
+	events: Box<[&'a Event]>,
+	/// Whether the pen is lifted between each event and the one following it,
+	/// even though both of them are touching the screen.
+	lifts: Box<[bool]>,
+	/// The method used to interpolate between the events.
+	interpolation: Interpolation,
 }
 impl Trace for EventTrace<'_> {
 	fn get<E>(&self, t: f64, buffer: &mut E) -> usize
@@ -263,49 +1378,177 @@ impl Trace for EventTrace<'_> {
 			buffer.extend(Some(Point {
 				x: self.events[0].x(),
 				y: self.events[0].y(),
-				touch: self.events[0].touching()
+				touch: self.events[0].touching(),
+				pressure: self.events[0].pressure()
 			}));
 			return 1
 		}
 
 		let t = t.clamp(0.0, 1.0);
-		let t = t * (self.events.len() - 1) as f64;
+		let (i, j, f) = self.locate(t);
 
-		let f = t.fract();
+		let a = self.events[i];
+		let b = self.events[j];
 
-		let i = t.floor();
-		let j = t.ceil();
+		let (x, y) = match self.interpolation {
+			Interpolation::Linear => (
+				lerp(f, a.x(), b.x()),
+				lerp(f, a.y(), b.y())
+			),
+			Interpolation::CatmullRom => {
+				/* Use the neighbors of the segment as the outer control
+				 * points, repeating the end points at the edges. */
+				let p = self.events[i.saturating_sub(1)];
+				let q = self.events[(j + 1).min(self.events.len() - 1)];
 
-		let a = self.events[i as usize];
-		let b = self.events[j as usize];
+				(
+					catmull_rom(f, p.x(), a.x(), b.x(), q.x()),
+					catmull_rom(f, p.y(), a.y(), b.y(), q.y())
+				)
+			}
+		};
 
 		buffer.extend(Some(Point {
-			x: lerp(f, a.x(), b.x()),
-			y: lerp(f, a.y(), b.y()),
-			touch: a.touching()
+			x: x.clamp(0.0, 1.0),
+			y: y.clamp(0.0, 1.0),
+			touch: a.touching() && !self.lifts.get(i).copied().unwrap_or(false),
+			pressure: lerp(f, a.pressure(), b.pressure())
 		}));
 		1
 	}
 }
 
+impl EventTrace<'_> {
+	/// Finds the pair of events surrounding the given time along the trace,
+	/// along with how far between the two events the time lies.
+	///
+	/// The time along the trace maps linearly on to the time elapsed between
+	/// the first and the last events, so that the pauses and the changes in
+	/// speed of the original pen strokes are preserved.
+	fn locate(&self, t: f64) -> (usize, usize, f64) {
+		let first = self.events[0].time();
+		let last = self.events[self.events.len() - 1].time();
+
+		let duration = last.duration_since(first);
+		if duration.is_zero() {
+			/* All of the events happened at the same time, so fall back to
+			 * spreading them out uniformly. */
+			let t = t * (self.events.len() - 1) as f64;
+			return (t.floor() as usize, t.ceil() as usize, t.fract())
+		}
+
+		let target = first + duration.mul_f64(t);
+		let j = self.events
+			.partition_point(|event| event.time() <= target)
+			.clamp(1, self.events.len() - 1);
+		let i = j - 1;
+
+		let a = self.events[i].time();
+		let b = self.events[j].time();
+
+		let span = b.duration_since(a).as_secs_f64();
+		let f = if span > 0.0 {
+			(target.saturating_duration_since(a).as_secs_f64() / span).clamp(0.0, 1.0)
+		} else {
+			0.0
+		};
+
+		(i, j, f)
+	}
+}
+
+/// The methods with which the points between two events may be interpolated.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Interpolation {
+	/// Points are joined by straight lines.
+	Linear,
+	/// Points are joined by a uniform Catmull-Rom spline, which passes through
+	/// all of the points while smoothing out the corners between them.
+	CatmullRom,
+}
+impl Default for Interpolation {
+	fn default() -> Self {
+		Self::Linear
+	}
+}
+
 fn lerp(s: f64, a: f64, b: f64) -> f64 {
 	(1.0 - s) * a + s * b
 }
 
+/// Evaluates the segment between `b` and `c` of a uniform Catmull-Rom spline
+/// whose control points are `a`, `b`, `c` and `d`.
+fn catmull_rom(s: f64, a: f64, b: f64, c: f64, d: f64) -> f64 {
+	let s2 = s * s;
+	let s3 = s2 * s;
+
+	0.5 * (2.0 * b
+		+ (c - a) * s
+		+ (2.0 * a - 5.0 * b + 4.0 * c - d) * s2
+		+ (3.0 * b - a - 3.0 * c + d) * s3)
+}
+
 /// Structure that represents a path generated from a bitmap rather than from
 /// a list of sign pad events.
 #[derive(Debug, Clone)]
 pub struct BitmapPath {
-	image: image::GrayImage
+	/// The high-contrast image, in which the pixels that are part of the
+	/// signature are black and all other pixels are white.
+	image: image::GrayImage,
+	/// The image the high-contrast one was made from, with its intensities
+	/// inverted if the path is inverted.
+	source: image::GrayImage,
+	/// Whether images of this path keep the intensities of the source image.
+	grayscale: bool,
+	/// The intensity below which pixels are considered part of the signature.
+	threshold: u8,
+	/// Whether the intensities of the source image were inverted before the
+	/// threshold was applied.
+	inverted: bool,
 }
 impl BitmapPath {
-	/// Creates a new bitmap path from the given image.
-	pub fn new(mut image: image::GrayImage) -> Self {
+	/// The threshold used by default by [`new()`].
+	///
+	/// [`new()`]: Self::new
+	pub const DEFAULT_THRESHOLD: u8 = 20;
+
+	/// The margin kept around the signature by default when trimming, in
+	/// pixels.
+	pub const DEFAULT_TRIM_MARGIN: u32 = 8;
+
+	/// Creates a new bitmap path from the given image, using the default
+	/// threshold.
+	pub fn new(image: image::GrayImage) -> Self {
+		Self::with_threshold(image, Self::DEFAULT_THRESHOLD)
+	}
+
+	/// Creates a new bitmap path from the given image, in which pixels darker
+	/// than the given threshold are considered part of the signature.
+	pub fn with_threshold(image: image::GrayImage, threshold: u8) -> Self {
+		Self::with_polarity(image, threshold, false)
+	}
+
+	/// Creates a new bitmap path from the given image, optionally inverting
+	/// the intensities of its pixels before applying the given threshold.
+	///
+	/// Inverting is meant for images with light strokes on a dark background,
+	/// such as some scans, which would otherwise have their background traced
+	/// instead of the signature.
+	pub fn with_polarity(
+		mut image: image::GrayImage,
+		threshold: u8,
+		inverted: bool) -> Self {
+
+		if inverted {
+			image::imageops::invert(&mut image);
+		}
+		let source = image.clone();
+
 		/* Force the image into a high-contrast format. */
 		for i in 0..image.height() {
 			for j in 0..image.width() {
 				let pixel = image.get_pixel_mut(j, i);
-				if pixel.0[0] < 20 {
+				if pixel.0[0] < threshold {
 					*pixel = Luma([0])
 				} else {
 					*pixel = Luma([255])
@@ -313,7 +1556,176 @@ impl BitmapPath {
 			}
 		}
 
-		Self { image }
+		Self { image, source, grayscale: false, threshold, inverted }
+	}
+
+	/// Creates a new bitmap path from the given image, with a threshold picked
+	/// automatically from the histogram of the image using Otsu's method.
+	///
+	/// This works best for scanned signatures, whose lighting conditions may
+	/// vary wildly from one scan to the next.
+	pub fn with_otsu_threshold(image: image::GrayImage) -> Self {
+		let threshold = otsu_threshold(&image);
+		Self::with_threshold(image, threshold)
+	}
+
+	/// The intensity below which pixels are considered part of the signature.
+	pub fn threshold(&self) -> u8 {
+		self.threshold
+	}
+
+	/// Whether the intensities of the source image were inverted before the
+	/// threshold was applied.
+	pub fn inverted(&self) -> bool {
+		self.inverted
+	}
+
+	/// Crops this path down to the smallest rectangle containing all of the
+	/// pixels in the signature, keeping a margin of the given number of pixels
+	/// around it, wherever the image is large enough to allow for it.
+	///
+	/// Since traces are normalized to the dimensions of the image, the trace of
+	/// the trimmed path spans the whole area it is played back to, rather than
+	/// just the part of it the signature was sitting on. If there is no
+	/// signature in this path, it is returned unchanged.
+	pub fn trimmed(&self, margin: u32) -> Self {
+		let mut bounds: Option<(u32, u32, u32, u32)> = None;
+		for (x, y, pixel) in self.image.enumerate_pixels() {
+			if pixel.0[0] != 0 { continue }
+
+			bounds = Some(match bounds {
+				Some((x0, y0, x1, y1)) =>
+					(x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+				None => (x, y, x, y)
+			});
+		}
+		let (x0, y0, x1, y1) = match bounds {
+			Some(bounds) => bounds,
+			None => return self.clone()
+		};
+
+		let x0 = x0.saturating_sub(margin);
+		let y0 = y0.saturating_sub(margin);
+		let x1 = x1.saturating_add(margin).min(self.image.width() - 1);
+		let y1 = y1.saturating_add(margin).min(self.image.height() - 1);
+
+		let crop = |image| image::imageops::crop_imm(
+			image,
+			x0,
+			y0,
+			x1 - x0 + 1,
+			y1 - y0 + 1).to_image();
+
+		Self {
+			image: crop(&self.image),
+			source: crop(&self.source),
+			grayscale: self.grayscale,
+			threshold: self.threshold,
+			inverted: self.inverted
+		}
+	}
+
+	/// Thins the strokes in this path down to lines one pixel wide, running
+	/// along the middle of the strokes, using the Zhang-Suen algorithm.
+	///
+	/// Thick strokes, such as the ones in scanned signatures, otherwise get
+	/// traced as a dense mass of points, which makes for slow and blotchy
+	/// playbacks. Thinning changes the look of the signature, however, since
+	/// the width of its strokes is lost.
+	pub fn thinned(&self) -> Self {
+		let width = self.image.width() as usize;
+		let height = self.image.height() as usize;
+
+		let mut set = self.image.pixels()
+			.map(|pixel| pixel.0[0] == 0)
+			.collect::<Vec<_>>();
+		let get = |set: &[bool], x: usize, y: usize, dx: isize, dy: isize| {
+			let x = x as isize + dx;
+			let y = y as isize + dy;
+			if x < 0 || y < 0 || x >= width as isize || y >= height as isize {
+				false
+			} else {
+				set[y as usize * width + x as usize]
+			}
+		};
+
+		let mut removed = Vec::new();
+		loop {
+			let mut changed = false;
+			for step in 0..2 {
+				for y in 0..height {
+					for x in 0..width {
+						if !set[y * width + x] { continue }
+
+						/* The neighbors, clockwise, starting from the top. */
+						let n = [
+							get(&set, x, y, 0, -1),
+							get(&set, x, y, 1, -1),
+							get(&set, x, y, 1, 0),
+							get(&set, x, y, 1, 1),
+							get(&set, x, y, 0, 1),
+							get(&set, x, y, -1, 1),
+							get(&set, x, y, -1, 0),
+							get(&set, x, y, -1, -1),
+						];
+						let count = n.iter().filter(|n| **n).count();
+						let transitions = (0..8)
+							.filter(|i| !n[*i] && n[(*i + 1) % 8])
+							.count();
+						let (a, b) = if step == 0 {
+							(n[0] && n[2] && n[4], n[2] && n[4] && n[6])
+						} else {
+							(n[0] && n[2] && n[6], n[0] && n[4] && n[6])
+						};
+
+						if (2..=6).contains(&count) && transitions == 1 && !a && !b {
+							removed.push(y * width + x);
+						}
+					}
+				}
+
+				changed |= !removed.is_empty();
+				for i in removed.drain(..) {
+					set[i] = false;
+				}
+			}
+
+			if !changed { break }
+		}
+
+		let image = image::GrayImage::from_fn(
+			width as u32,
+			height as u32,
+			|x, y| if set[y as usize * width + x as usize] {
+				Luma([0])
+			} else {
+				Luma([255])
+			});
+
+		Self {
+			image,
+			source: self.source.clone(),
+			grayscale: self.grayscale,
+			threshold: self.threshold,
+			inverted: self.inverted
+		}
+	}
+
+	/// Whether images of this path keep the intensities of the source image,
+	/// rather than being made to high contrast.
+	pub fn grayscale(&self) -> bool {
+		self.grayscale
+	}
+
+	/// Changes whether images of this path keep the intensities of the source
+	/// image, rather than being made to high contrast.
+	///
+	/// This is meant for showing the signature as it was in the source image,
+	/// smooth edges and all, rather than for painting it, as the trace of the
+	/// path always follows the high-contrast image.
+	pub fn with_grayscale(mut self, grayscale: bool) -> Self {
+		self.grayscale = grayscale;
+		self
 	}
 
 	/// Width of the canvas.
@@ -322,18 +1734,33 @@ impl BitmapPath {
 	/// Height of the canvas.
 	pub fn height(&self) -> u32 { self.image.height() }
 
+	/// Copies the image data in this canvas into a grayscale image, in which
+	/// pixels that are part of the signature are painted black and all other
+	/// pixels are painted white, unless this path keeps the intensities of the
+	/// source image.
+	pub fn to_image(&self) -> image::GrayImage {
+		if self.grayscale {
+			self.source.clone()
+		} else {
+			self.image.clone()
+		}
+	}
+
 	/// Copies the image data in this canvas into a memory blob encoded as a
 	/// bitmap.
 	///
 	/// The format the bitmap will be in is full color 24-bpp RGB, in which
 	/// pixels marked as active will be painted black and pixels that are not
-	/// will be painted white.
+	/// will be painted white, just like in [`to_image()`].
+	///
+	/// [`to_image()`]: Self::to_image
 	pub fn to_bitmap(&self) -> Box<[u8]> {
+		let source = self.to_image();
 		let image = image::ImageBuffer::from_fn(
-			self.image.width(),
-			self.image.height(),
+			source.width(),
+			source.height(),
 			|x, y| {
-				let pixel = self.image.get_pixel(x, y).0[0];
+				let pixel = source.get_pixel(x, y).0[0];
 				image::Rgb([pixel, pixel, pixel])
 			});
 
@@ -350,30 +1777,133 @@ impl BitmapPath {
 		buffer.into_boxed_slice()
 	}
 }
+
+/// Picks the threshold that best separates the pixels in the given image into
+/// two classes, by maximizing the variance between the two classes.
+fn otsu_threshold(image: &image::GrayImage) -> u8 {
+	let mut histogram = [0u64; 256];
+	for pixel in image.pixels() {
+		histogram[usize::from(pixel.0[0])] += 1;
+	}
+
+	let total = histogram.iter().sum::<u64>() as f64;
+	let sum = histogram.iter()
+		.enumerate()
+		.map(|(i, count)| i as f64 * *count as f64)
+		.sum::<f64>();
+
+	let mut best = (0.0, 0);
+	let mut weight = 0.0;
+	let mut partial = 0.0;
+	for (i, count) in histogram.iter().enumerate() {
+		weight += *count as f64;
+		partial += i as f64 * *count as f64;
+		if weight == 0.0 { continue }
+		if weight == total { break }
+
+		let mean_a = partial / weight;
+		let mean_b = (sum - partial) / (total - weight);
+		let variance = weight * (total - weight) * (mean_a - mean_b).powi(2);
+
+		if variance > best.0 {
+			best = (variance, i);
+		}
+	}
+
+	/* Pixels at the intensity picked by the method belong to the darker
+	 * class, so the threshold lies right above it. */
+	(best.1 + 1).min(255) as u8
+}
+
 impl IntoTrace for BitmapPath {
 	type Trace<'a> = BitmapTrace;
 	fn trace<'a>(&'a self) -> Self::Trace<'a> {
+		let width = self.image.width();
+		let height = self.image.height();
+
+		let index = |x: u32, y: u32| y as usize * width as usize + x as usize;
+		let mut visited = vec![false; width as usize * height as usize];
+
+		/* Walk each connected group of dark pixels depth-first, so that the
+		 * points along a stroke come out in the order they would have been
+		 * drawn, and the pen only has to be lifted between groups. */
 		let mut points = Vec::new();
-		for x in 0..self.image.width() {
-			for y in 0..self.image.height() {
-				if self.image.get_pixel(x, y).0[0] == 0 {
+		let mut stack = Vec::new();
+		let mut stroke = 0;
+		for x in 0..width {
+			for y in 0..height {
+				if visited[index(x, y)] || self.image.get_pixel(x, y).0[0] != 0 {
+					continue
+				}
+
+				visited[index(x, y)] = true;
+				stack.push((x, y));
+
+				let mut last: Option<(u32, u32)> = None;
+				while let Some((x, y)) = stack.pop() {
+					/* Backtracking to a pixel that is not next to the last
+					 * one requires that the pen be lifted. */
+					let adjacent = last
+						.map(|(lx, ly)| lx.max(x) - lx.min(x) <= 1 && ly.max(y) - ly.min(y) <= 1)
+						.unwrap_or(false);
+					if last.is_some() && !adjacent {
+						stroke += 1;
+					}
+
 					points.push((
-						f64::from(x) / f64::from(self.image.width()),
-						f64::from(y) / f64::from(self.image.height()),
-					))
+						f64::from(x) / f64::from(width),
+						f64::from(y) / f64::from(height),
+						stroke
+					));
+					last = Some((x, y));
+
+					for (dx, dy) in NEIGHBORS {
+						let nx = i64::from(x) + dx;
+						let ny = i64::from(y) + dy;
+						if nx < 0 || ny < 0 || nx >= i64::from(width) || ny >= i64::from(height) {
+							continue
+						}
+
+						let (nx, ny) = (nx as u32, ny as u32);
+						if !visited[index(nx, ny)] && self.image.get_pixel(nx, ny).0[0] == 0 {
+							visited[index(nx, ny)] = true;
+							stack.push((nx, ny));
+						}
+					}
 				}
+
+				stroke += 1;
 			}
 		}
 
 		BitmapTrace {
 			points: points.into_boxed_slice(),
+			last: Cell::new(None),
 		}
 	}
 }
 
+/// The offsets to the eight neighbors of a pixel, in the order in which they
+/// are visited when walking a stroke.
+const NEIGHBORS: [(i64, i64); 8] = [
+	(-1, -1), (0, -1), (1, -1),
+	(-1, 0), (1, 0),
+	(-1, 1), (0, 1), (1, 1),
+];
+
 /// A parametric curve derived from a bitmap path.
+///
+/// Bitmaps carry no pressure information, so all of the points in this curve
+/// are given full pressure.
+///
+/// The points in this curve are grouped into strokes, and the pen is kept
+/// touching the screen for as long as the curve is traced along the same
+/// stroke. Because of this, this curve is meant to be traced in order.
 pub struct BitmapTrace {
-	points: Box<[(f64, f64)]>,
+	/// The points along the curve, along with the strokes they belong to.
+	points: Box<[(f64, f64, usize)]>,
+	/// The last point that was traced.
+	last: Cell<Option<(f64, f64, usize)>>,
 }
 impl Trace for BitmapTrace {
 	fn get<E>(&self, t: f64, buffer: &mut E) -> usize
@@ -381,24 +1911,370 @@ impl Trace for BitmapTrace {
 
 		let index = t * self.points.len() as f64;
 		let index = index.floor() as usize;
-		let (x, y) = if index < self.points.len() {
+		let (x, y, stroke) = if index < self.points.len() {
 			self.points[index]
 		} else {
-			return 0
+			/* Lift the pen at the end of the curve. */
+			return match self.last.take() {
+				Some((x, y, _)) => {
+					buffer.extend(Some(Point { x, y, touch: false, pressure: 1.0 }));
+					1
+				},
+				None => 0
+			}
 		};
 
-		buffer.extend([
-			Point {
-				x,
-				y,
-				touch: true
-			},
-			Point {
-				x,
-				y,
-				touch: false
-			},
-		]);
-		2
+		let mut count = 0;
+		if let Some((lx, ly, last_stroke)) = self.last.get() {
+			if last_stroke != stroke {
+				/* Lift the pen before moving on to a new stroke. */
+				buffer.extend(Some(Point { x: lx, y: ly, touch: false, pressure: 1.0 }));
+				count += 1;
+			}
+		}
+
+		buffer.extend(Some(Point { x, y, touch: true, pressure: 1.0 }));
+		self.last.set(Some((x, y, stroke)));
+
+		count + 1
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Creates an event at the given normalized position, the given amount of
+	/// time after the epoch.
+	fn event(epoch: Instant, millis: u64, x: f64, y: f64, touching: bool) -> Event {
+		Event::from_serializable(&SerializableEvent {
+			offset: Duration::from_millis(millis),
+			position: (x, y, 1.0),
+			raw: ((x * 1000.0) as u16, (y * 1000.0) as u16, 1023),
+			resolution: (1000, 1000, 1023),
+			touching,
+			close: true,
+			option: None,
+			time_count: None,
+			sequence: None
+		}, epoch)
+	}
+
+	/// A pen stroke that jumps from one side of the screen to the other
+	/// without ever being lifted.
+	fn jump(epoch: Instant) -> [Event; 2] {
+		[
+			event(epoch, 0, 0.1, 0.5, true),
+			event(epoch, 10, 0.9, 0.5, true),
+		]
+	}
+
+	/// An accidental tap of the pen, followed by a real stroke.
+	fn tap_and_stroke(epoch: Instant) -> [Event; 7] {
+		[
+			event(epoch, 0, 0.2, 0.2, true),
+			event(epoch, 10, 0.2, 0.2, false),
+			event(epoch, 20, 0.5, 0.5, true),
+			event(epoch, 30, 0.6, 0.5, true),
+			event(epoch, 40, 0.7, 0.5, true),
+			event(epoch, 50, 0.8, 0.5, true),
+			event(epoch, 60, 0.8, 0.5, false),
+		]
+	}
+
+	#[test]
+	fn canvas_does_not_connect_a_jump() {
+		let mut canvas = EventCanvas::new(100, 100).unwrap();
+		for &event in jump(Instant::now()).iter() {
+			canvas.process(event);
+		}
+
+		assert_eq!(canvas.get(50, 50), Some(false));
+	}
+
+	#[test]
+	fn path_does_not_trace_across_a_jump() {
+		let mut path = EventPath::new();
+		for &event in jump(Instant::now()).iter() {
+			path.process(event);
+		}
+
+		let mut points = Vec::new();
+		path.trace().get(0.5, &mut points);
+
+		assert_eq!(points.len(), 1);
+		assert!(!points[0].touch);
+	}
+
+	#[test]
+	fn path_undoes_only_the_stroke_after_a_jump() {
+		let mut path = EventPath::new();
+		for &event in jump(Instant::now()).iter() {
+			path.process(event);
+		}
+
+		assert!(path.undo_stroke());
+		assert_eq!(path.len(), 1);
+	}
+
+	#[test]
+	fn statistics_split_strokes_at_a_jump() {
+		let mut path = EventPath::new();
+		for &event in jump(Instant::now()).iter() {
+			path.process(event);
+		}
+
+		let stats = path.statistics();
+		assert_eq!(stats.strokes, 2);
+		assert_eq!(stats.pen_lifts, 1);
+		assert_eq!(stats.length, 0.0);
+	}
+
+	#[test]
+	fn statistics_of_two_strokes() {
+		let epoch = Instant::now();
+		let mut path = EventPath::new();
+		for &event in [
+			event(epoch, 0, 0.1, 0.1, true),
+			event(epoch, 10, 0.13, 0.14, true),
+			event(epoch, 20, 0.13, 0.14, false),
+			event(epoch, 30, 0.5, 0.5, true),
+			event(epoch, 40, 0.56, 0.58, true),
+			event(epoch, 50, 0.56, 0.58, false),
+		].iter() {
+			path.process(event);
+		}
+
+		/* Raw positions are a thousand times the normalized ones, so the
+		 * strokes are 50 and 100 units long. */
+		let stats = path.statistics();
+		assert_eq!(stats.strokes, 2);
+		assert_eq!(stats.pen_lifts, 2);
+		assert!((stats.length - 150.0).abs() < 1e-9, "length was {}", stats.length);
+		assert_eq!(stats.bounds, Some((0.1, 0.1, 0.56, 0.58)));
+	}
+
+	/// Converts the events of the given path into serializable events, with
+	/// their offsets relative to the first one.
+	fn recorded(path: &EventPath) -> Vec<SerializableEvent> {
+		let epoch = path.iter().next().unwrap().time();
+		path.iter().map(|event| event.to_serializable(epoch)).collect()
+	}
+
+	#[test]
+	fn recordings_round_trip() {
+		let epoch = Instant::now();
+		let mut path = EventPath::new();
+		for &event in tap_and_stroke(epoch).iter() {
+			path.process(event);
+		}
+		path.process(Event::from_serializable(&SerializableEvent {
+			option: Some(7),
+			time_count: Some(1234),
+			sequence: Some(0),
+			..event(epoch, 75, 0.9, 0.1, true).to_serializable(epoch)
+		}, epoch));
+
+		let mut recording = Vec::new();
+		path.save(&mut recording).unwrap();
+		let loaded = EventPath::load(&recording[..]).unwrap();
+
+		assert_eq!(recorded(&loaded), recorded(&path));
+	}
+
+	#[test]
+	fn recordings_with_repeated_offsets_are_rejected() {
+		let epoch = Instant::now();
+		let mut path = EventPath::new();
+		path.process(event(epoch, 0, 0.1, 0.1, true));
+		path.process(event(epoch, 10, 0.2, 0.1, true));
+
+		let mut recording = Vec::new();
+		path.save(&mut recording).unwrap();
+
+		/* Give the second event the same offset as the first one. */
+		let size = (recording.len() - RECORDING_MAGIC.len() - 1 - 4) / 2;
+		let second = RECORDING_MAGIC.len() + 1 + 4 + size;
+		recording[second..second + 8].copy_from_slice(&0u64.to_le_bytes());
+
+		let error = EventPath::load(&recording[..]).unwrap_err();
+		assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+	}
+
+	#[test]
+	fn canvas_leaves_short_strokes_out_of_exports() {
+		let mut canvas = EventCanvas::new(100, 100).unwrap();
+		for &event in tap_and_stroke(Instant::now()).iter() {
+			canvas.process(event);
+		}
+
+		/* Pixels are dark where they are painted. */
+		let image = canvas.to_image();
+		assert!(image.get_pixel(20, 20)[0] < 128);
+		assert!(image.get_pixel(64, 50)[0] < 128);
+
+		canvas.set_min_stroke_events(Some(2));
+		let image = canvas.to_image();
+		assert_eq!(image.get_pixel(20, 20)[0], 255);
+		assert!(image.get_pixel(64, 50)[0] < 128);
+
+		/* The tap is still on the canvas itself. */
+		assert_eq!(canvas.get(20, 20), Some(true));
+	}
+
+	#[test]
+	fn path_leaves_short_strokes_out_of_the_trace() {
+		let mut path = EventPath::new();
+		for &event in tap_and_stroke(Instant::now()).iter() {
+			path.process(event);
+		}
+		assert_eq!(path.trace().events.len(), 7);
+
+		path.set_min_stroke_events(Some(2));
+		let trace = path.trace();
+		assert_eq!(trace.events.len(), 6);
+		assert!(trace.events.iter().all(|event| event.x() > 0.2 || !event.touching()));
+	}
+
+	#[test]
+	fn grid_mapping_keeps_diagonals_diagonal() {
+		/* An input grid of 2:1 on a screen of 16:9. */
+		let mut canvas = EventCanvas::new(160, 90).unwrap();
+		canvas.set_grid_mapping(GridMapping::fit((2000, 1000), (160, 90)));
+
+		/* Equal steps along both axes of the grid, which has square cells. */
+		let epoch = Instant::now();
+		for i in 0..=5 {
+			let x = 0.25 + 0.05 * f64::from(i);
+			let y = 0.25 + 0.1 * f64::from(i);
+			canvas.process(event(epoch, u64::from(i) * 10, x, y, true));
+		}
+		canvas.process(event(epoch, 60, 0.5, 0.75, false));
+
+		let (_, _, width, height) = canvas.bounding_box().unwrap();
+		assert!((i64::from(width) - i64::from(height)).abs() <= 1,
+			"a diagonal stroke was drawn {}x{} pixels", width, height);
+	}
+
+	#[test]
+	fn grid_mapping_stretches_matching_aspect_ratios() {
+		assert_eq!(GridMapping::fit((9600, 6000), (320, 200)), GridMapping::STRETCH);
+		assert_eq!(GridMapping::fit((0, 6000), (320, 200)), GridMapping::STRETCH);
+	}
+
+	/// Draws a horizontal stroke at the given height, from the given time on.
+	fn stroke(canvas: &mut EventCanvas, epoch: Instant, millis: u64, y: f64) {
+		for i in 0..4 {
+			let x = 0.2 + 0.1 * f64::from(i);
+			canvas.process(event(epoch, millis + u64::from(i) * 10, x, y, true));
+		}
+		canvas.process(event(epoch, millis + 40, 0.5, y, false));
+	}
+
+	#[test]
+	fn undo_leaves_exactly_the_first_stroke() {
+		let epoch = Instant::now();
+
+		let mut first = EventCanvas::new(100, 100).unwrap();
+		stroke(&mut first, epoch, 0, 0.25);
+
+		let mut both = EventCanvas::new(100, 100).unwrap();
+		stroke(&mut both, epoch, 0, 0.25);
+		stroke(&mut both, epoch, 100, 0.75);
+		let drawn = both.to_image();
+		assert_ne!(drawn, first.to_image());
+
+		assert!(both.undo_stroke());
+		assert_eq!(both.to_image(), first.to_image());
+
+		assert!(both.redo_stroke());
+		assert_eq!(both.to_image(), drawn);
+	}
+
+	#[test]
+	fn clearing_mid_stroke_starts_a_fresh_stroke() {
+		let epoch = Instant::now();
+		let mut canvas = EventCanvas::new(100, 100).unwrap();
+		canvas.process(event(epoch, 0, 0.2, 0.25, true));
+		canvas.process(event(epoch, 10, 0.3, 0.25, true));
+		canvas.clear();
+
+		/* The pen never left the screen. */
+		stroke(&mut canvas, epoch, 20, 0.75);
+		let drawn = canvas.to_image();
+		assert_eq!(canvas.get(25, 50), Some(false));
+
+		assert!(canvas.undo_stroke());
+		assert_eq!(canvas.bounding_box(), None);
+		assert!(canvas.redo_stroke());
+		assert_eq!(canvas.to_image(), drawn);
+	}
+
+	#[test]
+	fn otsu_threshold_separates_two_intensities() {
+		let image = image::GrayImage::from_fn(64, 64, |x, _|
+			if x < 16 { Luma([30]) } else { Luma([220]) });
+		let threshold = otsu_threshold(&image);
+		assert!(30 < threshold && threshold <= 220, "picked {}", threshold);
+
+		let path = BitmapPath::with_otsu_threshold(image);
+		assert_eq!(path.threshold(), threshold);
+		assert_eq!(path.to_image().get_pixel(0, 0).0, [0]);
+		assert_eq!(path.to_image().get_pixel(63, 0).0, [255]);
+	}
+
+	#[test]
+	fn otsu_threshold_handles_flat_images() {
+		let image = image::GrayImage::from_pixel(8, 8, Luma([128]));
+		assert!(otsu_threshold(&image) <= 128);
+	}
+
+	#[test]
+	fn thinned_strokes_are_one_pixel_wide() {
+		/* A horizontal bar three pixels thick. */
+		let image = image::GrayImage::from_fn(20, 9, |x, y|
+			if (2..18).contains(&x) && (3..6).contains(&y) { Luma([0]) } else { Luma([255]) });
+		let thinned = BitmapPath::new(image).thinned().to_image();
+
+		let set = |x: u32| (0..9).filter(|y| thinned.get_pixel(x, *y).0[0] == 0).count();
+		assert!((2..18).any(|x| set(x) > 0));
+		for x in 4..16 {
+			assert!(set(x) <= 1, "column {} is {} pixels thick", x, set(x));
+		}
+	}
+
+	#[test]
+	fn inverting_matches_thresholding_an_inverted_image() {
+		let image = image::GrayImage::from_fn(32, 8, |x, y| Luma([(x * 8 + y) as u8]));
+		let mut negative = image.clone();
+		image::imageops::invert(&mut negative);
+
+		let inverted = BitmapPath::with_polarity(image, 100, true);
+		let thresholded = BitmapPath::with_threshold(negative, 100);
+
+		assert!(inverted.inverted());
+		assert_eq!(inverted.to_image(), thresholded.to_image());
+		assert_eq!(
+			inverted.clone().with_grayscale(true).to_image(),
+			thresholded.with_grayscale(true).to_image());
+	}
+
+	#[test]
+	fn trimmed_trace_spans_the_whole_area() {
+		let image = image::GrayImage::from_fn(100, 100, |x, y|
+			if (40..50).contains(&x) && (40..50).contains(&y) { Luma([0]) } else { Luma([255]) });
+		let path = BitmapPath::new(image);
+
+		let span = |path: &BitmapPath| {
+			let trace = path.trace();
+			let xs = trace.points.iter().map(|(x, _, _)| *x);
+			(
+				xs.clone().fold(f64::INFINITY, f64::min),
+				xs.fold(f64::NEG_INFINITY, f64::max)
+			)
+		};
+
+		assert_eq!(span(&path), (0.4, 0.49));
+		assert_eq!(span(&path.trimmed(0)), (0.0, 0.9));
 	}
 }
@@ -2,7 +2,8 @@ use std::collections::VecDeque;
 use crate::path::{IntoTrace, Point, Trace};
 use std::time::{Duration, Instant};
 use std::num::NonZeroU32;
-use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 /// A global state lock for controlling access to the mouse.
 static MOUSE_LOCK: AtomicBool = AtomicBool::new(false);
@@ -19,131 +20,640 @@ pub struct Playback<T> {
 	pub delta: Duration,
 	/// The number of steps that will be used to play the path back.
 	pub steps: NonZeroU32,
+	/// The ratio between the width and the height of the source of the path.
+	///
+	/// When set, the path is fit within the target area so that this ratio is
+	/// preserved, centered along the axis with room to spare. Otherwise, the
+	/// path is stretched to fill the whole target area.
+	pub aspect_ratio: Option<f64>,
+	/// What the mouse buttons do while the path is played back.
+	pub click_mode: ClickMode,
+}
+impl<T> Playback<T> {
+	/// Starts building a playback of the given path over the given area of the
+	/// screen, validating its parameters once it's built.
+	pub fn builder(path: T, target: ScreenArea) -> PlaybackBuilder<T> {
+		let speed = PlaybackSpeed::default();
+		PlaybackBuilder {
+			path,
+			target,
+			delta: speed.delta(),
+			steps: speed.steps(),
+			aspect_ratio: None,
+			click_mode: ClickMode::default()
+		}
+	}
 }
 impl<T> Playback<T>
 	where T: IntoTrace {
 
-	/// Maps a point in normalized space into a point in screen space.
-	fn map(&self, point: Point) -> (i32, i32) {
+	/// Maps a point in normalized space into a point in the normalized
+	/// absolute coordinate space spanning the given virtual desktop.
+	fn map(&self, point: Point, desktop: ScreenArea) -> (i32, i32) {
 		let Point { x, y, .. } = point;
+		let a = self.fitted_target();
 
-		/* Using device coordinates forces all points to map to the primary
-		 * monitor, regardless of what monitor they're actually in. This is
-		 * wrong, but works so long as only areas entirely inside the primary
-		 * monitor are given to the Playback structure.
-		 *
-		 * TODO: Convert to virtual screen coordinates here.
-		 */
-		let w = f64::from(nwg::Monitor::width());
-		let h = f64::from(nwg::Monitor::height());
-
-		let a = self.target;
-
+		/* Find the position of the point in screen space. */
 		let x = x * a.width.saturating_sub(1) as f64 + a.x as f64;
 		let y = y * a.height.saturating_sub(1) as f64 + a.y as f64;
 
+		/* Then make it relative to the origin of the virtual desktop, which
+		 * may lie to the left of or above the primary monitor. */
+		let x = x - desktop.x as f64;
+		let y = y - desktop.y as f64;
+
+		let w = desktop.width.saturating_sub(1).max(1) as f64;
+		let h = desktop.height.saturating_sub(1).max(1) as f64;
+
 		let n = f64::from(256 * 256 - 1);
-		let x = (x / w * n) as i32;
-		let y = (y / h * n) as i32;
+		let x = (x / w * n).round() as i32;
+		let y = (y / h * n).round() as i32;
 
 		(x, y)
 	}
 
+	/// The area the path gets mapped to, taking the aspect ratio of its source
+	/// into account, if one has been given.
+	fn fitted_target(&self) -> ScreenArea {
+		match self.aspect_ratio {
+			Some(ratio) => self.target.fit(ratio),
+			None => self.target
+		}
+	}
+
+	/// Goes through the mouse inputs this playback emits over the given virtual
+	/// desktop, in order, for as long as the given function returns `true`.
+	fn walk<F>(&self, desktop: ScreenArea, mut emit: F)
+		where F: FnMut(PlaybackInput) -> bool {
+
+		let mut x = 0.0;
+		let mut pressed = false;
+		let trace = self.path.trace();
+
+		let dx = 1.0 / f64::from(self.steps.get());
+		let mut buffer = VecDeque::new();
+
+		for _ in 0..self.steps.get() {
+			/* Evaluate the curve at the current position. */
+			let points = trace.get(x, &mut buffer);
+			if points == 0 { break }
+
+			for point in buffer.drain(..) {
+				let (px, py) = self.map(point, desktop);
+				let flags = self.click_mode.transition(&mut pressed, point.touch);
+
+				x += dx;
+
+				if !emit(PlaybackInput { x: px, y: py, pressed, flags }) {
+					return
+				}
+			}
+		}
+	}
+
+	/// Goes through the mouse movements specified by this structure over the
+	/// given virtual desktop, without actually moving the mouse or waiting in
+	/// between movements, returning all of the inputs that would be emitted.
+	///
+	/// This is a dry run of [`play_and_notify()`], meant for checking where a
+	/// path would end up on the screen without getting in the way of the user.
+	///
+	/// [`play_and_notify()`]: Self::play_and_notify
+	pub fn simulate(&self, desktop: ScreenArea) -> Vec<PlaybackInput> {
+		let mut inputs = Vec::new();
+		self.walk(desktop, |input| {
+			inputs.push(input);
+			true
+		});
+
+		inputs
+	}
+
 	/// Perform the mouse movements specified by this structure on to the screen.
+	///
+	/// The movements are performed in a separate thread, and the given sender
+	/// is notified once they are done, regardless of whether they were
+	/// completed or cancelled. Only one playback may be controlling the mouse
+	/// at any given time, and trying to start a playback while another one is
+	/// running fails with [`PlaybackBusy`].
+	///
+	/// Since the mouse is out of the hands of the user while a playback runs,
+	/// holding down the Escape key cancels it, just like [`cancel()`] does.
+	///
+	/// [`cancel()`]: PlaybackHandle::cancel
+	/// [`PlaybackBusy`]: PlaybackBusy
 	pub fn play_and_notify(self, sender: nwg::NoticeSender)
+		-> Result<PlaybackHandle, PlaybackBusy>
 		where T: Send + 'static {
 
-		if MOUSE_LOCK.fetch_or(true, std::sync::atomic::Ordering::SeqCst) {
-			/* Calling this function twice is a bug in this program. */
-			panic!("Called Playback::play_and_notify() more than once");
-		}
+		let handle = PlaybackHandle::new();
+		self.play_and_notify_with(sender, &handle)?;
+
+		Ok(handle)
+	}
+
+	/// Perform the mouse movements specified by this structure on to the
+	/// screen, controlled through the given handle.
+	///
+	/// This is the same as [`play_and_notify()`], except that the handle
+	/// exists before the playback starts, so that it can be handed to whoever
+	/// gets notified by the sender before the sender has any chance of being
+	/// notified.
+	///
+	/// [`play_and_notify()`]: Self::play_and_notify
+	pub fn play_and_notify_with(self, sender: nwg::NoticeSender, handle: &PlaybackHandle)
+		-> Result<(), PlaybackBusy>
+		where T: Send + 'static {
+
+		let guard = MouseGuard {
+			lock: Some(MouseLock::acquire()?),
+			sender,
+			release: self.click_mode.flags().map(|(_, up)| up)
+		};
+
+		let cancelled = handle.cancelled.clone();
 
 		std::thread::spawn(move || {
-			let mut x = 0.0;
-			let mut pressed = false;
-			let trace = self.path.trace();
+			/* Hold on to the guard for as long as we're using the mouse. */
+			let _guard = guard;
 
 			let dt = self.delta.div_f64(f64::from(self.steps.get()));
-			let dx = 1.0 / f64::from(self.steps.get());
+			let desktop = ScreenArea::virtual_desktop();
+			let wait = PreciseWait::new();
+
+			self.walk(desktop, |input| {
+				if escape_held() {
+					cancelled.store(true, Ordering::SeqCst);
+				}
+				if cancelled.load(Ordering::SeqCst) { return false }
+				let timer1 = Instant::now();
+
+				/* Build the input structure and send it. */
+				unsafe {
+					let mut raw: winapi::um::winuser::INPUT =
+						std::mem::zeroed();
 
-			let mut buffer = VecDeque::new();
+					raw.type_ = winapi::um::winuser::INPUT_MOUSE;
 
-			for _ in 0..self.steps.get() {
-				/* Evaluate the curve at the current position. */
-				let points = trace.get(x, &mut buffer);
-				if points == 0 { break }
+					raw.u.mi_mut().dx = input.x;
+					raw.u.mi_mut().dy = input.y;
+					raw.u.mi_mut().mouseData = 0;
 
-				for point in buffer.drain(..) {
-					let timer1 = Instant::now();
+					raw.u.mi_mut().time = 0;
 
-					let (px, py) = self.map(point);
+					raw.u.mi_mut().dwExtraInfo = 0;
+					raw.u.mi_mut().dwFlags =
+						winapi::um::winuser::MOUSEEVENTF_ABSOLUTE
+							| winapi::um::winuser::MOUSEEVENTF_VIRTUALDESK
+							| winapi::um::winuser::MOUSEEVENTF_MOVE
+							| input.flags;
 
-					/* Build the input structure and send it. */
-					unsafe {
-						let mut input: winapi::um::winuser::INPUT =
-							std::mem::zeroed();
+					let _ = winapi::um::winuser::SendInput(
+						1,
+						&mut raw,
+						std::mem::size_of::<winapi::um::winuser::INPUT>() as _, );
+				}
 
-						input.type_ = winapi::um::winuser::INPUT_MOUSE;
+				wait.until(timer1 + dt);
+				true
+			});
+		});
 
-						input.u.mi_mut().dx = px;
-						input.u.mi_mut().dy = py;
-						input.u.mi_mut().mouseData = 0;
+		Ok(())
+	}
+}
+
+/// A single mouse input emitted by a [playback].
+///
+/// [playback]: Playback
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PlaybackInput {
+	/// The horizontal position of the cursor, in normalized absolute
+	/// coordinates spanning the virtual desktop, from `0` to `65535`.
+	pub x: i32,
+	/// The vertical position of the cursor, in normalized absolute
+	/// coordinates spanning the virtual desktop, from `0` to `65535`.
+	pub y: i32,
+	/// Whether the mouse button used by the playback is held down after this
+	/// input.
+	pub pressed: bool,
+	/// The input flags pressing or releasing the mouse button along with this
+	/// input, if any.
+	flags: u32,
+}
 
-						input.u.mi_mut().time = 0;
+/// A builder for a [playback], created with [`Playback::builder()`].
+///
+/// [playback]: Playback
+/// [`Playback::builder()`]: Playback::builder
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaybackBuilder<T> {
+	/// The path to be played back.
+	path: T,
+	/// The area of the screen the path is played back to.
+	target: ScreenArea,
+	/// The amount of time that the path should take to get written down.
+	delta: Duration,
+	/// The number of steps that will be used to play the path back.
+	steps: u32,
+	/// The ratio between the width and the height of the source of the path.
+	aspect_ratio: Option<f64>,
+	/// What the mouse buttons do while the path is played back.
+	click_mode: ClickMode,
+}
+impl<T> PlaybackBuilder<T> {
+	/// The amount of time that the path should take to get written down.
+	pub fn delta(mut self, delta: Duration) -> Self {
+		self.delta = delta;
+		self
+	}
 
-						input.u.mi_mut().dwExtraInfo = 0;
-						input.u.mi_mut().dwFlags =
-							winapi::um::winuser::MOUSEEVENTF_ABSOLUTE
-								| winapi::um::winuser::MOUSEEVENTF_MOVE
-								| if !pressed && point.touch {
-								pressed = true;
-								winapi::um::winuser::MOUSEEVENTF_LEFTDOWN
-							} else if pressed && !point.touch {
-								pressed = false;
-								winapi::um::winuser::MOUSEEVENTF_LEFTUP
-							} else { 0 };
+	/// The number of steps that will be used to play the path back, which
+	/// must not be zero.
+	pub fn steps(mut self, steps: u32) -> Self {
+		self.steps = steps;
+		self
+	}
 
-						let _ = winapi::um::winuser::SendInput(
-							1,
-							&mut input,
-							std::mem::size_of::<winapi::um::winuser::INPUT>() as _, );
-					}
+	/// Plays back the path at the given speed.
+	pub fn speed(self, speed: PlaybackSpeed) -> Self {
+		self.delta(speed.delta()).steps(speed.steps())
+	}
 
-					x += dx;
+	/// Preserves the given ratio between the width and the height of the
+	/// source of the path, as described in [`Playback::aspect_ratio`].
+	///
+	/// [`Playback::aspect_ratio`]: Playback::aspect_ratio
+	pub fn aspect_ratio(mut self, aspect_ratio: f64) -> Self {
+		self.aspect_ratio = Some(aspect_ratio);
+		self
+	}
 
-					/* Spinning is way more accurate than using thread::sleep,
-					 * and for small amounts time like we're dealing with here
-					 * it would be too inaccurate. */
-					while timer1.elapsed() < dt {}
-				}
-			}
+	/// Changes what the mouse buttons do while the path is played back.
+	pub fn click_mode(mut self, click_mode: ClickMode) -> Self {
+		self.click_mode = click_mode;
+		self
+	}
 
-			/* Tell the mouse to release the left down key. */
-			unsafe {
-				let mut input: winapi::um::winuser::INPUT =
-					std::mem::zeroed();
+	/// Validates the parameters and creates the playback.
+	pub fn build(self) -> Result<Playback<T>, InvalidPlayback> {
+		let steps = NonZeroU32::new(self.steps)
+			.ok_or(InvalidPlayback::NoSteps)?;
+		if self.target.width == 0 || self.target.height == 0 {
+			return Err(InvalidPlayback::EmptyTarget)
+		}
 
-				input.type_ = winapi::um::winuser::INPUT_MOUSE;
+		Ok(Playback {
+			path: self.path,
+			target: self.target,
+			delta: self.delta,
+			steps,
+			aspect_ratio: self.aspect_ratio,
+			click_mode: self.click_mode
+		})
+	}
+}
 
-				input.u.mi_mut().dx = 0;
-				input.u.mi_mut().dy = 0;
-				input.u.mi_mut().mouseData = 0;
+/// The reasons for which the parameters of a [playback] may be invalid.
+///
+/// [playback]: Playback
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, thiserror::Error)]
+pub enum InvalidPlayback {
+	/// The playback was given no steps to be performed in.
+	#[error("the playback must have at least one step")]
+	NoSteps,
+	/// The playback was given a target area with no width or no height.
+	#[error("the playback must target an area of the screen that isn't empty")]
+	EmptyTarget,
+}
 
-				input.u.mi_mut().time = 0;
+/// The speeds at which a [playback] may be performed.
+///
+/// Slower speeds take longer to write the path down, but also use more steps,
+/// which makes them both smoother and more tolerant to input getting dropped,
+/// such as happens over laggy remote desktop sessions.
+///
+/// [playback]: Playback
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum PlaybackSpeed {
+	/// Takes its time writing the path down.
+	Slow,
+	/// The speed that works well for most setups.
+	Normal,
+	/// Writes the path down as quickly as it reasonably can.
+	Fast,
+}
+impl PlaybackSpeed {
+	/// All of the available speeds, from the slowest to the fastest.
+	pub const ALL: [Self; 3] = [Self::Slow, Self::Normal, Self::Fast];
+
+	/// The amount of time a path takes to get written down at this speed.
+	pub fn delta(&self) -> Duration {
+		match self {
+			Self::Slow => Duration::from_secs(16),
+			Self::Normal => Duration::from_secs(8),
+			Self::Fast => Duration::from_secs(4),
+		}
+	}
 
-				input.u.mi_mut().dwExtraInfo = 0;
-				input.u.mi_mut().dwFlags = winapi::um::winuser::MOUSEEVENTF_LEFTUP;
+	/// The number of steps used to write a path down at this speed.
+	pub fn steps(&self) -> u32 {
+		match self {
+			Self::Slow => 10000,
+			Self::Normal => 5000,
+			Self::Fast => 5000,
+		}
+	}
+}
+impl Default for PlaybackSpeed {
+	fn default() -> Self {
+		Self::Normal
+	}
+}
+impl std::fmt::Display for PlaybackSpeed {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let name = match self {
+			Self::Slow => crate::strings::playback::speed_slow(),
+			Self::Normal => crate::strings::playback::speed_normal(),
+			Self::Fast => crate::strings::playback::speed_fast(),
+		};
+
+		write!(f, "{}", name)
+	}
+}
 
-				let _ = winapi::um::winuser::SendInput(
-					1,
-					&mut input,
-					std::mem::size_of::<winapi::um::winuser::INPUT>() as _,);
-			}
+/// What the mouse buttons do while a [playback] is running.
+///
+/// [playback]: Playback
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ClickMode {
+	/// Holds the left button down while the pen is touching the screen, which
+	/// draws the path in most painting applications.
+	Draw,
+	/// Never presses any buttons, only moving the cursor along the path.
+	MoveOnly,
+	/// Holds the given button down while the pen is touching the screen.
+	Button(MouseButton),
+}
+impl ClickMode {
+	/// The input flags pressing and releasing the button used by this mode,
+	/// in that order, if it uses any.
+	fn flags(&self) -> Option<(u32, u32)> {
+		let button = match self {
+			Self::Draw => MouseButton::Left,
+			Self::MoveOnly => return None,
+			Self::Button(button) => *button,
+		};
+
+		Some(button.flags())
+	}
 
-			/* Release our lock on the mouse. */
-			MOUSE_LOCK.store(false, std::sync::atomic::Ordering::SeqCst);
-			sender.notice();
-		});
+	/// The input flags for the change in the state of the button that comes
+	/// with the pen touching the screen or not, keeping track of whether the
+	/// button is currently being held down.
+	fn transition(&self, pressed: &mut bool, touch: bool) -> u32 {
+		let (down, up) = match self.flags() {
+			Some(flags) => flags,
+			None => return 0
+		};
+
+		if !*pressed && touch {
+			*pressed = true;
+			down
+		} else if *pressed && !touch {
+			*pressed = false;
+			up
+		} else { 0 }
+	}
+}
+impl Default for ClickMode {
+	fn default() -> Self {
+		Self::Draw
+	}
+}
+
+/// The mouse buttons that may be held down during a [playback].
+///
+/// [playback]: Playback
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MouseButton {
+	/// The primary button.
+	Left,
+	/// The secondary button.
+	Right,
+	/// The button under the scroll wheel.
+	Middle,
+}
+impl MouseButton {
+	/// The input flags pressing and releasing this button, in that order.
+	fn flags(&self) -> (u32, u32) {
+		use winapi::um::winuser as user;
+		match self {
+			Self::Left => (user::MOUSEEVENTF_LEFTDOWN, user::MOUSEEVENTF_LEFTUP),
+			Self::Right => (user::MOUSEEVENTF_RIGHTDOWN, user::MOUSEEVENTF_RIGHTUP),
+			Self::Middle => (user::MOUSEEVENTF_MIDDLEDOWN, user::MOUSEEVENTF_MIDDLEUP),
+		}
+	}
+}
+
+/// A handle to a running [playback], with which it may be cancelled.
+///
+/// Dropping this handle does not cancel the playback.
+///
+/// [playback]: Playback
+#[derive(Debug, Clone)]
+pub struct PlaybackHandle {
+	/// Whether the playback has been asked to stop.
+	cancelled: Arc<AtomicBool>,
+}
+impl PlaybackHandle {
+	/// Creates a handle that is not controlling any playback yet, to be
+	/// given to [`Playback::play_and_notify_with()`].
+	///
+	/// [`Playback::play_and_notify_with()`]: Playback::play_and_notify_with
+	pub fn new() -> Self {
+		Self { cancelled: Arc::new(AtomicBool::new(false)) }
+	}
+
+	/// Asks for the playback to stop as soon as possible.
+	///
+	/// The mouse button is released and the sender given to the playback is
+	/// notified once it stops, just as if it had completed.
+	pub fn cancel(&self) {
+		self.cancelled.store(true, Ordering::SeqCst);
+	}
+
+	/// Whether the playback has been cancelled, either through this handle or
+	/// by the user.
+	pub fn is_cancelled(&self) -> bool {
+		self.cancelled.load(Ordering::SeqCst)
+	}
+}
+impl Default for PlaybackHandle {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Whether the Escape key is currently being held down, regardless of which
+/// window has the focus.
+fn escape_held() -> bool {
+	let state = unsafe {
+		winapi::um::winuser::GetAsyncKeyState(winapi::um::winuser::VK_ESCAPE)
+	};
+	state as u16 & 0x8000 != 0
+}
+
+/// The error returned when trying to start a [playback] while another one is
+/// still controlling the mouse.
+///
+/// [playback]: Playback
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, thiserror::Error)]
+#[error("another playback is already controlling the mouse")]
+pub struct PlaybackBusy;
+
+/// The global lock on the mouse, which is released when dropped.
+#[derive(Debug)]
+struct MouseLock(());
+impl MouseLock {
+	/// Takes the global lock on the mouse, failing if another playback is
+	/// already holding it.
+	fn acquire() -> Result<Self, PlaybackBusy> {
+		if MOUSE_LOCK.fetch_or(true, Ordering::SeqCst) {
+			return Err(PlaybackBusy)
+		}
+
+		Ok(Self(()))
+	}
+}
+impl Drop for MouseLock {
+	fn drop(&mut self) {
+		MOUSE_LOCK.store(false, Ordering::SeqCst);
+	}
+}
+
+/// The guard holding the global lock on the mouse.
+///
+/// When dropped, even during unwinding, this guard releases the mouse button
+/// used by the playback, if any, then the lock, and then notifies the sender
+/// of the playback.
+struct MouseGuard {
+	/// The lock on the mouse, which is only ever taken out when dropped.
+	lock: Option<MouseLock>,
+	/// The sender to be notified when the playback is done.
+	sender: nwg::NoticeSender,
+	/// The input flag releasing the mouse button used by the playback, if any.
+	release: Option<u32>,
+}
+impl Drop for MouseGuard {
+	fn drop(&mut self) {
+		/* Tell the mouse to release the button used by the playback. */
+		if let Some(release) = self.release { unsafe {
+			let mut input: winapi::um::winuser::INPUT =
+				std::mem::zeroed();
+
+			input.type_ = winapi::um::winuser::INPUT_MOUSE;
+
+			input.u.mi_mut().dx = 0;
+			input.u.mi_mut().dy = 0;
+			input.u.mi_mut().mouseData = 0;
+
+			input.u.mi_mut().time = 0;
+
+			input.u.mi_mut().dwExtraInfo = 0;
+			input.u.mi_mut().dwFlags = release;
+
+			let _ = winapi::um::winuser::SendInput(
+				1,
+				&mut input,
+				std::mem::size_of::<winapi::um::winuser::INPUT>() as _,);
+		} }
+
+		/* Release our lock on the mouse before anyone gets the chance to try
+		 * and take it again. */
+		drop(self.lock.take());
+		self.sender.notice();
+	}
+}
+
+/// Waits for short amounts of time precisely, without keeping the processor
+/// busy for all of it.
+///
+/// Thread sleeps are far too coarse for the amounts of time between the steps
+/// of a playback, and spinning for all of it keeps a whole core busy for the
+/// entire playback. Instead, most of the wait is done on a high resolution
+/// waitable timer, which requires Windows 10, version 1803, or later, and only
+/// the last bit of it is spun through. On older versions of Windows, a regular
+/// thread sleep is used instead of the timer, leaving more of it to be spun.
+struct PreciseWait {
+	/// The high resolution waitable timer, if the system supports it.
+	timer: Option<winapi::um::winnt::HANDLE>,
+}
+impl PreciseWait {
+	/// The flag creating a high resolution waitable timer, which is missing
+	/// from our bindings.
+	const CREATE_WAITABLE_TIMER_HIGH_RESOLUTION: u32 = 0x00000002;
+
+	/// How much of the wait is left to be spun through, when waiting with the
+	/// high resolution timer.
+	const TIMER_SPIN: Duration = Duration::from_micros(200);
+
+	/// How much of the wait is left to be spun through, when waiting with
+	/// thread sleeps.
+	const SLEEP_SPIN: Duration = Duration::from_millis(2);
+
+	/// Creates a new waiter, picking the best way of waiting the system
+	/// supports.
+	fn new() -> Self {
+		let timer = unsafe {
+			winapi::um::synchapi::CreateWaitableTimerExW(
+				std::ptr::null_mut(),
+				std::ptr::null(),
+				Self::CREATE_WAITABLE_TIMER_HIGH_RESOLUTION,
+				winapi::um::winnt::TIMER_ALL_ACCESS)
+		};
+
+		Self { timer: if timer.is_null() { None } else { Some(timer) } }
+	}
+
+	/// Waits until the given point in time has been reached.
+	fn until(&self, deadline: Instant) {
+		let remaining = deadline.saturating_duration_since(Instant::now());
+		match self.timer {
+			Some(timer) if remaining > Self::TIMER_SPIN => unsafe {
+				/* Negative due times are relative, in units of 100ns. */
+				let ticks = (remaining - Self::TIMER_SPIN).as_nanos() / 100;
+				let mut due: winapi::shared::ntdef::LARGE_INTEGER = std::mem::zeroed();
+				*due.QuadPart_mut() = -(ticks.min(i64::MAX as u128) as i64);
+
+				let set = winapi::um::synchapi::SetWaitableTimer(
+					timer,
+					&due,
+					0,
+					None,
+					std::ptr::null_mut(),
+					0);
+				if set != 0 {
+					winapi::um::synchapi::WaitForSingleObject(
+						timer,
+						winapi::um::winbase::INFINITE);
+				}
+			},
+			None if remaining > Self::SLEEP_SPIN =>
+				std::thread::sleep(remaining - Self::SLEEP_SPIN),
+			_ => {}
+		}
+
+		while Instant::now() < deadline {
+			std::hint::spin_loop();
+		}
+	}
+}
+impl Drop for PreciseWait {
+	fn drop(&mut self) {
+		if let Some(timer) = self.timer {
+			unsafe { winapi::um::handleapi::CloseHandle(timer); }
+		}
 	}
 }
 
@@ -163,3 +673,167 @@ pub struct ScreenArea {
 	/// The height of the rectangular region.
 	pub height: u32,
 }
+impl ScreenArea {
+	/// The area spanned by the virtual desktop, which is the bounding
+	/// rectangle of all of the monitors attached to the system.
+	pub fn virtual_desktop() -> Self {
+		use winapi::um::winuser as user;
+		unsafe {
+			Self {
+				x: user::GetSystemMetrics(user::SM_XVIRTUALSCREEN),
+				y: user::GetSystemMetrics(user::SM_YVIRTUALSCREEN),
+				width: user::GetSystemMetrics(user::SM_CXVIRTUALSCREEN).max(0) as u32,
+				height: user::GetSystemMetrics(user::SM_CYVIRTUALSCREEN).max(0) as u32,
+			}
+		}
+	}
+
+	/// The largest area with the given ratio between its width and its height
+	/// that fits within this area, centered along the axis with room to spare.
+	///
+	/// Invalid ratios, as well as empty areas, leave this area as it is.
+	pub fn fit(&self, aspect_ratio: f64) -> Self {
+		let target = *self;
+		if !aspect_ratio.is_finite() || aspect_ratio <= 0.0 {
+			return target
+		}
+		if target.width == 0 || target.height == 0 {
+			return target
+		}
+
+		let width = f64::from(target.width);
+		let height = f64::from(target.height);
+		if width / height > aspect_ratio {
+			/* The target is wider than the source, so pillarbox it. */
+			let fitted = (height * aspect_ratio).round().min(width) as u32;
+			ScreenArea {
+				x: target.x + ((target.width - fitted) / 2) as i32,
+				width: fitted,
+				..target
+			}
+		} else {
+			/* The target is taller than the source, so letterbox it. */
+			let fitted = (width / aspect_ratio).round().min(height) as u32;
+			ScreenArea {
+				y: target.y + ((target.height - fitted) / 2) as i32,
+				height: fitted,
+				..target
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A virtual desktop with a second monitor to the left of the primary one,
+	/// and sitting higher than it.
+	const EXTENDED: ScreenArea = ScreenArea { x: -1920, y: -120, width: 3840, height: 1200 };
+
+	/// The monitor to the left of the primary one in [`EXTENDED`].
+	const LEFT: ScreenArea = ScreenArea { x: -1920, y: -120, width: 1920, height: 1080 };
+
+	/// A path going down the diagonal of its area, with the pen always down.
+	struct Diagonal;
+	impl Trace for Diagonal {
+		fn get<E>(&self, t: f64, buffer: &mut E) -> usize
+			where E: Extend<Point> {
+
+			buffer.extend(Some(Point { x: t, y: t, touch: true, pressure: 1.0 }));
+			1
+		}
+	}
+	impl IntoTrace for Diagonal {
+		type Trace<'a> = Diagonal;
+		fn trace(&self) -> Diagonal {
+			Diagonal
+		}
+	}
+
+	#[test]
+	fn fit_pillarboxes_wide_areas() {
+		let area = ScreenArea { x: 0, y: 0, width: 1920, height: 1080 };
+		assert_eq!(area.fit(1.0), ScreenArea { x: 420, y: 0, width: 1080, height: 1080 });
+	}
+
+	#[test]
+	fn fit_letterboxes_tall_areas() {
+		let area = ScreenArea { x: 0, y: 0, width: 1920, height: 1080 };
+		assert_eq!(area.fit(4.0), ScreenArea { x: 0, y: 300, width: 1920, height: 480 });
+	}
+
+	#[test]
+	fn fit_keeps_negative_origins() {
+		assert_eq!(LEFT.fit(1.0), ScreenArea { x: -1500, y: -120, width: 1080, height: 1080 });
+		assert_eq!(LEFT.fit(4.0), ScreenArea { x: -1920, y: 180, width: 1920, height: 480 });
+	}
+
+	#[test]
+	fn fit_ignores_invalid_ratios() {
+		assert_eq!(LEFT.fit(0.0), LEFT);
+		assert_eq!(LEFT.fit(-1.0), LEFT);
+		assert_eq!(LEFT.fit(f64::NAN), LEFT);
+		assert_eq!(LEFT.fit(f64::INFINITY), LEFT);
+	}
+
+	#[test]
+	fn playback_maps_relative_to_a_negative_origin() {
+		let playback = Playback::builder(Diagonal, LEFT)
+			.steps(2)
+			.build()
+			.unwrap();
+		let inputs = playback.simulate(EXTENDED);
+
+		assert_eq!(inputs.len(), 2);
+		assert_eq!((inputs[0].x, inputs[0].y), (0, 0));
+		assert_eq!((inputs[1].x, inputs[1].y), (16379, 29488));
+	}
+	#[test]
+	fn mouse_lock_is_held_by_one_playback_at_a_time() {
+		let lock = MouseLock::acquire().unwrap();
+		assert_eq!(MouseLock::acquire().unwrap_err(), PlaybackBusy);
+
+		drop(lock);
+		assert!(MouseLock::acquire().is_ok());
+	}
+
+	#[test]
+	fn builder_rejects_invalid_playbacks() {
+		let empty = ScreenArea { x: 0, y: 0, width: 0, height: 1080 };
+
+		assert_eq!(
+			Playback::builder(Diagonal, LEFT).steps(0).build().err(),
+			Some(InvalidPlayback::NoSteps));
+		assert_eq!(
+			Playback::builder(Diagonal, empty).build().err(),
+			Some(InvalidPlayback::EmptyTarget));
+	}
+
+	#[test]
+	fn move_only_never_presses_a_button() {
+		let playback = Playback::builder(Diagonal, LEFT)
+			.steps(16)
+			.click_mode(ClickMode::MoveOnly)
+			.build()
+			.unwrap();
+		let inputs = playback.simulate(EXTENDED);
+
+		assert!(!inputs.is_empty());
+		assert!(inputs.iter().all(|input| input.flags == 0 && !input.pressed));
+	}
+
+	#[test]
+	fn fit_centers_wide_paths_in_square_areas() {
+		let area = ScreenArea { x: 0, y: 0, width: 500, height: 500 };
+		assert_eq!(
+			area.fit(1000.0 / 200.0),
+			ScreenArea { x: 0, y: 200, width: 500, height: 100 });
+	}
+
+	#[test]
+	fn fit_centers_square_paths_in_wide_areas() {
+		let area = ScreenArea { x: 0, y: 0, width: 1000, height: 500 };
+		assert_eq!(area.fit(1.0), ScreenArea { x: 250, y: 0, width: 500, height: 500 });
+	}
+}
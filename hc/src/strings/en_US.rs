@@ -3,10 +3,24 @@
 pub mod manager {
 	pub fn title() -> &'static str { "Tablet" }
 	pub fn help_btn() -> &'static str { "Help" }
+	pub fn bitmap_upload_btn() -> &'static str { "Use Image" }
 	pub fn help() -> &'static str { "Nothing here but us chickens!" }
 	pub fn display_clear_btn() -> &'static str { "Clear" }
 	pub fn display_paint_btn() -> &'static str { "Paint" }
+	pub fn undo_btn() -> &'static str { "Undo" }
+	pub fn redo_btn() -> &'static str { "Redo" }
+	pub fn save_btn() -> &'static str { "Save" }
+	pub fn copy_btn() -> &'static str { "Copy" }
+	pub fn save_title() -> &'static str { "Save the signature" }
+	pub fn save_filter_png() -> &'static str { "PNG image" }
+	pub fn save_filter_bmp() -> &'static str { "Bitmap image" }
 	pub fn display_label() -> &'static str { "Display Controls" }
+	pub fn dropped_reports(what: stu::Error) -> String {
+		format!(
+			"Parts of the signature may have been lost on their way from the \
+			device ({}). Please clear the signature and sign again.",
+			what)
+	}
 }
 
 /// Strings used in the device selection window.
@@ -19,6 +33,10 @@ pub mod selector {
 
 /// Strings used in the area selection window.
 pub mod area {
+	pub fn title() -> &'static str { "Area Selection" }
+	pub fn dimensions(width: u32, height: u32) -> String {
+		format!("{} x {} pixels", width, height)
+	}
 	pub fn tip() -> &'static str {
 		"Select a region by clicking and dragging. Press and hold the Alt key \
 		to fix its aspect ratio. When done, press 'e' to paint on to the \
@@ -26,6 +44,39 @@ pub mod area {
 	}
 }
 
+/// Strings used in bitmap printing menu.
+pub mod bitmap {
+	pub fn display_label() -> &'static str { "Signature Image" }
+	pub fn file_select_title() -> &'static str {
+		"Select the image containing the signature"
+	}
+	pub fn file_select_filter_image() -> &'static str {
+		"Image files"
+	}
+	pub fn file_select_filter_all() -> &'static str {
+		"All files"
+	}
+	pub fn threshold_label(threshold: u8) -> String {
+		format!("Contrast threshold: {}", threshold)
+	}
+	pub fn invert_check() -> &'static str { "Invert" }
+	pub fn cancel_btn() -> &'static str { "Cancel" }
+	pub fn display_paint_btn() -> &'static str { "Paint" }
+	pub fn title() -> &'static str { "Signature in the file" }
+}
+
+/// Strings used in the playback controls.
+pub mod playback {
+	pub fn speed_slow() -> &'static str { "Slow speed" }
+	pub fn speed_normal() -> &'static str { "Normal speed" }
+	pub fn speed_fast() -> &'static str { "Fast speed" }
+}
+
+/// Strings used in the windowless capture mode.
+pub mod capture {
+	pub fn finish_prompt() -> &'static str { "Sign on the tablet, then press Enter to finish." }
+}
+
 /// Strings used in error messages.
 pub mod errors {
 	pub fn title() -> &'static str { "Error" }
@@ -33,9 +84,17 @@ pub mod errors {
 		what: crate::window::PickPhysicalAreaError) -> String {
 		format!("Could not display paint controls: {}", what)
 	}
+	pub fn invalid_playback(
+		what: crate::robot::InvalidPlayback) -> String {
+		format!("Could not paint the signature: {}", what)
+	}
 	pub fn no_tablets_available() -> &'static str {
 		"There are no tablet devices available on the system"
 	}
+	pub fn device_listing_failed(
+		what: stu::Error) -> String {
+		format!("Could not list the available tablet devices: {}", what)
+	}
 	pub fn device_prompt_creation_failed(
 		what: nwg::NwgError) -> String {
 		format!("Could not create device prompt window: {}", what)
@@ -43,21 +102,19 @@ pub mod errors {
 	pub fn tablet_not_found(
 		information: stu::Information) -> String {
 		format!(
-			"Could not find \"{} - {:04x}:{:04x}\". Has the tablet been disconnected?",
-			information.device(), information.vendor(), information.product())
+			"Could not find \"{}\". Has the tablet been disconnected?",
+			information)
 	}
 	pub fn tablet_connection_failed(
 		information: stu::Information,
 		what: stu::Error) -> String {
 		format!(
 			"\
-				Could not connect to \"{} - {:04x}:{:04x}\": {}.\n\n\
+				Could not connect to \"{}\": {}.\n\n\
 				\
 				Error: {:?}\
 			",
-			information.device(),
-			information.vendor(),
-			information.product(),
+			information,
 			what, what)
 	}
 	pub fn management_failed(
@@ -66,4 +123,13 @@ pub mod errors {
 			"An error has occurred while managing the device: {}",
 			what)
 	}
+	pub fn window_creation(what: nwg::NwgError) -> String {
+		format!("An error has occurred while opening the window: {}", what)
+	}
+	pub fn invalid_file() -> &'static str {
+		"The selected file is not valid"
+	}
+	pub fn file_not_found() -> &'static str {
+		"The file could not be found"
+	}
 }
@@ -1,11 +1,191 @@
+use std::sync::atomic::{AtomicU8, Ordering};
 
-#[cfg(feature = "lang-en_US")]
-include!("en_US.rs");
-#[cfg(feature = "lang-pt_BR")]
-include!("pt_BR.rs");
+#[allow(non_snake_case)]
+mod en_US;
+#[allow(non_snake_case)]
+mod pt_BR;
 
-#[cfg(not(any(
-	feature = "lang-en_US",
-	feature = "lang-pt_BR",
-)))]
-std::compile_error!("No string set has been selected.");
+/// The languages the strings used in the UI are available in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Language {
+	/// English, as spoken in the United States.
+	EnUs,
+	/// Portuguese, as spoken in Brazil.
+	PtBr,
+}
+impl Language {
+	/// Picks the language that best matches the UI language of the user, as
+	/// configured in the system, falling back to English for languages we
+	/// have no translation for.
+	pub fn from_system() -> Self {
+		/* The primary language identifier sits in the lower ten bits. */
+		let id = unsafe { winapi::um::winnls::GetUserDefaultUILanguage() };
+		match u32::from(id) & 0x3ff {
+			0x16 => Self::PtBr,
+			_ => Self::EnUs,
+		}
+	}
+
+	/// Decodes a language from its value in the [`LANGUAGE`] cell.
+	fn from_u8(value: u8) -> Option<Self> {
+		match value {
+			1 => Some(Self::EnUs),
+			2 => Some(Self::PtBr),
+			_ => None
+		}
+	}
+
+	/// Encodes this language into its value in the [`LANGUAGE`] cell.
+	fn to_u8(self) -> u8 {
+		match self {
+			Self::EnUs => 1,
+			Self::PtBr => 2,
+		}
+	}
+}
+
+/// The currently active language, with zero meaning it hasn't been picked yet.
+static LANGUAGE: AtomicU8 = AtomicU8::new(0);
+
+/// Sets the language in which strings will be returned from now on.
+pub fn set_language(language: Language) {
+	LANGUAGE.store(language.to_u8(), Ordering::Relaxed);
+}
+
+/// The language in which strings are currently being returned.
+///
+/// If no language has been set with [`set_language()`], this picks one from
+/// the system locale the first time it gets called.
+pub fn language() -> Language {
+	match Language::from_u8(LANGUAGE.load(Ordering::Relaxed)) {
+		Some(language) => language,
+		None => {
+			let language = Language::from_system();
+			set_language(language);
+
+			language
+		}
+	}
+}
+
+/// Generates the modules containing the strings used in the UI, with every
+/// function in them dispatching to the translation for the active language.
+macro_rules! strings {
+	($(
+		$(#[$meta:meta])*
+		mod $module:ident {
+			$(fn $name:ident($($arg:ident: $ty:ty),*) -> $ret:ty;)*
+		}
+	)*) => {
+		$(
+			$(#[$meta])*
+			pub mod $module {
+				$(pub fn $name($($arg: $ty),*) -> $ret {
+					match super::language() {
+						super::Language::EnUs =>
+							super::en_US::$module::$name($($arg),*),
+						super::Language::PtBr =>
+							super::pt_BR::$module::$name($($arg),*),
+					}
+				})*
+			}
+		)*
+	}
+}
+
+strings! {
+	/// Strings used in the tablet management window.
+	mod manager {
+		fn title() -> &'static str;
+		fn help_btn() -> &'static str;
+		fn bitmap_upload_btn() -> &'static str;
+		fn help() -> &'static str;
+		fn display_clear_btn() -> &'static str;
+		fn display_paint_btn() -> &'static str;
+		fn undo_btn() -> &'static str;
+		fn redo_btn() -> &'static str;
+		fn save_btn() -> &'static str;
+		fn copy_btn() -> &'static str;
+		fn save_title() -> &'static str;
+		fn save_filter_png() -> &'static str;
+		fn save_filter_bmp() -> &'static str;
+		fn display_label() -> &'static str;
+		fn dropped_reports(what: stu::Error) -> String;
+	}
+
+	/// Strings used in the device selection window.
+	mod selector {
+		fn title() -> &'static str;
+		fn description() -> &'static str;
+		fn cancel() -> &'static str;
+		fn accept() -> &'static str;
+	}
+
+	/// Strings used in the area selection window.
+	mod area {
+		fn title() -> &'static str;
+		fn dimensions(width: u32, height: u32) -> String;
+		fn tip() -> &'static str;
+	}
+
+	/// Strings used in bitmap printing menu.
+	mod bitmap {
+		fn display_label() -> &'static str;
+		fn file_select_title() -> &'static str;
+		fn file_select_filter_image() -> &'static str;
+		fn file_select_filter_all() -> &'static str;
+		fn threshold_label(threshold: u8) -> String;
+		fn invert_check() -> &'static str;
+		fn cancel_btn() -> &'static str;
+		fn display_paint_btn() -> &'static str;
+		fn title() -> &'static str;
+	}
+
+	/// Strings used in the playback controls.
+	mod playback {
+		fn speed_slow() -> &'static str;
+		fn speed_normal() -> &'static str;
+		fn speed_fast() -> &'static str;
+	}
+
+	/// Strings used in the windowless capture mode.
+	mod capture {
+		fn finish_prompt() -> &'static str;
+	}
+
+	/// Strings used in error messages.
+	mod errors {
+		fn title() -> &'static str;
+		fn signature_paint_pick_area_failed(
+			what: crate::window::PickPhysicalAreaError) -> String;
+		fn invalid_playback(what: crate::robot::InvalidPlayback) -> String;
+		fn no_tablets_available() -> &'static str;
+		fn device_listing_failed(what: stu::Error) -> String;
+		fn device_prompt_creation_failed(what: nwg::NwgError) -> String;
+		fn tablet_not_found(information: stu::Information) -> String;
+		fn tablet_connection_failed(
+			information: stu::Information,
+			what: stu::Error) -> String;
+		fn management_failed(what: crate::window::ManagementError) -> String;
+		fn window_creation(what: nwg::NwgError) -> String;
+		fn invalid_file() -> &'static str;
+		fn file_not_found() -> &'static str;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn set_language_changes_the_strings() {
+		set_language(Language::EnUs);
+		assert_eq!(language(), Language::EnUs);
+		assert_eq!(manager::title(), en_US::manager::title());
+
+		set_language(Language::PtBr);
+		assert_eq!(language(), Language::PtBr);
+		assert_eq!(manager::title(), pt_BR::manager::title());
+		assert_ne!(en_US::manager::title(), pt_BR::manager::title());
+	}
+}
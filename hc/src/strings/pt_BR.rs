@@ -17,7 +17,20 @@ pub mod manager {
 	}
 	pub fn display_clear_btn() -> &'static str { "Limpar" }
 	pub fn display_paint_btn() -> &'static str { "Assinar" }
+	pub fn undo_btn() -> &'static str { "Desfazer" }
+	pub fn redo_btn() -> &'static str { "Refazer" }
+	pub fn save_btn() -> &'static str { "Salvar" }
+	pub fn copy_btn() -> &'static str { "Copiar" }
+	pub fn save_title() -> &'static str { "Salvar a assinatura" }
+	pub fn save_filter_png() -> &'static str { "Imagem PNG" }
+	pub fn save_filter_bmp() -> &'static str { "Imagem Bitmap" }
 	pub fn display_label() -> &'static str { "Oncologia Clínica - HC FMRP - USP" }
+	pub fn dropped_reports(what: stu::Error) -> String {
+		format!(
+			"Partes da assinatura podem ter sido perdidas no caminho a partir \
+			do dispositivo ({}). Limpe a assinatura e assine novamente.",
+			what)
+	}
 }
 
 /// Strings used in the device selection window.
@@ -30,6 +43,10 @@ pub mod selector {
 
 /// Strings used in the area selection window.
 pub mod area {
+	pub fn title() -> &'static str { "Seleção de Área" }
+	pub fn dimensions(width: u32, height: u32) -> String {
+		format!("{} x {} pixels", width, height)
+	}
 	pub fn tip() -> &'static str {
 		"Selecione uma regiao clicando e arrastando em qualquer parte da tela. \
 		Pressione 'e' para confirmar a regiao selecionada e 'q' para cancelar."
@@ -48,11 +65,27 @@ pub mod bitmap {
 	pub fn file_select_filter_all() -> &'static str {
 		"Todos os arquivos"
 	}
+	pub fn threshold_label(threshold: u8) -> String {
+		format!("Limiar de contraste: {}", threshold)
+	}
+	pub fn invert_check() -> &'static str { "Inverter" }
 	pub fn cancel_btn() -> &'static str { "Cancelar" }
 	pub fn display_paint_btn() -> &'static str { "Assinar" }
 	pub fn title() -> &'static str { "Assinatura contida no arquivo" }
 }
 
+/// Strings used in the playback controls.
+pub mod playback {
+	pub fn speed_slow() -> &'static str { "Velocidade lenta" }
+	pub fn speed_normal() -> &'static str { "Velocidade normal" }
+	pub fn speed_fast() -> &'static str { "Velocidade rápida" }
+}
+
+/// Strings used in the windowless capture mode.
+pub mod capture {
+	pub fn finish_prompt() -> &'static str { "Assine no tablet e pressione Enter para terminar." }
+}
+
 /// Strings used in error messages.
 pub mod errors {
 	use nwg::NwgError;
@@ -63,9 +96,18 @@ pub mod errors {
 		format!("Não foi possível mostrar a seleção de região de pintura: {}",
 			what)
 	}
+	pub fn invalid_playback(
+		what: crate::robot::InvalidPlayback) -> String {
+		format!("Não foi possível pintar a assinatura: {}", what)
+	}
 	pub fn no_tablets_available() -> &'static str {
 		"Não há dispositivos de entrada de assinatura disponíveis neste sistema"
 	}
+	pub fn device_listing_failed(
+		what: stu::Error) -> String {
+		format!("Não foi possível listar os dispositivos de entrada de \
+			assinatura disponíveis: {}", what)
+	}
 	pub fn device_prompt_creation_failed(
 		what: nwg::NwgError) -> String {
 		format!("Não foi possível criar a janela de seleção de dispositivo de \
@@ -74,9 +116,9 @@ pub mod errors {
 	pub fn tablet_not_found(
 		information: stu::Information) -> String {
 		format!(
-			"Não foi possível encontrar o dispositivo \"{} - {:04x}:{:04x}\". \
+			"Não foi possível encontrar o dispositivo \"{}\". \
 			Certifique-se que esse não foi desconectado.",
-			information.device(), information.vendor(), information.product())
+			information)
 	}
 	pub fn tablet_connection_failed(
 		information: stu::Information,
@@ -84,13 +126,11 @@ pub mod errors {
 		format!(
 			"\
 				Não foi possível conectar-se ao dispositivo \
-				\"{} - {:04x}:{:04x}\": {}.\n\n\
+				\"{}\": {}.\n\n\
 				\
 				Error: {:?}\
 			",
-			information.device(),
-			information.vendor(),
-			information.product(),
+			information,
 			what, what)
 	}
 	pub fn management_failed(
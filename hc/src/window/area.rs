@@ -1,14 +1,16 @@
 use crate::robot::ScreenArea;
 use std::cell::RefCell;
-use std::convert::TryFrom;
 use std::ffi::CString;
 
 /// Display a window control that lets the user select a rectangular region on
 /// the screen. This is intended for use with the signature painting
 /// functionality.
+///
+/// Along with the region, this returns what the screen looked like under it
+/// when the prompt was opened, if the capture could be copied out.
 pub fn pick_physical_area(
 	parameters: AreaSelectionParameters)
-	-> Result<ScreenArea, PickPhysicalAreaError> {
+	-> Result<(ScreenArea, Option<image::RgbImage>), PickPhysicalAreaError> {
 
 	let (tx, rx) = std::sync::mpsc::channel();
 	let window = AreaSelection::new(parameters, tx);
@@ -29,6 +31,93 @@ pub struct AreaSelectionParameters {
 	pub preferred_dimensions: (u32, u32),
 }
 
+/// The distance between the cursor and the label with the dimensions of the
+/// selection, along both axes, in pixels.
+const LABEL_OFFSET: i32 = 16;
+
+/// The largest the label with the dimensions of the selection gets, including
+/// its outline, in pixels.
+const LABEL_EXTENT: (u32, u32) = (256, 32);
+
+/// The parts of the window that follow the mouse, painted over the capture.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct Overlay {
+	/// The area selection on the screen.
+	selection: ScreenArea,
+	/// The position of the cursor on the screen, if it has been seen yet.
+	cursor: Option<(i32, i32)>,
+}
+
+/// The areas of the virtual desktop that have to be repainted for the overlay
+/// to go from the old state to the new one.
+///
+/// These are the union of the old and the new selections, along with the rows
+/// and columns of the crosshairs and the labels at both cursor positions.
+fn dirty_areas(desktop: ScreenArea, old: Overlay, new: Overlay) -> Vec<ScreenArea> {
+	let mut areas = Vec::with_capacity(7);
+
+	let selection = union(old.selection, new.selection);
+	if !is_empty(selection) {
+		areas.push(selection);
+	}
+
+	for (x, y) in [old.cursor, new.cursor].iter().copied().flatten() {
+		areas.push(ScreenArea { x, y: desktop.y, width: 1, height: desktop.height });
+		areas.push(ScreenArea { x: desktop.x, y, width: desktop.width, height: 1 });
+		areas.push(ScreenArea {
+			x: x + LABEL_OFFSET - 1,
+			y: y + LABEL_OFFSET - 1,
+			width: LABEL_EXTENT.0,
+			height: LABEL_EXTENT.1
+		});
+	}
+
+	areas
+}
+
+/// The smallest area containing both of the given areas, ignoring empty ones.
+fn union(a: ScreenArea, b: ScreenArea) -> ScreenArea {
+	if is_empty(a) { return b }
+	if is_empty(b) { return a }
+
+	let x0 = a.x.min(b.x);
+	let y0 = a.y.min(b.y);
+	let x1 = (i64::from(a.x) + i64::from(a.width)).max(i64::from(b.x) + i64::from(b.width));
+	let y1 = (i64::from(a.y) + i64::from(a.height)).max(i64::from(b.y) + i64::from(b.height));
+
+	ScreenArea {
+		x: x0,
+		y: y0,
+		width: (x1 - i64::from(x0)) as u32,
+		height: (y1 - i64::from(y0)) as u32
+	}
+}
+
+/// Clips the given area of the virtual desktop to the capture of the given
+/// desktop, which it may hang off of, returning the bounds of the clipped area
+/// relative to the capture, as the positions of its top left and bottom right
+/// corners, in that order.
+///
+/// If none of the area lies within the capture, this function returns
+/// [`None`].
+fn clip(area: ScreenArea, desktop: ScreenArea) -> Option<(u32, u32, u32, u32)> {
+	let x0 = i64::from(area.x) - i64::from(desktop.x);
+	let y0 = i64::from(area.y) - i64::from(desktop.y);
+	let x1 = (x0 + i64::from(area.width)).min(i64::from(desktop.width));
+	let y1 = (y0 + i64::from(area.height)).min(i64::from(desktop.height));
+	let (x0, y0) = (x0.max(0), y0.max(0));
+	if x0 >= x1 || y0 >= y1 {
+		return None
+	}
+
+	Some((x0 as u32, y0 as u32, x1 as u32, y1 as u32))
+}
+
+/// Whether the given area covers no pixels at all.
+fn is_empty(area: ScreenArea) -> bool {
+	area.width == 0 || area.height == 0
+}
+
 /// The structure controlling the physical area selection.
 #[derive(nwd::NwgUi)]
 pub struct AreaSelection {
@@ -38,7 +127,6 @@ pub struct AreaSelection {
 
 	/// The top level window this controller is contained in.
 	#[nwg_control(
-		title: "Area Selection",
 		flags: "WINDOW",
 		icon: Some(&data.icon),
 	)]
@@ -57,6 +145,15 @@ pub struct AreaSelection {
 
 	/// The bitmap containing a screen capture.
 	screen: RefCell<winapi::shared::windef::HBITMAP>,
+	/// The bitmap containing the screen capture, shaded the way it is shown
+	/// outside of the selection.
+	///
+	/// Shading is done once, up front, so that repaints, which happen with
+	/// every move of the mouse, only have to copy bitmaps around.
+	shaded: RefCell<winapi::shared::windef::HBITMAP>,
+
+	/// The area of the virtual desktop covered by the window and the capture.
+	desktop: RefCell<ScreenArea>,
 
 	/// The parameters for the selection operation.
 	params: AreaSelectionParameters,
@@ -71,20 +168,24 @@ pub struct AreaSelection {
 
 	/// The current area selection on the screen.
 	selection: RefCell<ScreenArea>,
+	/// The overlay as of the last time the window was marked as dirty.
+	overlay: RefCell<Overlay>,
 
 	/// The channel through which we report our result.
-	channel: std::sync::mpsc::Sender<Result<ScreenArea, PickPhysicalAreaError>>,
+	channel: std::sync::mpsc::Sender<Result<(ScreenArea, Option<image::RgbImage>), PickPhysicalAreaError>>,
 }
 impl AreaSelection {
 	fn new(
 		params: AreaSelectionParameters,
-		channel:std::sync::mpsc::Sender<Result<ScreenArea, PickPhysicalAreaError>>)
+		channel:std::sync::mpsc::Sender<Result<(ScreenArea, Option<image::RgbImage>), PickPhysicalAreaError>>)
 		-> Self {
 
 		Self {
 			icon: Default::default(),
 			window: Default::default(),
 			screen: RefCell::new(std::ptr::null_mut()),
+			shaded: RefCell::new(std::ptr::null_mut()),
+			desktop: RefCell::new(ScreenArea::virtual_desktop()),
 			params,
 			mouse_pressed: RefCell::new(false),
 			lock_to_preferred_aspect_ratio: RefCell::new(false),
@@ -95,6 +196,15 @@ impl AreaSelection {
 				width: 0,
 				height: 0
 			}),
+			overlay: RefCell::new(Overlay {
+				selection: ScreenArea {
+					x: 0,
+					y: 0,
+					width: 0,
+					height: 0
+				},
+				cursor: None
+			}),
 			channel
 		}
 	}
@@ -108,8 +218,8 @@ impl AreaSelection {
 
 					*self.mouse_anchor.borrow_mut() = anchor;
 					*self.selection.borrow_mut() = ScreenArea {
-						x: anchor.0.max(0),
-						y: anchor.1.max(0),
+						x: anchor.0,
+						y: anchor.1,
 						width: 0,
 						height: 0
 					};
@@ -133,10 +243,11 @@ impl AreaSelection {
 					return
 				}
 
-				let _ = self.channel.send(Ok(area));
+				let capture = unsafe { self.crop(area) };
+				let _ = self.channel.send(Ok((area, capture)));
 				nwg::stop_thread_dispatch();
 			},
-			nwg::keys::_Q => {
+			nwg::keys::_Q | nwg::keys::ESCAPE => {
 				let _ = self.channel.send(Err(PickPhysicalAreaError::Cancelled));
 				nwg::stop_thread_dispatch()
 			},
@@ -159,16 +270,53 @@ impl AreaSelection {
 
 	/// Called when the mouse has moved on the screen.
 	fn on_mouse_move(&self) {
-		if !*self.mouse_pressed.borrow() { return }
+		if *self.mouse_pressed.borrow() {
+			self.resize_selection();
+		}
+
+		/* Mark the parts of the window the overlay has changed in as being
+		 * dirty, so that the cursor overlay follows the mouse even when no
+		 * region is being selected, without repainting the whole desktop. */
+		let overlay = Overlay {
+			selection: *self.selection.borrow(),
+			cursor: Some(nwg::GlobalCursor::position())
+		};
+		let old = self.overlay.replace(overlay);
+		let desktop = *self.desktop.borrow();
+
+		unsafe {
+			let hwnd = self.window.handle.hwnd().unwrap();
+
+			use winapi::um::winuser as user;
+			use winapi::um::errhandlingapi::GetLastError;
 
+			for area in dirty_areas(desktop, old, overlay) {
+				let rect = winapi::shared::windef::RECT {
+					left: area.x - desktop.x,
+					top: area.y - desktop.y,
+					right: area.x - desktop.x + area.width as i32,
+					bottom: area.y - desktop.y + area.height as i32
+				};
+
+				let result = user::InvalidateRect(hwnd, &rect, 0);
+				if result == 0 {
+					self.fail(PickPhysicalAreaError::WindowLogicError {
+						scope: format!("AreaSelection::on_mouse_move({:p})", self),
+						message: format!("InvalidateRect({:p}, {:p}, {}) has failed: 0x{:08x}",
+							hwnd, &rect, 0, GetLastError())
+					});
+					return
+				}
+			}
+		}
+	}
+
+	/// Resizes the selection region to follow the mouse cursor.
+	fn resize_selection(&self) {
 		/* Resize the physical selection region. */
 		let (x, y) = nwg::GlobalCursor::position();
-		let x = x.max(0);
-		let y = y.max(0);
 
 		let (ax, ay) = *self.mouse_anchor.borrow();
-		let ax = ax.max(0);
-		let ay = ay.max(0);
 
 		let (x, y, width, height) = if !*self.lock_to_preferred_aspect_ratio.borrow() {
 			let (x, width) = if x < ax {
@@ -218,33 +366,6 @@ impl AreaSelection {
 			width: width as u32,
 			height: height as u32
 		};
-
-		/* Mark the window as being dirty. */
-		unsafe {
-			let hwnd = self.window.handle.hwnd().unwrap();
-
-			use winapi::um::winuser as user;
-			use winapi::um::errhandlingapi::GetLastError;
-
-			let mut rect = std::mem::zeroed();
-			let result = user::GetClientRect(hwnd, &mut rect);
-			if result == 0 {
-				self.fail(PickPhysicalAreaError::WindowLogicError {
-					scope: format!("AreaSelection::on_mouse_move({:p})", self),
-					message: format!("GetClientRect({:p}, {:p}) has failed: 0x{:08x}",
-						hwnd, &rect, GetLastError())
-				})
-			}
-
-			let result = user::InvalidateRect(hwnd, &rect, 0);
-			if result == 0 {
-				self.fail(PickPhysicalAreaError::WindowLogicError {
-					scope: format!("AreaSelection::on_mouse_move({:p})", self),
-					message: format!("InvalidateRect({:p}, {:p}, {}) has failed: 0x{:08x}",
-						hwnd, &rect, 1, GetLastError())
-				})
-			}
-		}
 	}
 
 	/// Called when the window has been closed.
@@ -352,9 +473,23 @@ impl AreaSelection {
 			return
 		}
 
-		/* Paint the screenshot over the window. */
-		let screen = self.screen.borrow();
-		if !screen.is_null() {
+		/* Paint the shaded screenshot over the window, then the unshaded one
+		 * over the selection. */
+		let desktop = *self.desktop.borrow();
+		let selection = *self.selection.borrow();
+		let layers = [
+			(*self.shaded.borrow(), 0, 0, width, height),
+			(
+				*self.screen.borrow(),
+				selection.x - desktop.x,
+				selection.y - desktop.y,
+				selection.width as i32,
+				selection.height as i32
+			),
+		];
+		for (bitmap, x, y, width, height) in layers {
+			if bitmap.is_null() || width == 0 || height == 0 { continue }
+
 			let dc = gdi::CreateCompatibleDC(target_dc);
 			if dc.is_null() {
 				self.fail(PickPhysicalAreaError::WindowLogicError {
@@ -366,14 +501,14 @@ impl AreaSelection {
 				return
 			}
 
-			let replaced = gdi::SelectObject(dc, *screen as _);
+			let replaced = gdi::SelectObject(dc, bitmap as _);
 			if replaced.is_null() {
 				self.fail(PickPhysicalAreaError::WindowLogicError {
 					scope: format!("AreaSelection::paint({:p})", self),
 					message: format!(
 						"SelectObject({:p}, {:p}) failed: 0x{:08x}",
 						dc,
-						*screen,
+						bitmap,
 						GetLastError())
 				});
 				return
@@ -381,13 +516,13 @@ impl AreaSelection {
 
 			let result = gdi::BitBlt(
 				target_dc,
-				0,
-				0,
-				width as _,
-				height as _,
+				x,
+				y,
+				width,
+				height,
 				dc,
-				0,
-				0,
+				x,
+				y,
 				gdi::SRCCOPY);
 			if result == 0 {
 				self.fail(PickPhysicalAreaError::WindowLogicError {
@@ -395,13 +530,13 @@ impl AreaSelection {
 					message: format!(
 						"BitBlit({:p}, {}, {}, {}, {}, {:p}, {}, {}, 0x{:08x}) failed: 0x{:08x}",
 						target_dc,
-						0,
-						0,
+						x,
+						y,
 						width,
 						height,
 						dc,
-						0,
-						0,
+						x,
+						y,
 						gdi::SRCCOPY,
 						GetLastError())
 				});
@@ -410,170 +545,54 @@ impl AreaSelection {
 
 			let _ = gdi::SelectObject(dc, replaced);
 			let _ = gdi::DeleteDC(dc);
-		};
+		}
 
-		/* Paint the shading and selected areas and blend them on to the client
-		 * screen. */
+		/* Paint the crosshair and the dimensions of the selection next to the
+		 * cursor. */
 		let _ = {
-			let dc = gdi::CreateCompatibleDC(target_dc);
-			if dc.is_null() {
-				self.fail(PickPhysicalAreaError::WindowLogicError {
-					scope: format!("AreaSelection::paint({:p})", self),
-					message: format!("CreateCompatibleDC({:p}) failed: 0x{:08x}",
+			let mut cursor = std::mem::zeroed();
+			if user::GetCursorPos(&mut cursor) != 0
+				&& user::ScreenToClient(hwnd, &mut cursor) != 0 {
+
+				let _ = gdi::PatBlt(target_dc, cursor.x, 0, 1, height, gdi::DSTINVERT);
+				let _ = gdi::PatBlt(target_dc, 0, cursor.y, width, 1, gdi::DSTINVERT);
+
+				let selection = *self.selection.borrow();
+				let string = crate::strings::area::dimensions(
+					selection.width,
+					selection.height)
+					.encode_utf16()
+					.collect::<Vec<_>>();
+
+				/* Draw the text over a contrasting outline, so that it stays
+				 * readable over both light and dark backgrounds. */
+				let _ = gdi::SetTextAlign(target_dc, gdi::TA_LEFT | gdi::TA_TOP);
+				let _ = gdi::SetBkMode(target_dc, gdi::TRANSPARENT as _);
+
+				let x = cursor.x + LABEL_OFFSET;
+				let y = cursor.y + LABEL_OFFSET;
+
+				let _ = gdi::SetTextColor(target_dc, gdi::RGB(0, 0, 0));
+				for (ox, oy) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+					let _ = gdi::TextOutW(
 						target_dc,
-						GetLastError())
-				});
-				return
-			}
-
-			let mut info = std::mem::zeroed::<gdi::BITMAPINFO>();
-			info.bmiHeader.biSize = std::mem::size_of::<gdi::BITMAPINFO>() as _;
-			info.bmiHeader.biPlanes = 1;
-			info.bmiHeader.biBitCount = 32;
-			info.bmiHeader.biCompression = gdi::BI_RGB;
-			info.bmiHeader.biWidth = width;
-			info.bmiHeader.biHeight = -height;
-
-			let mut buffer = std::ptr::null_mut();
-			let bitmap = gdi::CreateDIBSection(
-				paint.hdc,
-				&info,
-				gdi::DIB_RGB_COLORS,
-				&mut buffer,
-				std::ptr::null_mut(),
-				0);
-			if bitmap.is_null() || buffer.is_null() {
-				self.fail(PickPhysicalAreaError::WindowLogicError {
-					scope: format!("AreaSelection::paint({:p})", self),
-					message: format!(
-						"CreateDIBSection({:p}, {:p}, 0x{:08x}, {:p}, {:p}, \
-							{}) failed: 0x{:08x}",
-						paint.hdc,
-						&info,
-						gdi::DIB_RGB_COLORS,
-						&mut buffer,
-						std::ptr::null_mut::<()>(),
-						0,
-						GetLastError())
-				});
-				return
-			}
-
-			/* Before we render to the bitmap buffer, make sure there are no
-			 * pending GDI operations that could cause a race condition. */
-			let _ = gdi::GdiFlush();
-
-			/* Render to the bitmap buffer. */
-			let buffer = {
-				let offset = buffer.align_offset(std::mem::align_of::<u8>());
-				if offset != 0 {
-					self.fail(PickPhysicalAreaError::WindowLogicError {
-						scope: format!("AreaSelection::paint({:p})", self),
-						message: format!(
-							"Bitmap buffer at {:p} is not byte-aligned",
-							buffer)
-					});
-					return
+						x + ox,
+						y + oy,
+						string.as_ptr(),
+						string.len() as _);
 				}
 
-				let width = width.abs();
-				let height = height.abs();
-
-				let pixels = width as u64 * height as u64;
-				let length = pixels.checked_mul(4)
-					.and_then(|bytes| usize::try_from(bytes).ok());
-				let length = match length {
-					Some(length) => length,
-					None => {
-						self.fail(PickPhysicalAreaError::WindowLogicError {
-							scope: format!("AreaSelection::paint({:p})", self),
-							message: format!(
-								"Number of bytes in the bitmap {} * {} * {} \
-								does not fit in a usize",
-								width, height, 4)
-						});
-						return
-					}
-				};
-
-				std::slice::from_raw_parts_mut(buffer as *mut u8, length)
-			};
-			for (i, slice) in buffer.chunks_exact_mut(4).enumerate() {
-				let x = (i % width.abs() as usize) as i32;
-				let y = (i / width.abs() as usize) as i32;
-
-				let selection = self.selection.borrow();
-				let horizontal = x >= selection.x && x < selection.x + selection.width as i32;
-				let vertical = y >= selection.y && y < selection.y + selection.height as i32;
-
-				slice[0] = 0;
-				slice[1] = 0;
-				slice[2] = 0;
-				slice[3] = if horizontal && vertical {
-					0
-				} else {
-					127
-				};
-			}
-
-			/* Blend the bitmap we just rendered on to the window. */
-			let replaced = gdi::SelectObject(dc, bitmap as _);
-			if replaced.is_null() {
-				self.fail(PickPhysicalAreaError::WindowLogicError {
-					scope: format!("AreaSelection::paint({:p})", self),
-					message: format!(
-						"SelectObject({:p}, {:p}) failed: 0x{:08x}",
-						dc,
-						bitmap,
-						GetLastError())
-				});
-				return
-			}
-
-			let mut alpha = std::mem::zeroed::<gdi::BLENDFUNCTION>();
-			alpha.BlendOp = gdi::AC_SRC_OVER;
-			alpha.BlendFlags = 0;
-			alpha.SourceConstantAlpha = 255;
-			alpha.AlphaFormat = gdi::AC_SRC_ALPHA;
+				let _ = gdi::SetTextColor(target_dc, gdi::RGB(255, 255, 255));
+				let _ = gdi::TextOutW(
+					target_dc,
+					x,
+					y,
+					string.as_ptr(),
+					string.len() as _);
 
-			let result = gdi::AlphaBlend(
-				target_dc,
-				0,
-				0,
-				width as _,
-				height as _,
-				dc,
-				0,
-				0,
-				width as _,
-				height as _,
-				alpha);
-			if result == 0 {
-				self.fail(PickPhysicalAreaError::WindowLogicError {
-					scope: format!("AreaSelection::paint({:p})", self),
-					message: format!(
-						"AlphaBlend({:p}, {}, {}, {}, {}, {:p}, {}, {}, {}, \
-							{}, {:p}) failed: 0x{:08x}",
-						target_dc,
-						0,
-						0,
-						width,
-						height,
-						dc,
-						0,
-						0,
-						width,
-						height,
-						&alpha,
-						GetLastError())
-				});
-				return
+				let _ = gdi::SetTextColor(target_dc, gdi::RGB(0, 0, 0));
+				let _ = gdi::SetBkMode(target_dc, gdi::OPAQUE as _);
 			}
-
-			/* Clean up. */
-			let _ = gdi::SelectObject(dc, replaced);
-			let _ = gdi::DeleteObject(bitmap as _);
-			let _ = gdi::DeleteDC(dc);
 		};
 
 		/* Paint the tooltip UI. */
@@ -644,8 +663,135 @@ impl AreaSelection {
 		nwg::stop_thread_dispatch();
 	}
 
+	/// Copies the part of the screen capture under the given area of the
+	/// virtual desktop out into an image.
+	unsafe fn crop(&self, area: ScreenArea) -> Option<image::RgbImage> {
+		use winapi::um::wingdi as gdi;
+		use winapi::um::winuser as user;
+
+		let screen = *self.screen.borrow();
+		let desktop = *self.desktop.borrow();
+		if screen.is_null() || desktop.width == 0 || desktop.height == 0 {
+			return None
+		}
+
+		/* Read the whole capture as top-down rows of BGRX pixels. */
+		let mut info = std::mem::zeroed::<gdi::BITMAPINFO>();
+		info.bmiHeader.biSize = std::mem::size_of::<gdi::BITMAPINFOHEADER>() as _;
+		info.bmiHeader.biWidth = desktop.width as _;
+		info.bmiHeader.biHeight = -(desktop.height as i32);
+		info.bmiHeader.biPlanes = 1;
+		info.bmiHeader.biBitCount = 32;
+		info.bmiHeader.biCompression = gdi::BI_RGB;
+
+		let mut pixels = vec![0u8; desktop.width as usize * desktop.height as usize * 4];
+		let screen_dc = user::GetDC(user::HWND_DESKTOP);
+		if screen_dc.is_null() {
+			return None
+		}
+		let lines = gdi::GetDIBits(
+			screen_dc,
+			screen,
+			0,
+			desktop.height,
+			pixels.as_mut_ptr() as _,
+			&mut info,
+			gdi::DIB_RGB_COLORS);
+		let _ = user::ReleaseDC(user::HWND_DESKTOP, screen_dc);
+		if lines == 0 {
+			return None
+		}
+
+		let (x0, y0, x1, y1) = clip(area, desktop)?;
+		let image = image::RgbImage::from_fn(
+			x1 - x0,
+			y1 - y0,
+			|x, y| {
+				let x = x0 as usize + x as usize;
+				let y = y0 as usize + y as usize;
+				let i = (y * desktop.width as usize + x) * 4;
+
+				image::Rgb([pixels[i + 2], pixels[i + 1], pixels[i]])
+			});
+		Some(image)
+	}
+
+	/// Creates a shaded copy of the screen capture currently selected into the
+	/// given device context.
+	unsafe fn shade(
+		&self,
+		screen_dc: winapi::shared::windef::HDC,
+		source_dc: winapi::shared::windef::HDC,
+		width: i32,
+		height: i32) -> Option<winapi::shared::windef::HBITMAP> {
+
+		use winapi::um::wingdi as gdi;
+		use winapi::um::errhandlingapi::GetLastError;
+
+		let fail = |message: String| {
+			self.fail(PickPhysicalAreaError::WindowLogicError {
+				scope: format!("AreaSelection::shade({:p})", self),
+				message
+			});
+		};
+
+		let dc = gdi::CreateCompatibleDC(screen_dc);
+		if dc.is_null() {
+			fail(format!("CreateCompatibleDC({:p}) failed: 0x{:08x}",
+				screen_dc, GetLastError()));
+			return None
+		}
+
+		/* A single black pixel, stretched over the whole capture. */
+		let black = gdi::CreateCompatibleBitmap(screen_dc, 1, 1);
+		let shaded = gdi::CreateCompatibleBitmap(screen_dc, width, height);
+		if black.is_null() || shaded.is_null() {
+			fail(format!("CreateCompatibleBitmap({:p}) failed: 0x{:08x}",
+				screen_dc, GetLastError()));
+			if !black.is_null() { let _ = gdi::DeleteObject(black as _); }
+			if !shaded.is_null() { let _ = gdi::DeleteObject(shaded as _); }
+			let _ = gdi::DeleteDC(dc);
+			return None
+		}
+
+		let black_dc = gdi::CreateCompatibleDC(screen_dc);
+		let replaced_black = gdi::SelectObject(black_dc, black as _);
+		let _ = gdi::PatBlt(black_dc, 0, 0, 1, 1, gdi::BLACKNESS);
+
+		let replaced = gdi::SelectObject(dc, shaded as _);
+		let copied = gdi::BitBlt(dc, 0, 0, width, height, source_dc, 0, 0, gdi::SRCCOPY);
+
+		let mut alpha = std::mem::zeroed::<gdi::BLENDFUNCTION>();
+		alpha.BlendOp = gdi::AC_SRC_OVER;
+		alpha.BlendFlags = 0;
+		alpha.SourceConstantAlpha = 127;
+		alpha.AlphaFormat = 0;
+
+		let blended = gdi::AlphaBlend(
+			dc, 0, 0, width, height,
+			black_dc, 0, 0, 1, 1,
+			alpha);
+
+		let _ = gdi::SelectObject(black_dc, replaced_black);
+		let _ = gdi::DeleteDC(black_dc);
+		let _ = gdi::DeleteObject(black as _);
+		let _ = gdi::SelectObject(dc, replaced);
+		let _ = gdi::DeleteDC(dc);
+
+		if copied == 0 || blended == 0 {
+			fail(format!("Shading the screen capture failed: 0x{:08x}",
+				GetLastError()));
+			let _ = gdi::DeleteObject(shaded as _);
+			return None
+		}
+
+		Some(shaded)
+	}
+
 	/// Initialize the screen.
 	fn init(&self) {
+		self.window.set_text(crate::strings::area::title());
+
 		/* Take a screenshot of the currently visible desktop. */
 		let screenshot = unsafe {
 			use winapi::um::wingdi as gdi;
@@ -673,8 +819,13 @@ impl AreaSelection {
 				return
 			}
 
-			let width = gdi::GetDeviceCaps(screen_dc, gdi::HORZRES);
-			let height = gdi::GetDeviceCaps(screen_dc, gdi::VERTRES);
+			/* Capture the whole of the virtual desktop, rather than just the
+			 * primary monitor. */
+			let desktop = ScreenArea::virtual_desktop();
+			*self.desktop.borrow_mut() = desktop;
+
+			let width = desktop.width as i32;
+			let height = desktop.height as i32;
 
 			let bitmap = gdi::CreateCompatibleBitmap(screen_dc, width, height);
 			if bitmap.is_null() {
@@ -696,36 +847,42 @@ impl AreaSelection {
 				return
 			}
 
-			let w = user::GetSystemMetrics(user::SM_CXSCREEN);
-			let h = user::GetSystemMetrics(user::SM_CYSCREEN);
-
 			let result = gdi::BitBlt(
 				compat_dc,
 				0,
 				0,
-				w,
-				h,
+				width,
+				height,
 				screen_dc,
-				0,
-				0,
+				desktop.x,
+				desktop.y,
 				gdi::SRCCOPY | gdi::CAPTUREBLT);
 			if result == 0 {
 				self.fail(PickPhysicalAreaError::WindowLogicError {
 					scope: format!("AreaSelection::init({:p})", self),
 					message: format!("BitBlt({:p}, {}, {}, {}, {}, {:?}, {}, {}, 0x{:08x}) failed: 0x{:08x}",
-						compat_dc, 0, 0, w, h, screen_dc, 0, 0, gdi::SRCCOPY | gdi::CAPTUREBLT, GetLastError())
+						compat_dc, 0, 0, width, height, screen_dc, desktop.x, desktop.y, gdi::SRCCOPY | gdi::CAPTUREBLT, GetLastError())
 				});
 				return
 			}
 
+			let shaded = self.shade(screen_dc, compat_dc, width, height);
+
 			let _ = gdi::SelectObject(compat_dc, replaced);
 			let _ = gdi::DeleteDC(compat_dc);
 			let _ = user::ReleaseDC(user::HWND_DESKTOP, screen_dc);
 
-			bitmap
+			match shaded {
+				Some(shaded) => (bitmap, shaded),
+				None => {
+					let _ = gdi::DeleteObject(bitmap as _);
+					return
+				}
+			}
 		};
 
-		*self.screen.borrow_mut() = screenshot;
+		*self.screen.borrow_mut() = screenshot.0;
+		*self.shaded.borrow_mut() = screenshot.1;
 
 		/* Make the main window full screen and show it. */
 		unsafe {
@@ -741,16 +898,14 @@ impl AreaSelection {
 				user::GWL_EXSTYLE,
 				0);
 
-			let w = user::GetSystemMetrics(user::SM_CXSCREEN);
-			let h = user::GetSystemMetrics(user::SM_CYSCREEN);
-
+			let desktop = *self.desktop.borrow();
 			let _ = user::SetWindowPos(
 				hwnd,
 				std::ptr::null_mut(),
-				0,
-				0,
-				w,
-				h,
+				desktop.x,
+				desktop.y,
+				desktop.width as _,
+				desktop.height as _,
 				user::SWP_FRAMECHANGED);
 		}
 		self.window.set_visible(true);
@@ -760,9 +915,11 @@ impl AreaSelection {
 impl Drop for AreaSelection {
 	fn drop(&mut self) {
 		unsafe {
-			let screen = self.screen.borrow();
-			if !screen.is_null() {
-				winapi::um::wingdi::DeleteObject(*screen as _);
+			for bitmap in [&self.screen, &self.shaded] {
+				let bitmap = bitmap.borrow();
+				if !bitmap.is_null() {
+					winapi::um::wingdi::DeleteObject(*bitmap as _);
+				}
 			}
 		}
 	}
@@ -787,3 +944,89 @@ pub enum PickPhysicalAreaError {
 	#[error("the operation was cancelled")]
 	Cancelled,
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A virtual desktop made up of a single full HD monitor.
+	const DESKTOP: ScreenArea = ScreenArea { x: 0, y: 0, width: 1920, height: 1080 };
+
+	/// Whether any of the given areas covers the given pixel.
+	fn covers(areas: &[ScreenArea], x: i32, y: i32) -> bool {
+		areas.iter().any(|area| x >= area.x
+			&& y >= area.y
+			&& i64::from(x) < i64::from(area.x) + i64::from(area.width)
+			&& i64::from(y) < i64::from(area.y) + i64::from(area.height))
+	}
+
+	/// A virtual desktop with a second monitor to the left of the primary one,
+	/// and sitting higher than it.
+	const EXTENDED: ScreenArea = ScreenArea { x: -1920, y: -120, width: 3840, height: 1200 };
+
+	#[test]
+	fn crop_clips_areas_hanging_off_a_negative_origin() {
+		let area = ScreenArea { x: -2000, y: -200, width: 300, height: 300 };
+		assert_eq!(clip(area, EXTENDED), Some((0, 0, 220, 220)));
+	}
+
+	#[test]
+	fn crop_maps_areas_relative_to_a_negative_origin() {
+		let area = ScreenArea { x: 100, y: 100, width: 50, height: 50 };
+		assert_eq!(clip(area, EXTENDED), Some((2020, 220, 2070, 270)));
+
+		let area = ScreenArea { x: 1800, y: 1000, width: 500, height: 500 };
+		assert_eq!(clip(area, EXTENDED), Some((3720, 1120, 3840, 1200)));
+	}
+
+	#[test]
+	fn crop_rejects_areas_outside_of_the_desktop() {
+		let area = ScreenArea { x: -2500, y: 0, width: 300, height: 300 };
+		assert_eq!(clip(area, EXTENDED), None);
+
+		let area = ScreenArea { x: 0, y: 0, width: 0, height: 300 };
+		assert_eq!(clip(area, EXTENDED), None);
+	}
+
+	#[test]
+	fn union_spans_both_areas() {
+		let a = ScreenArea { x: -100, y: 10, width: 50, height: 20 };
+		let b = ScreenArea { x: 200, y: -30, width: 10, height: 10 };
+
+		assert_eq!(union(a, b), ScreenArea { x: -100, y: -30, width: 310, height: 60 });
+	}
+
+	#[test]
+	fn union_ignores_empty_areas() {
+		let a = ScreenArea { x: 10, y: 10, width: 50, height: 20 };
+		let empty = ScreenArea { x: 500, y: 500, width: 0, height: 0 };
+
+		assert_eq!(union(a, empty), a);
+		assert_eq!(union(empty, a), a);
+	}
+
+	#[test]
+	fn mouse_move_dirties_only_what_changed() {
+		let old = Overlay {
+			selection: ScreenArea { x: 100, y: 100, width: 50, height: 50 },
+			cursor: Some((150, 150))
+		};
+		let new = Overlay {
+			selection: ScreenArea { x: 100, y: 100, width: 60, height: 60 },
+			cursor: Some((160, 160))
+		};
+		let areas = dirty_areas(DESKTOP, old, new);
+
+		/* Both selections, crosshairs and labels. */
+		assert!(covers(&areas, 100, 100));
+		assert!(covers(&areas, 159, 159));
+		assert!(covers(&areas, 150, 1000) && covers(&areas, 160, 1000));
+		assert!(covers(&areas, 1800, 150) && covers(&areas, 1800, 160));
+		assert!(covers(&areas, 150 + LABEL_OFFSET, 150 + LABEL_OFFSET));
+		assert!(covers(&areas, 160 + LABEL_OFFSET, 160 + LABEL_OFFSET));
+
+		/* Nothing else on the desktop. */
+		assert!(!covers(&areas, 1000, 600));
+		assert!(!covers(&areas, 0, 0));
+	}
+}
@@ -1,9 +1,7 @@
 use std::cell::RefCell;
-use std::num::NonZeroU32;
-use std::time::Duration;
 use nwg::{FileDialogAction, NoticeSender, NwgError};
 use crate::path::BitmapPath;
-use crate::robot::Playback;
+use crate::robot::{Playback, PlaybackHandle, PlaybackSpeed};
 use crate::window::{AreaSelectionParameters, PickPhysicalAreaError};
 
 /// Run the bitmap procedure.
@@ -23,14 +21,13 @@ pub fn run(notify: Option<NoticeSender>) -> Result<(), BitmapError> {
 		return Err(BitmapError::Cancelled)
 	}
 	let file = file_dialog.get_selected_item().unwrap();
-	let file = image::open(&file)
-		.map_err(BitmapError::InvalidFile)?;
+	let file = open_image(&file)?;
 	let file = file.to_luma8();
 
 	/* Open the manager and pass the bitmap to it. */
 	let (tx, rx) = std::sync::mpsc::channel();
 
-	let window = BitmapWindow::new(BitmapPath::new(file), tx);
+	let window = BitmapWindow::new(file, tx);
 	let _window = nwg::NativeUi::build_ui(window)
 		.map_err(BitmapError::WindowCreationError)?;
 
@@ -45,6 +42,28 @@ pub fn run(notify: Option<NoticeSender>) -> Result<(), BitmapError> {
 	}
 }
 
+/// Opens and decodes the image in the given file.
+///
+/// The format of the image is picked from the contents of the file rather than
+/// from its extension, so that images saved with the wrong extension, such as
+/// bitmaps renamed to PNG, still open.
+fn open_image<P: AsRef<std::path::Path>>(path: P) -> Result<image::DynamicImage, BitmapError> {
+	let io_error = |what: std::io::Error| match what.kind() {
+		std::io::ErrorKind::NotFound => BitmapError::FileNotFound,
+		_ => BitmapError::InvalidFile(image::ImageError::IoError(what))
+	};
+
+	image::io::Reader::open(path)
+		.map_err(io_error)?
+		.with_guessed_format()
+		.map_err(io_error)?
+		.decode()
+		.map_err(BitmapError::InvalidFile)
+}
+
+/// The height of the threshold slider, in pixels.
+const SLIDER_HEIGHT: u32 = 30;
+
 #[derive(nwd::NwgUi)]
 pub struct BitmapWindow {
 	/// The icon we're gonna be using for the window.
@@ -53,7 +72,6 @@ pub struct BitmapWindow {
 
 	/// The top level window this controller is contained in.
 	#[nwg_control(
-		title: "Tablet",
 		flags: "WINDOW|MINIMIZE_BOX",
 		center: true,
 		icon: Some(&data.icon),
@@ -61,7 +79,8 @@ pub struct BitmapWindow {
 	)]
 	#[nwg_events(
 		OnInit: [Self::init],
-		OnWindowClose: [Self::on_exit]
+		OnWindowClose: [Self::on_exit],
+		OnKeyPress: [Self::on_key_press(SELF, EVT_DATA)]
 	)]
 	window: nwg::Window,
 
@@ -79,6 +98,36 @@ pub struct BitmapWindow {
 	)]
 	display_label: nwg::Label,
 
+	/// Slider for picking the threshold used to tell the signature apart from
+	/// the background of the image.
+	#[nwg_control(
+		range: Some(0..256),
+		pos: Some(BitmapPath::DEFAULT_THRESHOLD as usize)
+	)]
+	#[nwg_events(
+		OnHorizontalScroll: [Self::on_threshold_changed]
+	)]
+	threshold_slider: nwg::TrackBar,
+
+	/// Label for the threshold slider.
+	#[nwg_control()]
+	threshold_label: nwg::Label,
+
+	/// Check box for inverting the intensities of the image, for signatures
+	/// with light strokes on a dark background.
+	#[nwg_control()]
+	#[nwg_events(
+		OnButtonClick: [Self::on_invert_changed]
+	)]
+	invert_check: nwg::CheckBox,
+
+	/// Drop down for picking the speed at which the signature is painted.
+	#[nwg_control(
+		collection: PlaybackSpeed::ALL.to_vec(),
+		selected_index: Some(1)
+	)]
+	playback_speed: nwg::ComboBox<PlaybackSpeed>,
+
 	/// Button for cancelling the operation.
 	#[nwg_control(
 		position: (10, 150)
@@ -100,9 +149,16 @@ pub struct BitmapWindow {
 	/// Whether the management window is currently locked.
 	locked: RefCell<bool>,
 
+	/// The image the signature data is extracted from.
+	source: image::GrayImage,
+
 	/// The path containing the signature data.
 	path: RefCell<BitmapPath>,
 
+	/// The handle to the playback started by the painting thread, if it has
+	/// started one.
+	playback: std::sync::Arc<std::sync::Mutex<Option<PlaybackHandle>>>,
+
 	/// The notification channel through which we know the painting is done.
 	#[nwg_control()]
 	#[nwg_events(
@@ -122,18 +178,27 @@ pub struct BitmapWindow {
 }
 impl BitmapWindow {
 	fn new(
-		path: BitmapPath,
+		source: image::GrayImage,
 		fails: std::sync::mpsc::Sender<BitmapError>) -> Self {
 
+		let path = BitmapPath::new(source.clone())
+			.trimmed(BitmapPath::DEFAULT_TRIM_MARGIN);
+
 		Self {
 			icon: Default::default(),
 			window: Default::default(),
 			display: Default::default(),
 			display_label: Default::default(),
+			threshold_slider: Default::default(),
+			threshold_label: Default::default(),
+			invert_check: Default::default(),
+			playback_speed: Default::default(),
 			cancel_btn: Default::default(),
 			display_paint_btn: Default::default(),
 			locked: RefCell::new(false),
+			source,
 			path: RefCell::new(path),
+			playback: Default::default(),
 			display_paint_done: Default::default(),
 			area_selection_done: Default::default(),
 			fails
@@ -144,6 +209,9 @@ impl BitmapWindow {
 	fn lock(&self) {
 		self.cancel_btn.set_enabled(false);
 		self.display_paint_btn.set_enabled(false);
+		self.threshold_slider.set_enabled(false);
+		self.invert_check.set_enabled(false);
+		self.playback_speed.set_enabled(false);
 		*self.locked.borrow_mut() = true;
 	}
 
@@ -151,9 +219,19 @@ impl BitmapWindow {
 	fn unlock(&self) {
 		self.cancel_btn.set_enabled(true);
 		self.display_paint_btn.set_enabled(true);
+		self.threshold_slider.set_enabled(true);
+		self.invert_check.set_enabled(true);
+		self.playback_speed.set_enabled(true);
 		*self.locked.borrow_mut() = false;
 	}
 
+	/// The playback speed currently picked by the user.
+	fn playback_speed(&self) -> PlaybackSpeed {
+		self.playback_speed.selection()
+			.and_then(|index| PlaybackSpeed::ALL.get(index).copied())
+			.unwrap_or_default()
+	}
+
 	/// Sets all the necessary conditions to return with the given error.
 	fn fail(&self, what: BitmapError) {
 		let _ = self.fails.send(what);
@@ -166,6 +244,7 @@ impl BitmapWindow {
 		self.display_paint_btn.set_text(&crate::strings::bitmap::display_paint_btn());
 		self.cancel_btn.set_text(&crate::strings::bitmap::cancel_btn());
 		self.display_label.set_text(&crate::strings::bitmap::display_label());
+		self.invert_check.set_text(&crate::strings::bitmap::invert_check());
 
 		self.update();
 
@@ -173,34 +252,86 @@ impl BitmapWindow {
 		self.window.set_focus();
 	}
 
+	/// Called when the threshold slider has been moved.
+	fn on_threshold_changed(&self) {
+		let threshold = self.threshold_slider.pos().min(255) as u8;
+		if threshold == self.path.borrow().threshold() {
+			return
+		}
+		self.rebuild();
+	}
+
+	/// Called when the invert check box has been toggled.
+	fn on_invert_changed(&self) {
+		self.rebuild();
+	}
+
+	/// Extracts the signature from the source image again, with the threshold
+	/// and the polarity currently picked by the user.
+	fn rebuild(&self) {
+		let threshold = self.threshold_slider.pos().min(255) as u8;
+		let inverted = self.invert_check.check_state() == nwg::CheckBoxState::Checked;
+
+		/* Always start over from the source image, so that thresholds do not
+		 * compound. */
+		*self.path.borrow_mut() = BitmapPath::with_polarity(
+			self.source.clone(),
+			threshold,
+			inverted).trimmed(BitmapPath::DEFAULT_TRIM_MARGIN);
+		self.update();
+	}
+
+	/// Repaints the preview and moves the controls around it.
 	fn update(&self) {
 		let path = self.path.borrow();
+		self.threshold_label.set_text(
+			&crate::strings::bitmap::threshold_label(path.threshold()));
+
 		let blob = path.to_bitmap();
 		let bitmap = nwg::Bitmap::from_bin(&blob[..]).unwrap();
 
 		self.display.set_size(path.width(), path.height());
 		self.display.set_bitmap(Some(&bitmap));
 
-		/* Move the UI around. */
-		self.window.set_size(path.width() + 20, path.height() + 85);
-		let (_, btn_height) = self.cancel_btn.size();
-		let (_, lbl_height) = self.display_label.size();
+		/* Move the UI around, scaling everything but the preview itself to the
+		 * DPI of the system. */
+		let px = super::scaled;
+		let slider_height = px(SLIDER_HEIGHT);
+		self.display.set_position(px(10) as i32, px(40) as i32);
+		self.window.set_size(
+			path.width() + px(20),
+			path.height() + px(85) + slider_height);
+		let btn_height = px(super::BUTTON_HEIGHT);
+		let lbl_height = px(super::LABEL_HEIGHT);
 
 		self.display_label.set_size(
-			path.width().saturating_sub(80),
+			path.width().saturating_sub(px(130)),
 			lbl_height);
-		self.cancel_btn.set_size(
-			(path.width() / 2).saturating_sub(5),
-			btn_height);
-		self.display_paint_btn.set_size(
-			(path.width() / 2).saturating_sub(5),
-			btn_height);
-		self.cancel_btn.set_position(
-			10,
-			lbl_height as i32 + 30 + path.height() as i32);
+		self.playback_speed.set_size(px(120), btn_height);
+		self.playback_speed.set_position(
+			path.width().saturating_sub(px(110)) as i32,
+			px(7) as i32);
+
+		let slider_y = (lbl_height + px(25) + path.height()) as i32;
+		self.threshold_label.set_size(px(150), lbl_height);
+		self.threshold_label.set_position(px(10) as i32, slider_y + px(5) as i32);
+		self.threshold_slider.set_size(
+			path.width().saturating_sub(px(270)),
+			slider_height);
+		self.threshold_slider.set_position(px(170) as i32, slider_y);
+		self.invert_check.set_size(px(100), lbl_height);
+		self.invert_check.set_position(
+			path.width().saturating_sub(px(90)) as i32,
+			slider_y + px(5) as i32);
+
+		let btn_width = (path.width() / 2).saturating_sub(px(5));
+		let btn_y = (lbl_height + px(30) + path.height() + slider_height) as i32;
+		self.cancel_btn.set_size(btn_width, btn_height);
+		self.display_paint_btn.set_size(btn_width, btn_height);
+		self.cancel_btn.set_position(px(10) as i32, btn_y);
 		self.display_paint_btn.set_position(
-			(20 + (path.width() / 2).saturating_sub(5)) as i32,
-			lbl_height as i32 + 30 + path.height() as i32);
+			(px(20) + btn_width) as i32,
+			btn_y);
 	}
 
 	/// Called when an intent for painting the device data has been fired.
@@ -210,6 +341,8 @@ impl BitmapWindow {
 		let path = self.path.borrow().clone();
 		let done_sender = self.display_paint_done.sender();
 		let area_sender = self.area_selection_done.sender();
+		let speed = self.playback_speed();
+		let handle = self.playback.clone();
 
 		let width = path.width();
 		let height = path.height();
@@ -219,7 +352,7 @@ impl BitmapWindow {
 				preferred_dimensions: (width, height)
 			});
 			let area = match area {
-				Ok(area) => area,
+				Ok((area, _)) => area,
 				Err(PickPhysicalAreaError::Cancelled) => {
 					area_sender.notice();
 					return
@@ -233,18 +366,40 @@ impl BitmapWindow {
 				}
 			};
 
-			Playback {
-				path,
-				target: area,
-				delta: Duration::from_secs(8),
-				steps: unsafe { NonZeroU32::new_unchecked(5000) }
-			}.play_and_notify(done_sender);
+			let playback = Playback::builder(path, area)
+				.speed(speed)
+				.aspect_ratio(f64::from(width) / f64::from(height))
+				.build();
+			let playback = match playback {
+				Ok(playback) => playback,
+				Err(what) => {
+					nwg::error_message(
+						&crate::strings::errors::title(),
+						&crate::strings::errors::invalid_playback(what));
+					area_sender.notice();
+					return
+				}
+			};
+
+			/* Hand the playback over before it starts, since it may be done
+			 * before it even gets the chance to be handed over otherwise. */
+			let playback_handle = PlaybackHandle::new();
+			*handle.lock().unwrap() = Some(playback_handle.clone());
+			if playback.play_and_notify_with(done_sender, &playback_handle).is_err() {
+				/* Another playback is still running. Just give up. */
+				*handle.lock().unwrap() = None;
+				done_sender.notice();
+			}
 		});
 	}
 
 	/// Called when the painting of the signature has been completed.
 	fn on_paint_done(&self) {
-		nwg::stop_thread_dispatch();
+		let playback = self.playback.lock().unwrap().take();
+		match paint_outcome(playback.as_ref()) {
+			PaintOutcome::Retry => self.unlock(),
+			PaintOutcome::Close => nwg::stop_thread_dispatch()
+		}
 	}
 
 	/// Called when the painting of the signature has been completed.
@@ -252,6 +407,18 @@ impl BitmapWindow {
 		self.unlock();
 	}
 
+	/// Called when a key on the keyboard has been pressed.
+	fn on_key_press(&self, data: &nwg::EventData) {
+		if !super::is_cancel_key(data) {
+			return
+		}
+
+		let cancelled = cancel_playback(self.playback.lock().unwrap().as_ref());
+		if !cancelled {
+			self.on_exit()
+		}
+	}
+
 	/// Called when the window has been told to close.
 	fn on_exit(&self) {
 		nwg::stop_thread_dispatch();
@@ -263,6 +430,40 @@ impl BitmapWindow {
 	}
 }
 
+/// What the bitmap window does once a playback of the signature is done.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum PaintOutcome {
+	/// The window is given back to the user to try again.
+	Retry,
+	/// The window is closed.
+	Close,
+}
+
+/// Picks what the bitmap window does once the given playback, if it started
+/// one, is done.
+///
+/// Cancelled playbacks are likely to have been aimed at the wrong place, so
+/// rather than closing, the window is given back to the user to try again.
+fn paint_outcome(playback: Option<&PlaybackHandle>) -> PaintOutcome {
+	if playback.map(PlaybackHandle::is_cancelled).unwrap_or(false) {
+		PaintOutcome::Retry
+	} else {
+		PaintOutcome::Close
+	}
+}
+
+/// Stops the given playback, if there is one, rather than the whole window,
+/// returning whether there was a playback to be stopped.
+fn cancel_playback(playback: Option<&PlaybackHandle>) -> bool {
+	match playback {
+		Some(playback) => {
+			playback.cancel();
+			true
+		},
+		None => false
+	}
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum BitmapError {
 	#[error("the bitmap insertion procedure was cancelled")]
@@ -273,4 +474,52 @@ pub enum BitmapError {
 	FileNotFound,
 	#[error("the window could not be created")]
 	WindowCreationError(NwgError)
-}
\ No newline at end of file
+}
+impl BitmapError {
+	/// The message to be shown to the user when the bitmap procedure fails
+	/// with this error, if any should be shown at all.
+	pub fn message(self) -> Option<String> {
+		match self {
+			BitmapError::Cancelled => None,
+			BitmapError::InvalidFile(what) => Some(format!(
+				"{}: {}",
+				crate::strings::errors::invalid_file(),
+				what)),
+			BitmapError::FileNotFound =>
+				Some(crate::strings::errors::file_not_found().to_string()),
+			BitmapError::WindowCreationError(what) =>
+				Some(crate::strings::errors::window_creation(what))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn escape_cancels_the_playback_and_gives_the_window_back() {
+		let slot = std::sync::Mutex::new(Some(PlaybackHandle::new()));
+
+		/* The playback thread holds on to its own copy of the handle. */
+		let running = slot.lock().unwrap().clone().unwrap();
+		assert!(!running.is_cancelled());
+
+		assert!(cancel_playback(slot.lock().unwrap().as_ref()));
+		assert!(running.is_cancelled());
+
+		let playback = slot.lock().unwrap().take();
+		assert_eq!(paint_outcome(playback.as_ref()), PaintOutcome::Retry);
+	}
+
+	#[test]
+	fn finished_playbacks_close_the_window() {
+		assert_eq!(paint_outcome(Some(&PlaybackHandle::new())), PaintOutcome::Close);
+		assert_eq!(paint_outcome(None), PaintOutcome::Close);
+	}
+
+	#[test]
+	fn escape_without_a_playback_closes_the_window() {
+		assert!(!cancel_playback(None));
+	}
+}
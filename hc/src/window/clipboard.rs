@@ -0,0 +1,64 @@
+use winapi::um::winuser as user;
+use winapi::um::winbase as base;
+use std::io;
+
+/// The size of the file header at the start of a bitmap file, which is not a
+/// part of the device-independent bitmap held by the clipboard.
+const BITMAP_FILE_HEADER_SIZE: usize = 14;
+
+/// Places the given bitmap file on to the clipboard, as a device-independent
+/// bitmap, replacing whatever was on it before.
+pub fn set_bitmap(owner: &nwg::Window, bitmap: &[u8]) -> io::Result<()> {
+	let dib = bitmap.get(BITMAP_FILE_HEADER_SIZE..)
+		.filter(|dib| !dib.is_empty())
+		.ok_or_else(|| io::Error::new(
+			io::ErrorKind::InvalidInput,
+			"the bitmap data is too short"))?;
+	let owner = owner.handle.hwnd()
+		.ok_or_else(|| io::Error::new(
+			io::ErrorKind::InvalidInput,
+			"the owner window has not been created"))?;
+
+	unsafe {
+		if user::OpenClipboard(owner) == 0 {
+			return Err(io::Error::last_os_error())
+		}
+		let result = (|| {
+			if user::EmptyClipboard() == 0 {
+				return Err(io::Error::last_os_error())
+			}
+
+			/* Copy the bitmap over to a global memory block. */
+			let memory = base::GlobalAlloc(base::GMEM_MOVEABLE, dib.len());
+			if memory.is_null() {
+				return Err(io::Error::last_os_error())
+			}
+			let target = base::GlobalLock(memory);
+			if target.is_null() {
+				let what = io::Error::last_os_error();
+				base::GlobalFree(memory);
+
+				return Err(what)
+			}
+			std::ptr::copy_nonoverlapping(
+				dib.as_ptr(),
+				target as *mut u8,
+				dib.len());
+			base::GlobalUnlock(memory);
+
+			/* The clipboard only takes ownership of the memory block if the
+			 * call succeeds. Otherwise, it's still ours to free. */
+			if user::SetClipboardData(user::CF_DIB, memory as _).is_null() {
+				let what = io::Error::last_os_error();
+				base::GlobalFree(memory);
+
+				return Err(what)
+			}
+
+			Ok(())
+		})();
+		user::CloseClipboard();
+
+		result
+	}
+}
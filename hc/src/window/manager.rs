@@ -1,25 +1,34 @@
-use stu::{Tablet, Queue, Capability};
+use stu::{Tablet, Queue};
 use std::cell::RefCell;
-use crate::path::{EventPath, EventCanvas};
+use std::time::{Duration, Instant};
+use crate::path::{EventPath, EventCanvas, CanvasError, GridMapping};
 use crate::window::area::PickPhysicalAreaError;
-use crate::robot::Playback;
-use std::time::Duration;
-use std::num::NonZeroU32;
-use crate::BitmapError;
+use crate::robot::{Playback, PlaybackSpeed};
 use crate::window::AreaSelectionParameters;
 
 /// Manage the given tablet device.
-pub fn manage(device: Tablet) -> Result<(), ManagementError> {
+///
+/// When given an idle timeout, the window closes by itself once the pen has not
+/// touched the screen for that long, so that sessions left behind by someone
+/// who walked away without signing don't stay open forever.
+pub fn manage(
+	device: Tablet,
+	idle_timeout: Option<Duration>) -> Result<(), ManagementError> {
+
 	let queue = device.queue()
 		.map_err(ManagementError::QueueCreationError)?;
 	let caps = device.capability()
 		.map_err(ManagementError::CapabilityQueryError)?;
+	let mut canvas = EventCanvas::new(caps.width(), caps.height())
+		.map_err(ManagementError::CanvasCreationError)?;
+	canvas.set_grid_mapping(GridMapping::from_capability(&caps));
 	let (tx, rx) = std::sync::mpsc::channel();
 
 	let window = ManagementWindow::new(
 		device,
 		queue,
-		caps,
+		canvas,
+		idle_timeout,
 		tx);
 	let _window = nwg::NativeUi::build_ui(window)
 		.map_err(ManagementError::WindowCreationError)?;
@@ -31,6 +40,10 @@ pub fn manage(device: Tablet) -> Result<(), ManagementError> {
 	}
 }
 
+/// The factor by which saved signatures are enlarged, so that they stay crisp
+/// once printed, rather than having the coarse resolution of the device.
+pub const EXPORT_SCALE: u32 = 4;
+
 /// Tries running a device command and kills the manager if it fails.
 macro_rules! mng_cmd_try {
 	($this:expr, $e:expr) => {
@@ -41,6 +54,42 @@ macro_rules! mng_cmd_try {
 	}
 }
 
+/// The rates at which the management window polls the tablet for events.
+///
+/// The window polls quickly while the pen is being used, so that fast strokes
+/// are captured smoothly, and backs off once the pen has been lifted for a
+/// while, so that no time is wasted while nobody is signing.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PollRate {
+	/// The interval between polls while the pen is touching the screen.
+	pub active: Duration,
+	/// The interval between polls while the pen is idle or hovering.
+	pub idle: Duration,
+	/// How long after the pen was last touching the screen we keep polling at
+	/// the active rate.
+	pub grace: Duration,
+}
+impl PollRate {
+	/// The interval to wait for until the next poll, given the last time the
+	/// pen was seen touching the screen.
+	fn interval(&self, last_touch: Option<Instant>, now: Instant) -> Duration {
+		match last_touch {
+			Some(last_touch) if now.saturating_duration_since(last_touch) < self.grace =>
+				self.active,
+			_ => self.idle
+		}
+	}
+}
+impl Default for PollRate {
+	fn default() -> Self {
+		Self {
+			active: Duration::from_millis(8),
+			idle: Duration::from_millis(40),
+			grace: Duration::from_secs(1),
+		}
+	}
+}
+
 ///
 #[derive(nwd::NwgUi)]
 pub struct ManagementWindow {
@@ -50,7 +99,6 @@ pub struct ManagementWindow {
 
 	/// The top level window this controller is contained in.
 	#[nwg_control(
-		title: "Tablet",
 		flags: "WINDOW|MINIMIZE_BOX",
 		center: true,
 		icon: Some(&data.icon),
@@ -58,7 +106,8 @@ pub struct ManagementWindow {
 	)]
 	#[nwg_events(
 		OnInit: [Self::init],
-		OnWindowClose: [Self::on_exit]
+		OnWindowClose: [Self::on_exit],
+		OnKeyPress: [Self::on_key_press(SELF, EVT_DATA)]
 	)]
 	window: nwg::Window,
 
@@ -94,6 +143,43 @@ pub struct ManagementWindow {
 	)]
 	display_paint_btn: nwg::Button,
 
+	/// Button for saving the signature to a file.
+	#[nwg_control(
+		position: (310, 150)
+	)]
+	#[nwg_events(
+		OnButtonClick: [Self::on_save_pressed]
+	)]
+	save_btn: nwg::Button,
+
+	/// Drop down for picking the speed at which the signature is painted.
+	#[nwg_control(
+		collection: PlaybackSpeed::ALL.to_vec(),
+		selected_index: Some(1)
+	)]
+	playback_speed: nwg::ComboBox<PlaybackSpeed>,
+
+	/// Button for copying the signature to the clipboard.
+	#[nwg_control()]
+	#[nwg_events(
+		OnButtonClick: [Self::on_copy_pressed]
+	)]
+	copy_btn: nwg::Button,
+
+	/// Button for undoing the last stroke of the signature.
+	#[nwg_control()]
+	#[nwg_events(
+		OnButtonClick: [Self::on_undo_pressed]
+	)]
+	undo_btn: nwg::Button,
+
+	/// Button for redoing the last undone stroke of the signature.
+	#[nwg_control()]
+	#[nwg_events(
+		OnButtonClick: [Self::on_redo_pressed]
+	)]
+	redo_btn: nwg::Button,
+
 	/// Button for accessing the help dialog box.
 	#[nwg_control()]
 	#[nwg_events(
@@ -123,6 +209,21 @@ pub struct ManagementWindow {
 	/// Whether the management window is currently locked.
 	locked: RefCell<bool>,
 
+	/// The rates at which the tablet gets polled for events.
+	poll_rate: RefCell<PollRate>,
+	/// The last time the pen was seen touching the screen.
+	last_touch: RefCell<Option<Instant>>,
+
+	/// How long the pen may stay off the screen before the window closes by
+	/// itself, if it should close by itself at all.
+	idle_timeout: Option<Duration>,
+	/// The time at which the window was opened.
+	opened: Instant,
+
+	/// Whether inking was enabled on the device before we took it over, if
+	/// the device let us know.
+	initial_inking: RefCell<Option<bool>>,
+
 	/// The device we're connected to.
 	device: Tablet,
 	/// The queue though which we receive device updates.
@@ -154,7 +255,8 @@ impl ManagementWindow {
 	fn new(
 		device: Tablet,
 		queue: Queue,
-		caps: Capability,
+		canvas: EventCanvas,
+		idle_timeout: Option<Duration>,
 		fails: std::sync::mpsc::Sender<ManagementError>) -> Self {
 
 		Self {
@@ -164,14 +266,24 @@ impl ManagementWindow {
 			display_label: Default::default(),
 			display_clear_btn: Default::default(),
 			display_paint_btn: Default::default(),
+			save_btn: Default::default(),
+			copy_btn: Default::default(),
+			playback_speed: Default::default(),
+			undo_btn: Default::default(),
+			redo_btn: Default::default(),
 			help_btn: Default::default(),
 			bitmap_upload_btn: Default::default(),
 			update: Default::default(),
 			locked: RefCell::new(false),
+			poll_rate: Default::default(),
+			last_touch: RefCell::new(None),
+			idle_timeout,
+			opened: Instant::now(),
+			initial_inking: RefCell::new(None),
 			device,
 			queue: RefCell::new(queue),
 			path: Default::default(),
-			canvas: RefCell::new(EventCanvas::new(caps.width(), caps.height())),
+			canvas: RefCell::new(canvas),
 			display_paint_done: Default::default(),
 			bitmap_window_done: Default::default(),
 			fails
@@ -179,21 +291,67 @@ impl ManagementWindow {
 	}
 
 	/// Locks all of the controls in this window.
-	fn lock(&self) {
-		mng_cmd_try!(self, self.device.inking(false));
+	///
+	/// Returns whether the device could be locked along with the window. When
+	/// it could not, the manager has already been told to fail, and callers
+	/// must not go on with whatever they needed the lock for.
+	#[must_use]
+	fn lock(&self) -> bool {
+		/* Lock the window first, so that it is never left half locked. */
 		self.display_clear_btn.set_enabled(false);
 		self.display_paint_btn.set_enabled(false);
+		self.save_btn.set_enabled(false);
+		self.copy_btn.set_enabled(false);
+		self.playback_speed.set_enabled(false);
+		self.undo_btn.set_enabled(false);
+		self.redo_btn.set_enabled(false);
 		*self.locked.borrow_mut() = true;
+
+		if let Err(what) = self.device.try_inking(false) {
+			self.fail(ManagementError::DeviceCommandFailed(what));
+			return false
+		}
+		true
 	}
 
 	/// Unlocks all of the controls in this window.
 	fn unlock(&self) {
-		mng_cmd_try!(self, self.device.inking(true));
+		mng_cmd_try!(self, self.device.try_inking(true));
 		self.display_clear_btn.set_enabled(true);
 		self.display_paint_btn.set_enabled(true);
+		self.save_btn.set_enabled(true);
+		self.copy_btn.set_enabled(true);
+		self.playback_speed.set_enabled(true);
+		self.undo_btn.set_enabled(true);
+		self.redo_btn.set_enabled(true);
 		*self.locked.borrow_mut() = false;
 	}
 
+	/// The playback speed currently picked by the user.
+	fn playback_speed(&self) -> PlaybackSpeed {
+		self.playback_speed.selection()
+			.and_then(|index| PlaybackSpeed::ALL.get(index).copied())
+			.unwrap_or_default()
+	}
+
+	/// Changes the rates at which the tablet gets polled for events.
+	pub fn set_poll_rate(&self, rate: PollRate) {
+		*self.poll_rate.borrow_mut() = rate;
+	}
+
+	/// Whether the pen has stayed off the screen for longer than the idle
+	/// timeout, counting from when the window was opened if it never touched
+	/// the screen at all.
+	fn is_idle(&self, now: Instant) -> bool {
+		let timeout = match self.idle_timeout {
+			Some(timeout) => timeout,
+			None => return false
+		};
+		let since = self.last_touch.borrow().unwrap_or(self.opened);
+
+		now.saturating_duration_since(since) >= timeout
+	}
+
 	/// Sets all the necessary conditions to return with the given error.
 	fn fail(&self, what: ManagementError) {
 		let _ = self.fails.send(what);
@@ -202,16 +360,31 @@ impl ManagementWindow {
 
 	/// Populates the data in the window controls.
 	fn init(&self) {
-		mng_cmd_try!(self, self.device.clear());
-		mng_cmd_try!(self, self.device.inking(true));
+		mng_cmd_try!(self, self.device.try_clear());
+
+		/* Leave the inking mode alone if the device is already inking, so that
+		 * we don't miss the first strokes while it switches modes. */
+		let inking = self.device.inking_state().ok();
+		*self.initial_inking.borrow_mut() = inking;
+		if inking != Some(true) {
+			mng_cmd_try!(self, self.device.try_inking(true));
+		}
 
 		self.window.set_text(&crate::strings::manager::title());
 		self.help_btn.set_text(&crate::strings::manager::help_btn());
 		self.bitmap_upload_btn.set_text(&crate::strings::manager::bitmap_upload_btn());
 		self.display_paint_btn.set_text(&crate::strings::manager::display_paint_btn());
 		self.display_clear_btn.set_text(&crate::strings::manager::display_clear_btn());
+		self.save_btn.set_text(&crate::strings::manager::save_btn());
+		self.copy_btn.set_text(&crate::strings::manager::copy_btn());
+		self.undo_btn.set_text(&crate::strings::manager::undo_btn());
+		self.redo_btn.set_text(&crate::strings::manager::redo_btn());
 		self.display_label.set_text(&crate::strings::manager::display_label());
 
+		/* Don't offer to clear a screen the device has no way to clear. */
+		self.display_clear_btn.set_visible(
+			self.device.supports(stu::ReportId::ClearScreen));
+
 		self.update(true);
 		self.update.start();
 
@@ -221,17 +394,121 @@ impl ManagementWindow {
 
 	/// Called when an intent for clearing the device screen has been fired.
 	fn on_clear_pressed(&self) {
-		mng_cmd_try!(self, self.device.inking(false));
+		mng_cmd_try!(self, self.device.try_inking(false));
 
 		self.canvas.borrow_mut().clear();
 		self.path.borrow_mut().clear();
 
-		mng_cmd_try!(self, self.device.clear());
-		mng_cmd_try!(self, self.device.inking(true));
+		mng_cmd_try!(self, self.device.try_clear());
+
+		/* Throw away whatever was drawn before the screen got cleared, so it
+		 * doesn't show up as ghost strokes in the new signature. */
+		mng_cmd_try!(self, self.queue.borrow_mut().flush());
+		mng_cmd_try!(self, self.device.try_inking(true));
 
 		self.update(true);
 	}
 
+	/// Called when an intent for saving the signature to a file has been fired.
+	///
+	/// The format of the file is picked from its extension. Bitmaps are saved
+	/// with the full contents of the canvas, while PNG images, the default, are
+	/// cropped down to the signature.
+	fn on_save_pressed(&self) {
+		let mut dialog = Default::default();
+		let result = nwg::FileDialog::builder()
+			.title(crate::strings::manager::save_title())
+			.filters(format!("{}(*.png)|{}(*.bmp)",
+				crate::strings::manager::save_filter_png(),
+				crate::strings::manager::save_filter_bmp()))
+			.action(nwg::FileDialogAction::Save)
+			.build(&mut dialog);
+		if let Err(what) = result {
+			self.fail(ManagementError::WindowCreationError(what));
+			return
+		}
+
+		if !dialog.run(Some(&self.window)) {
+			return
+		}
+		let file = match dialog.get_selected_item() {
+			Ok(file) => std::path::PathBuf::from(file),
+			Err(_) => return
+		};
+
+		let bitmap = file.extension()
+			.map(|extension| extension.eq_ignore_ascii_case("bmp"))
+			.unwrap_or(false);
+
+		let canvas = self.canvas.borrow();
+		let data = if bitmap {
+			canvas.to_bitmap()
+		} else {
+			canvas.to_png_cropped_scaled(EXPORT_SCALE)
+				.unwrap_or_else(|| canvas.to_png())
+		};
+
+		if let Err(what) = std::fs::write(&file, &data[..]) {
+			self.fail(ManagementError::SaveFailed(what));
+		}
+	}
+
+	/// Called when an intent for copying the signature to the clipboard has
+	/// been fired.
+	fn on_copy_pressed(&self) {
+		if *self.locked.borrow() { return }
+
+		let canvas = self.canvas.borrow();
+		let data = canvas.to_bitmap_cropped()
+			.unwrap_or_else(|| canvas.to_bitmap());
+
+		if let Err(what) = super::clipboard::set_bitmap(&self.window, &data[..]) {
+			self.fail(ManagementError::CopyFailed(what));
+		}
+	}
+
+	/// Called when an intent for undoing the last stroke has been fired.
+	fn on_undo_pressed(&self) {
+		let undone = self.canvas.borrow_mut().undo_stroke();
+		self.path.borrow_mut().undo_stroke();
+
+		if undone {
+			self.redraw_device();
+			self.update(true);
+		}
+	}
+
+	/// Called when an intent for redoing the last undone stroke has been fired.
+	fn on_redo_pressed(&self) {
+		let redone = self.canvas.borrow_mut().redo_stroke();
+		self.path.borrow_mut().redo_stroke();
+
+		if redone {
+			self.redraw_device();
+			self.update(true);
+		}
+	}
+
+	/// Replaces the ink on the screen of the device with the contents of the
+	/// canvas.
+	///
+	/// Devices that cannot display images have their screens cleared instead.
+	fn redraw_device(&self) {
+		let image = self.canvas.borrow().to_image();
+
+		mng_cmd_try!(self, self.device.try_inking(false));
+		match self.device.write_image(&image, stu::ImageEncoding::Monochrome) {
+			Ok(_) => {},
+			Err(stu::Error::ClientError(stu::ClientError::UnsupportedReportId { .. })) =>
+				mng_cmd_try!(self, self.device.try_clear()),
+			Err(what) => {
+				self.fail(ManagementError::DeviceCommandFailed(what));
+				return
+			}
+		}
+		mng_cmd_try!(self, self.device.try_inking(true));
+	}
+
 	/// Called when an intent for opening the help dialog has been fired.
 	fn on_help_pressed(&self) {
 		nwg::modal_info_message(
@@ -242,31 +519,18 @@ impl ManagementWindow {
 
 	/// Called when an intent for loading a bitmap signature has been fired.
 	fn on_bitmap_load_pressed(&self) {
-		self.lock();
+		if !self.lock() { return }
 		let channel = self.bitmap_window_done.sender();
 
 		std::thread::spawn(move || {
-			match super::bitmap::run(Some(channel)) {
-				Ok(_) => {},
-				Err(BitmapError::Cancelled) => {
-					channel.notice();
-				},
-				Err(what) => {
+			/* The bitmap window only notifies us when it ran to completion. */
+			if let Err(what) = super::bitmap::run(Some(channel)) {
+				if let Some(message) = what.message() {
 					nwg::error_message(
 						&crate::strings::errors::title(),
-						&*match what {
-							BitmapError::Cancelled => unreachable!(),
-							BitmapError::InvalidFile(what) => format!(
-								"{}: {}",
-								crate::strings::errors::invalid_file(),
-								what),
-							BitmapError::FileNotFound =>
-								crate::strings::errors::file_not_found().to_string(),
-							BitmapError::WindowCreationError(what) =>
-								crate::strings::errors::window_creation(what)
-						});
-					channel.notice();
+						&message);
 				}
+				channel.notice();
 			}
 		});
 	}
@@ -278,10 +542,11 @@ impl ManagementWindow {
 
 	/// Called when an intent for painting the device data has been fired.
 	fn on_paint_pressed(&self) {
-		self.lock();
+		if !self.lock() { return }
 
 		let path = self.path.borrow().clone();
 		let sender = self.display_paint_done.sender();
+		let speed = self.playback_speed();
 
 		let canvas = self.canvas.borrow();
 		let width = canvas.width();
@@ -292,7 +557,7 @@ impl ManagementWindow {
 				preferred_dimensions: (width, height)
 			});
 			let area = match area {
-				Ok(area) => area,
+				Ok((area, _)) => area,
 				Err(PickPhysicalAreaError::Cancelled) => {
 					sender.notice();
 					return
@@ -306,12 +571,26 @@ impl ManagementWindow {
 				}
 			};
 
-			Playback {
-				path,
-				target: area,
-				delta: Duration::from_secs(8),
-				steps: unsafe { NonZeroU32::new_unchecked(5000) }
-			}.play_and_notify(sender);
+			let playback = Playback::builder(path, area)
+				.speed(speed)
+				.aspect_ratio(f64::from(width) / f64::from(height))
+				.build();
+			let playback = match playback {
+				Ok(playback) => playback,
+				Err(what) => {
+					nwg::error_message(
+						&crate::strings::errors::title(),
+						&crate::strings::errors::invalid_playback(what));
+					sender.notice();
+					return
+				}
+			};
+
+			let playback = playback.play_and_notify(sender);
+			if playback.is_err() {
+				/* Another playback is still running. Just give up. */
+				sender.notice();
+			}
 		});
 	}
 
@@ -332,6 +611,9 @@ impl ManagementWindow {
 		loop {
 			match queue.try_recv() {
 				Ok(event) => {
+					if event.touching() {
+						*self.last_touch.borrow_mut() = Some(Instant::now());
+					}
 					if !*locked {
 						canvas.process(event);
 						path.process(event);
@@ -342,7 +624,7 @@ impl ManagementWindow {
 				Err(stu::TryRecvError::Empty) =>
 				/* Done processing events for now. */
 					break,
-				Err(stu::TryRecvError::Failed(what)) => {
+				Err(what @ stu::TryRecvError::Failed(_)) => {
 					/* The polling process has failed. */
 					self.fail(ManagementError::DevicePollingFailed(what));
 					return
@@ -350,52 +632,126 @@ impl ManagementWindow {
 			}
 		}
 
+		/* There is nothing worth painting until the pen has touched the screen. */
+		if !*locked {
+			self.display_paint_btn.set_enabled(
+				path.iter().any(|event| event.touching()));
+		}
+
+		/* Poll faster while the pen is in use and slower when it isn't. */
+		let interval = self.poll_rate.borrow().interval(
+			*self.last_touch.borrow(),
+			Instant::now());
+		self.update.set_interval(interval);
+
 		/* Update the display after the changes made by the events. */
 		if dirty || force_repaint {
-			let blob = canvas.to_bitmap();
+			let blob = canvas.to_preview_bitmap();
 			let bitmap = nwg::Bitmap::from_bin(&blob[..]).unwrap();
 
 			self.display.set_size(canvas.width(), canvas.height());
 			self.display.set_bitmap(Some(&bitmap));
 
-			/* Move the UI around. */
-			self.window.set_size(canvas.width() + 20, canvas.height() + 85);
-			let (_, btn_height) = self.display_clear_btn.size();
-			let (_, lbl_height) = self.display_label.size();
+			/* Move the UI around, scaling everything but the preview itself to
+			 * the DPI of the system. */
+			let px = super::scaled;
+			self.display.set_position(px(10) as i32, px(40) as i32);
+			self.window.set_size(
+				canvas.width() + px(20),
+				canvas.height() + px(85));
+			let btn_height = px(super::BUTTON_HEIGHT);
+			let lbl_height = px(super::LABEL_HEIGHT);
 
 			self.display_label.set_size(
-				canvas.width().saturating_sub(80),
+				canvas.width().saturating_sub(px(405)),
 				lbl_height);
+			self.playback_speed.set_size(px(120), btn_height);
+			self.playback_speed.set_position(
+				canvas.width().saturating_sub(px(395)) as i32,
+				px(7) as i32);
+			self.undo_btn.set_size(px(85), btn_height);
+			self.redo_btn.set_size(px(85), btn_height);
+			self.undo_btn.set_position(
+				canvas.width().saturating_sub(px(270)) as i32,
+				px(7) as i32);
+			self.redo_btn.set_position(
+				canvas.width().saturating_sub(px(180)) as i32,
+				px(7) as i32);
 			self.help_btn.set_position(
-				canvas.width().saturating_sub(90) as i32,
-				7);
-			self.display_clear_btn.set_size(
-				(canvas.width() / 3).saturating_sub(5),
-				btn_height);
-			self.display_paint_btn.set_size(
-				(canvas.width() / 3).saturating_sub(5),
-				btn_height);
-			self.display_paint_btn.set_position(
-				(20 + (canvas.width() / 3).saturating_sub(5)) as i32,
-				150);
-			self.bitmap_upload_btn.set_position(
-				(20 + (canvas.width() / 3 * 2).saturating_sub(5)) as i32,
-				150);
+				canvas.width().saturating_sub(px(90)) as i32,
+				px(7) as i32);
+
+			let btn_width = (canvas.width() / 5).saturating_sub(px(5));
+			let buttons = [
+				&self.display_clear_btn,
+				&self.display_paint_btn,
+				&self.bitmap_upload_btn,
+				&self.save_btn,
+				&self.copy_btn
+			];
+			for (i, button) in buttons.iter().enumerate() {
+				button.set_size(btn_width, btn_height);
+				button.set_position(
+					(px(10) + i as u32 * (btn_width + px(5))) as i32,
+					px(150) as i32);
+			}
 		}
 	}
 
 	/// Called when an update to the pen display preview has been requested.
 	fn on_update(&self) {
-		self.update(false)
+		self.update(false);
+
+		/* Give up on the session if nobody is signing, unless we're busy
+		 * painting the signature. */
+		if !*self.locked.borrow() && self.is_idle(Instant::now()) {
+			self.on_exit();
+			return
+		}
+
+		/* Let the user know the signature may be missing parts of it. The
+		 * queue must not be borrowed while the message is up, since the timer
+		 * keeps on ticking behind it. */
+		let sequence = self.queue.borrow_mut().check_sequence();
+		if let Err(what) = sequence {
+			nwg::modal_info_message(
+				&self.window,
+				&crate::strings::manager::title(),
+				&crate::strings::manager::dropped_reports(what));
+		}
+	}
+
+	/// Called when a key on the keyboard has been pressed.
+	fn on_key_press(&self, data: &nwg::EventData) {
+		/* This window is not modal, and closing it throws the signature away,
+		 * so it is deliberately not closed with the Escape key. */
+		if data.on_key() == nwg::keys::_C && control_pressed() {
+			self.on_copy_pressed();
+		}
 	}
 
 	/// Called when the window has been told to close.
 	fn on_exit(&self) {
 		self.on_clear_pressed();
+
+		/* Hand the device back in the inking mode we found it in. */
+		if let Some(inking) = *self.initial_inking.borrow() {
+			mng_cmd_try!(self, self.device.try_inking(inking));
+		}
 		nwg::stop_thread_dispatch();
 	}
 }
 
+/// Whether either of the control keys is currently being held down.
+fn control_pressed() -> bool {
+	let state = unsafe {
+		winapi::um::winuser::GetKeyState(winapi::um::winuser::VK_CONTROL)
+	};
+
+	/* The high order bit is set while the key is down. */
+	state < 0
+}
+
 /// This structure enumerates the reasons for which creation of a management
 /// window may fail.
 #[derive(Debug, thiserror::Error)]
@@ -411,13 +767,23 @@ pub enum ManagementError {
 	/// device we would be managing and, thus cannot perform its job.
 	#[error("could not query for device capabilities: {0}")]
 	CapabilityQueryError(stu::Error),
+	/// The capabilities reported by the tablet device describe a screen we
+	/// cannot draw the signature for, such as one with no width or height.
+	#[error("could not create the signature canvas: {0}")]
+	CanvasCreationError(CanvasError),
 	/// While trying to poll events off the tablet device, we encountered a
 	/// fatal error and had to terminate the management structure.
-	#[error("device polling failed: {0}")]
-	DevicePollingFailed(stu::Error),
+	#[error("the connection to the device was lost, {0}")]
+	DevicePollingFailed(#[source] stu::TryRecvError),
 	/// While trying to send a command off to the tablet device, we encountered
 	/// a fatal error and had to terminate the management structure.
 	#[error("device command failed: {0}")]
 	DeviceCommandFailed(stu::Error),
+	/// The signature could not be written to the file picked by the user.
+	#[error("could not save the signature: {0}")]
+	SaveFailed(std::io::Error),
+	/// The signature could not be copied to the clipboard.
+	#[error("could not copy the signature to the clipboard: {0}")]
+	CopyFailed(std::io::Error),
 }
 
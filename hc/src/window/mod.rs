@@ -14,6 +14,9 @@ mod area;
 /// Bitmap painter window and logic.
 pub(crate) mod bitmap;
 
+/// Access to the system clipboard.
+mod clipboard;
+
 /// Initialize globals required by the windowing interface.
 pub fn init() {
 	nwg::init().expect("Could not initialize Win32 UI framework.");
@@ -29,13 +32,84 @@ pub fn init() {
 	let mut font = Default::default();
 	nwg::Font::builder()
 		.family("Segoe UI")
-		.size(16)
+		.size(scaled(16))
 		.build(&mut font)
 		.unwrap();
 	nwg::Font::set_global_default(Some(font)).unwrap();
 }
 
+/// The DPI at which the layouts of the windows were designed.
+const BASE_DPI: u32 = 96;
+
+/// The height of the buttons in the layouts of the windows, at [`BASE_DPI`].
+const BUTTON_HEIGHT: u32 = 25;
+
+/// The height of the labels in the layouts of the windows, at [`BASE_DPI`].
+const LABEL_HEIGHT: u32 = 20;
+
+/// The DPI of the system, once it has been queried.
+static SYSTEM_DPI: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+/// Scales a length in the layout of a window, given in pixels at [`BASE_DPI`],
+/// to the DPI of the system.
+///
+/// Since we work with physical pixels, the system won't scale our windows up
+/// for us on displays set to larger text sizes, so the layouts have to.
+fn scaled(length: u32) -> u32 {
+	use std::convert::TryFrom;
+	use std::sync::atomic::Ordering;
+
+	let mut dpi = SYSTEM_DPI.load(Ordering::Relaxed);
+	if dpi == 0 {
+		dpi = unsafe {
+			let context = winapi::um::winuser::GetDC(std::ptr::null_mut());
+			let dpi = winapi::um::wingdi::GetDeviceCaps(
+				context,
+				winapi::um::wingdi::LOGPIXELSY);
+			winapi::um::winuser::ReleaseDC(std::ptr::null_mut(), context);
+
+			u32::try_from(dpi).ok().filter(|dpi| *dpi > 0).unwrap_or(BASE_DPI)
+		};
+		SYSTEM_DPI.store(dpi, Ordering::Relaxed);
+	}
+
+	scale(length, dpi)
+}
+
+/// Scales the given length in pixels at [`BASE_DPI`] to the given DPI.
+fn scale(length: u32, dpi: u32) -> u32 {
+	use std::convert::TryFrom;
+
+	let length = u64::from(length) * u64::from(dpi) / u64::from(BASE_DPI);
+	u32::try_from(length).unwrap_or(u32::MAX)
+}
+
+/// Whether the given key press is meant to back out of the current window.
+///
+/// Every modal window treats this key the same way as it treats being closed.
+fn is_cancel_key(data: &nwg::EventData) -> bool {
+	data.on_key() == nwg::keys::ESCAPE
+}
+
+/// Whether the given key press is meant to confirm the current window.
+fn is_accept_key(data: &nwg::EventData) -> bool {
+	data.on_key() == nwg::keys::RETURN
+}
+
 /* Re-export the user-facing functionality in our modules. */
-pub use manager::{manage, ManagementError};
+pub use manager::{manage, ManagementError, EXPORT_SCALE};
 pub use selector::{pick_tablet, NoTabletConnector};
 pub use area::{pick_physical_area, PickPhysicalAreaError, AreaSelectionParameters};
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn lengths_scale_with_the_dpi() {
+		assert_eq!(scale(100, 96), 100);
+		assert_eq!(scale(100, 144), 150);
+		assert_eq!(scale(100, 192), 200);
+		assert_eq!(scale(u32::MAX, 192), u32::MAX);
+	}
+}
@@ -1,14 +1,21 @@
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::time::Duration;
+
+/// The interval between two consecutive polls of the devices attached to the
+/// system, while the selection window is open.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
 
 /// Prompt the user to pick a tablet device to connect to.
 pub fn pick_tablet() -> Result<stu::Information, NoTabletConnector> {
 	let devices = stu::list_devices()
-		.map(|connector| connector.info())
-		.collect::<Vec<_>>();
-	if devices.len() == 0 {
+		.map_err(NoTabletConnector::DeviceListingFailed)?;
+	if devices.is_empty() {
 		return Err(NoTabletConnector::NoDevicesAvailable)
 	}
+	let devices = devices
+		.map(|connector| connector.info())
+		.collect::<Vec<_>>();
 
 	let channel = Rc::new(RefCell::new(None));
 	let _ = {
@@ -34,6 +41,9 @@ pub enum NoTabletConnector {
 	/// This variant indicates that are no available devices.
 	#[error("there are no available tablet devices")]
 	NoDevicesAvailable,
+	/// The devices available on the system could not be listed.
+	#[error("could not list the available tablet devices: {0}")]
+	DeviceListingFailed(stu::Error),
 	/// The user has cancelled the operation.
 	#[error("the operation was cancelled")]
 	Cancelled,
@@ -58,7 +68,8 @@ pub struct DeviceSelection {
 	)]
 	#[nwg_events(
 		OnInit: [Self::init],
-		OnWindowClose: [Self::on_cancel]
+		OnWindowClose: [Self::on_cancel],
+		OnKeyPress: [Self::on_key_press(SELF, EVT_DATA)]
 	)]
 	window: nwg::Window,
 
@@ -70,10 +81,16 @@ pub struct DeviceSelection {
 	description: nwg::Label,
 
 	/// The device connector selection box.
+	///
+	/// The height of this control is that of its drop down list, which scrolls
+	/// once there are more devices than fit in it.
 	#[nwg_control(
-		size: (380, 40),
+		size: (380, 200),
 		position: (10, 30)
 	)]
+	#[nwg_events(
+		OnKeyPress: [Self::on_key_press(SELF, EVT_DATA)]
+	)]
 	selection: nwg::ComboBox<ConnectorDisplay>,
 
 	/// The cancel button.
@@ -84,7 +101,8 @@ pub struct DeviceSelection {
 		position: (290, 65)
 	)]
 	#[nwg_events(
-		OnButtonClick: [Self::on_cancel]
+		OnButtonClick: [Self::on_cancel],
+		OnKeyPress: [Self::on_cancel_key_press(SELF, EVT_DATA)]
 	)]
 	cancel: nwg::Button,
 
@@ -96,10 +114,27 @@ pub struct DeviceSelection {
 		position: (180, 65)
 	)]
 	#[nwg_events(
-		OnButtonClick: [Self::on_accept]
+		OnButtonClick: [Self::on_accept],
+		OnKeyPress: [Self::on_key_press(SELF, EVT_DATA)]
 	)]
 	accept: nwg::Button,
 
+	/// The timer that pulls in devices being attached to and detached from
+	/// the system, keeping the selection box up to date.
+	#[nwg_control(
+		interval: std::time::Duration::new(0, 100_000_000),
+		active: false,
+		lifetime: None,
+	)]
+	#[nwg_events(
+		OnTimerTick: [Self::on_refresh]
+	)]
+	refresh: nwg::AnimationTimer,
+
+	/// The watcher reporting devices being attached to and detached from the
+	/// system.
+	watcher: stu::DeviceWatcher,
+
 	/// The list of table devices currently available to us.
 	devices: RefCell<Vec<stu::Information>>,
 
@@ -124,6 +159,8 @@ impl DeviceSelection {
 			cancel: Default::default(),
 			accept: Default::default(),
 			selection: Default::default(),
+			refresh: Default::default(),
+			watcher: stu::watch_devices(WATCH_INTERVAL),
 			devices: RefCell::new(devices),
 			channel
 		}
@@ -145,8 +182,69 @@ impl DeviceSelection {
 		self.accept.set_text(crate::strings::selector::accept());
 		self.cancel.set_text(crate::strings::selector::cancel());
 
+		/* Focus the selection box, so that a device can be picked with the
+		 * arrow keys and connected to with Enter, without needing a mouse. */
 		self.window.set_visible(true);
 		self.window.set_focus();
+		self.selection.set_focus();
+
+		self.refresh.start();
+	}
+
+	/// Called periodically to bring the selection box up to date with the
+	/// devices currently attached to the system.
+	fn on_refresh(&self) {
+		let mut changed = false;
+		while let Some(event) = self.watcher.try_recv() {
+			let mut collection = self.selection.collection_mut();
+			let position = |device: &stu::Information| collection
+				.iter()
+				.position(|display| display.0.as_ref() == Some(device));
+
+			match event {
+				stu::DeviceEvent::Added(device) => {
+					/* The watcher reports the devices that were already
+					 * attached as having been added, so skip those. */
+					if position(&device).is_none() {
+						collection.push(ConnectorDisplay(Some(device)));
+						changed = true;
+					}
+				},
+				stu::DeviceEvent::Removed(device) => {
+					if let Some(index) = position(&device) {
+						collection.remove(index);
+						changed = true;
+					}
+				}
+			}
+		}
+		if !changed { return }
+
+		let selection = self.selection.selection();
+		self.selection.sync();
+		let count = self.selection.len();
+		self.selection.set_selection(match selection {
+			_ if count == 0 => None,
+			Some(selection) => Some(selection.min(count - 1)),
+			None => Some(0)
+		});
+	}
+
+	/// Called when a key on the keyboard has been pressed.
+	fn on_key_press(&self, data: &nwg::EventData) {
+		if super::is_cancel_key(data) {
+			self.on_cancel();
+		} else if super::is_accept_key(data) {
+			self.on_accept();
+		}
+	}
+
+	/// Called when a key on the keyboard has been pressed while the cancel
+	/// button has focus, in which case Enter presses the button instead.
+	fn on_cancel_key_press(&self, data: &nwg::EventData) {
+		if super::is_cancel_key(data) || super::is_accept_key(data) {
+			self.on_cancel();
+		}
 	}
 
 	/// A source of cancellation intent has been fired.
@@ -156,7 +254,10 @@ impl DeviceSelection {
 
 	/// A source of acceptance intent has been fired.
 	fn on_accept(&self) {
-		let selection = self.selection.selection().unwrap();
+		let selection = match self.selection.selection() {
+			Some(selection) => selection,
+			None => return
+		};
 		let selection = self.selection.collection_mut().swap_remove(selection);
 
 		*RefCell::borrow_mut(&self.channel) = Some(selection.0.unwrap());
@@ -170,10 +271,6 @@ struct ConnectorDisplay(Option<stu::Information>);
 impl std::fmt::Display for ConnectorDisplay {
 	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
 		let info = self.0.as_ref().unwrap();
-
-		write!(f, "{} - {:04x}:{:04x}",
-			info.device(),
-			info.vendor(),
-			info.product())
+		write!(f, "{}", info)
 	}
 }
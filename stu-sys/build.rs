@@ -4,26 +4,53 @@ use std::path::PathBuf;
  * the root folder of the user's Wacom STU SDK installation. */
 const ENV_WACOM_STU_HOME: &'static str = "WACOM_STU_SDK_HOME";
 
+/** The environment variable which, when set, overrides the location of the
+ * folder containing the C headers of the Wacom STU SDK. */
+const ENV_WACOM_STU_INCLUDE_DIR: &'static str = "WACOM_STU_INCLUDE_DIR";
+
+/** The environment variable which, when set, overrides the location of the
+ * folder containing the C library of the Wacom STU SDK. */
+const ENV_WACOM_STU_LIB_DIR: &'static str = "WACOM_STU_LIB_DIR";
+
 fn main() {
 	println!("cargo:rerun-if-env-changed={}", ENV_WACOM_STU_HOME);
+	println!("cargo:rerun-if-env-changed={}", ENV_WACOM_STU_INCLUDE_DIR);
+	println!("cargo:rerun-if-env-changed={}", ENV_WACOM_STU_LIB_DIR);
 
-	let home = match std::env::var_os(ENV_WACOM_STU_HOME) {
+	/* The overrides take precedence over the paths derived from the root
+	 * folder of the SDK, which is only required if any of them is missing. */
+	let home = || match std::env::var_os(ENV_WACOM_STU_HOME) {
 		Some(home) => PathBuf::from(home),
 		None =>
 			panic!("Missing the required {} environment variable, which is \
-				used to determine the root folder of the Wacom STU SDK",
-				ENV_WACOM_STU_HOME)
+				used to determine the root folder of the Wacom STU SDK when \
+				either of the {} or {} environment variables is not set",
+				ENV_WACOM_STU_HOME,
+				ENV_WACOM_STU_INCLUDE_DIR,
+				ENV_WACOM_STU_LIB_DIR)
+	};
+	let include_dir = match std::env::var_os(ENV_WACOM_STU_INCLUDE_DIR) {
+		Some(include_dir) => PathBuf::from(include_dir),
+		None => home().join("C/include")
+	};
+	let lib_dir = match std::env::var_os(ENV_WACOM_STU_LIB_DIR) {
+		Some(lib_dir) => PathBuf::from(lib_dir),
+		None => home().join("C/lib/").join(target_name())
 	};
 
 	/* Generate the bindings with the header file. */
-	let header = home.join("C/include/WacomGSS/wgssSTU.h");
+	let header = include_dir.join("WacomGSS/wgssSTU.h");
 	if !header.exists() {
 		panic!(
-			"Missing the required C header file at {:?}",
+			"Missing the required C header file at {:?}. Its folder is taken \
+			from the {} environment variable if set, or from the {} \
+			environment variable otherwise",
 			match header.canonicalize() {
 				Ok(canonical) => canonical,
 				Err(_) => header
-			});
+			},
+			ENV_WACOM_STU_INCLUDE_DIR,
+			ENV_WACOM_STU_HOME);
 	}
 	let header = match header.to_str() {
 		Some(header) => header,
@@ -48,17 +75,22 @@ fn main() {
 	}
 
 	/* Tell rustc what libraries we will be linking against. */
-	let lib = home
-		.join("C/lib/")
-		.join(target_name())
-		.join(library_name());
+	let lib = lib_dir.join(library_name());
 	if !lib.exists() {
+		/* Not every version of the SDK ships libraries for every target, so
+		 * be clear about which one we were looking for. */
 		panic!(
-			"Missing the required C library file at {:?}",
+			"Missing the required C library file at {:?}. Its folder is taken \
+			from the {} environment variable if set, or from the {} \
+			environment variable otherwise, in which case make sure your \
+			version of the Wacom STU SDK provides libraries for the {} target",
 			match lib.canonicalize() {
 				Ok(canonical) => canonical,
 				Err(_) => lib
-			});
+			},
+			ENV_WACOM_STU_LIB_DIR,
+			ENV_WACOM_STU_HOME,
+			target_name());
 	}
 	let lib = match lib.parent().unwrap().to_str() {
 		Some(lib) => lib,
@@ -68,7 +100,7 @@ fn main() {
 	};
 
 	println!("cargo:rustc-link-search={}", lib);
-	println!("cargo:rustc-link-lib=wgssSTU");
+	println!("cargo:rustc-link-lib=dylib=wgssSTU");
 }
 
 /** Name of the current target, in Wacom's naming scheme. */
@@ -81,11 +113,25 @@ const fn target_name() -> &'static str {
 	let name = "Linux-x86_64";
 	#[cfg(all(target_arch = "x86", target_os = "linux"))]
 	let name = "Linux-i386";
+	#[cfg(all(target_arch = "aarch64", target_os = "windows"))]
+	let name = "ARM64";
+	#[cfg(all(target_arch = "aarch64", target_os = "linux"))]
+	let name = "Linux-aarch64";
+	/* The macOS libraries are universal binaries, shared by all of the
+	 * architectures supported by the SDK. */
+	#[cfg(all(
+		any(target_arch = "x86_64", target_arch = "aarch64"),
+		target_os = "macos"))]
+	let name = "macOS";
 	#[cfg(not(any(
 		all(target_arch = "x86_64", target_os = "windows"),
 		all(target_arch = "x86", target_os = "windows"),
 		all(target_arch = "x86_64", target_os = "linux"),
-		all(target_arch = "x86", target_os = "linux")
+		all(target_arch = "x86", target_os = "linux"),
+		all(target_arch = "aarch64", target_os = "windows"),
+		all(target_arch = "aarch64", target_os = "linux"),
+		all(target_arch = "x86_64", target_os = "macos"),
+		all(target_arch = "aarch64", target_os = "macos")
 	)))]
 	std::compile_error!("Unsupported target for the Wacom STU SDK");
 
@@ -98,9 +144,12 @@ const fn library_name() -> &'static str {
 	let name = "wgssSTU.lib";
 	#[cfg(target_os = "linux")]
 	let name = "libwgssSTU.so";
+	#[cfg(target_os = "macos")]
+	let name = "libwgssSTU.dylib";
 	#[cfg(not(any(
 		target_os = "windows",
 		target_os = "linux",
+		target_os = "macos",
 	)))]
 	std::compile_error!("Unsupported target for the Wacom STU SDK");
 
@@ -0,0 +1,33 @@
+//! Displays a checkered test pattern on the screen of the first available
+//! tablet device, so that one may verify that the screen is working.
+
+fn main() {
+	let mut devices = stu::list_devices()
+		.expect("Could not list the tablet devices");
+	let connector = match devices.next() {
+		Some(connector) => connector,
+		None => {
+			eprintln!("There are no tablet devices available on the system");
+			std::process::exit(1);
+		}
+	};
+	let tablet = connector.connect()
+		.expect("Could not connect to the tablet device");
+	let caps = tablet.capability()
+		.expect("Could not query for device capabilities");
+
+	/* Draw a checkerboard with cells of 32 pixels. */
+	let image = image::GrayImage::from_fn(
+		caps.width(),
+		caps.height(),
+		|x, y| {
+			if (x / 32 + y / 32) % 2 == 0 {
+				image::Luma([0])
+			} else {
+				image::Luma([255])
+			}
+		});
+
+	tablet.write_image(&image, stu::ImageEncoding::Monochrome)
+		.expect("Could not write the test pattern to the device");
+}
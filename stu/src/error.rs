@@ -1,5 +1,4 @@
 use crate::handle::Handle;
-use std::ffi::CStr;
 
 /// The main error type for this crate.
 ///
@@ -8,12 +7,23 @@ use std::ffi::CStr;
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
 	/// An error originating in the Wacom STU API.
-	#[error("{0}")]
+	#[error(transparent)]
 	ApiError(ApiError),
 	/// An error originating in this crate.
-	#[error("{0}")]
+	#[error(transparent)]
 	ClientError(ClientError)
 }
+impl Error {
+	/// The raw value returned by the Wacom STU API function that failed, if
+	/// this error originated in the Wacom STU API.
+	pub fn code(&self) -> Option<i32> {
+		match self {
+			Self::ApiError(what) => Some(what.code()),
+			Self::ClientError(ClientError::Rejected { code }) => Some(*code),
+			Self::ClientError(_) => None
+		}
+	}
+}
 
 /// A structure describing the contents of an API error.
 #[derive(Debug)]
@@ -24,32 +34,150 @@ pub struct ApiError {
 	data: Handle<[std::os::raw::c_char]>,
 	/// The integer code, as given by the Wacom STU API.
 	stu_code: std::os::raw::c_int,
+	/// The value returned by the Wacom STU API function that failed.
+	return_code: std::os::raw::c_int,
+}
+impl ApiError {
+	/// The exception that originated this error.
+	pub fn exception(&self) -> &Exception {
+		&self.exception
+	}
+
+	/// The raw value returned by the Wacom STU API function that failed.
+	pub fn code(&self) -> i32 {
+		self.return_code
+	}
+
+	/// The raw code of the exception, as given by the Wacom STU API.
+	pub fn exception_code(&self) -> i32 {
+		self.stu_code
+	}
+
+	/// The message describing this error, as given by the Wacom STU API, if
+	/// it gave one.
+	///
+	/// This message is picked up from the API once, as the error is created,
+	/// and is usually more specific than the [exception] alone, naming things
+	/// like the system call that failed.
+	///
+	/// [exception]: Self::exception
+	pub fn message(&self) -> Option<String> {
+		if self.data.is_empty() {
+			return None
+		}
+
+		let bytes = self.data.iter()
+			.take_while(|c| **c != 0)
+			.map(|c| *c as u8)
+			.collect::<Vec<_>>();
+		if bytes.is_empty() {
+			None
+		} else {
+			Some(String::from_utf8_lossy(&bytes).into_owned())
+		}
+	}
 }
 impl std::fmt::Display for ApiError {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		write!(f, "{}", self.exception)?;
 
-		let message = unsafe { CStr::from_ptr(self.data.as_ptr() as _) }.to_string_lossy();
-		if message.len() > 0 { write!(f, ": {}", message)?; }
+		if let Some(message) = self.message() { write!(f, ": {}", message)?; }
 
 		Ok(())
 	}
 }
-impl std::error::Error for ApiError { }
+impl std::error::Error for ApiError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		Some(&self.exception)
+	}
+}
 
 /// A structure describing the contents of a client error.
 #[derive(Debug, thiserror::Error)]
 pub enum ClientError {
 	/// An operation that is required but is not supported by the device it's
 	/// trying to operate in.
-	#[error("unsupported operation \"{report_id}\"")]
+	#[error("the device does not support {report_id} reports")]
 	UnsupportedReportId {
 		/// The Report ID of the operation.
-		report_id: stu_sys::tagWacomGSS_ReportId,
+		report_id: crate::ReportId,
 	},
 	/// An incomplete or invalid report was generated by the API.
 	#[error("the device handed out an invalid report")]
-	InvalidReport
+	InvalidReport,
+	/// The name of a serial port could not be passed on to the API.
+	#[error("invalid serial port name \"{port}\"")]
+	InvalidSerialPort {
+		/// The name of the serial port.
+		port: String,
+	},
+	/// An image whose dimensions do not match those of the screen of the
+	/// device was given to be displayed.
+	#[error("expected an image of {}x{} pixels, got {}x{}", expected.0, expected.1, found.0, found.1)]
+	ImageDimensionMismatch {
+		/// The dimensions of the screen of the device.
+		expected: (u32, u32),
+		/// The dimensions of the image that was given.
+		found: (u32, u32),
+	},
+	/// Image data whose length does not match the one expected for the
+	/// dimensions and the encoding of the image was about to be sent to the
+	/// device.
+	#[error("expected {expected} bytes of image data, got {found}")]
+	ImageDataLengthMismatch {
+		/// The number of bytes the device expects.
+		expected: usize,
+		/// The number of bytes that were produced.
+		found: usize,
+	},
+	/// A region that does not lie entirely within the screen of the device
+	/// was given to be drawn to.
+	#[error("the region {}x{}+{}+{} does not fit in a screen of {}x{} pixels", region.width, region.height, region.x, region.y, bounds.0, bounds.1)]
+	RegionOutOfBounds {
+		/// The region that was given.
+		region: crate::Rect,
+		/// The dimensions of the screen of the device.
+		bounds: (u32, u32),
+	},
+	/// Reports coming from the device were lost before they could be read.
+	#[error("pen data reports were lost: expected sequence number {expected}, got {got}")]
+	DroppedReports {
+		/// The sequence number that was expected.
+		expected: u16,
+		/// The sequence number that was received.
+		got: u16,
+	},
+	/// A blocking receive on a queue was interrupted through its
+	/// [`InterruptHandle`].
+	///
+	/// [`InterruptHandle`]: crate::InterruptHandle
+	#[error("the wait for a report was interrupted")]
+	Interrupted,
+	/// The device a tablet was connected to is no longer attached to the
+	/// system, and could not be reconnected to.
+	#[error("the device is no longer attached to the system")]
+	DeviceNotPresent,
+	/// The device a tablet was connected to is attached to the system, but
+	/// a new connection to it could not be established.
+	#[error("could not reconnect to the device")]
+	ReconnectionFailed(#[source] Box<Error>),
+	/// A brightness level above the greatest one supported by the device was
+	/// given to the backlight.
+	#[error("the backlight level {level} is above the maximum of {max} supported by the device")]
+	BacklightOutOfRange {
+		/// The level that was given.
+		level: u8,
+		/// The greatest level supported by the device.
+		max: u8,
+	},
+	/// A call into the Wacom STU API was rejected without the API raising an
+	/// exception, such as when the system does not support the means through
+	/// which devices are attached to it.
+	#[error("the Wacom STU API rejected the operation with code {code}")]
+	Rejected {
+		/// The value returned by the Wacom STU API function that failed.
+		code: i32,
+	},
 }
 
 /// An exception thrown by the Wacom STU API.
@@ -107,6 +235,8 @@ pub(crate) struct InternalError {
 	data: Handle<[std::os::raw::c_char]>,
 	/// The integer code, as given by the Wacom STU API.
 	stu_code: std::os::raw::c_int,
+	/// The value returned by the Wacom STU API function that failed.
+	return_code: std::os::raw::c_int,
 }
 impl InternalError {
 	/// Unwraps this error to an exception, if possible.
@@ -121,10 +251,27 @@ impl InternalError {
 		Error::ApiError(ApiError {
 			exception,
 			data: self.data,
-			stu_code: self.stu_code
+			stu_code: self.stu_code,
+			return_code: self.return_code
 		})
 	}
 
+	/// Converts this error into a general error, for calls whose failures may
+	/// not be exceptions.
+	///
+	/// Unlike [`unwrap_to_general()`], this never panics, and errors that are
+	/// not exceptions get turned into [`ClientError::Rejected`].
+	///
+	/// [`unwrap_to_general()`]: Self::unwrap_to_general
+	pub fn into_error(self) -> Error {
+		match self.code {
+			InternalErrorCode::Exception(_) => self.unwrap_to_general(),
+			_ => Error::ClientError(ClientError::Rejected {
+				code: self.return_code
+			})
+		}
+	}
+
 	/// Tries to create a wrapper around the error value from the Wacom STU API.
 	pub fn from_wacom_stu(what: std::os::raw::c_int) -> Result<(), Self> {
 		let code = match InternalErrorCode::from_wacom_stu(what) {
@@ -147,7 +294,7 @@ impl InternalError {
 			(data, stu_code)
 		};
 
-		Err(Self { code, data, stu_code })
+		Err(Self { code, data, stu_code, return_code: what })
 	}
 }
 
@@ -194,3 +341,75 @@ impl InternalErrorCode {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::error::Error as _;
+
+	/// Creates an API error for the given exception and return value, with no
+	/// message attached to it.
+	///
+	/// The message handle points to no memory managed by the Wacom STU API, so
+	/// the error must be forgotten rather than dropped.
+	fn api_error(exception: Exception, return_code: i32) -> Error {
+		let data = unsafe {
+			Handle::wrap_slice(std::ptr::NonNull::dangling().as_ptr(), 0)
+		};
+
+		Error::ApiError(ApiError {
+			exception,
+			data,
+			stu_code: 0,
+			return_code
+		})
+	}
+
+	#[test]
+	fn api_error_code_survives_a_round_trip() {
+		let code = stu_sys::tagWacomGSS_Return_WacomGSS_Return_Exception_timeout;
+		let error = api_error(Exception::TimedOut, code);
+
+		assert_eq!(error.code(), Some(code));
+		assert_eq!(error.to_string(), Exception::TimedOut.to_string());
+		assert!(format!("{:?}", error).contains(&format!("return_code: {}", code)));
+		assert!(matches!(
+			error.source().and_then(|source| source.downcast_ref::<Exception>()),
+			Some(Exception::TimedOut)));
+
+		std::mem::forget(error);
+	}
+
+	#[test]
+	fn rejected_code_survives_a_round_trip() {
+		let error = Error::ClientError(ClientError::Rejected { code: 42 });
+
+		assert_eq!(error.code(), Some(42));
+		assert!(error.to_string().contains("42"));
+		assert!(format!("{:?}", error).contains("42"));
+	}
+
+	#[test]
+	fn code_survives_the_error_chain() {
+		let error = Error::ClientError(ClientError::ReconnectionFailed(
+			Box::new(Error::ClientError(ClientError::Rejected { code: 42 }))));
+		assert_eq!(error.code(), None);
+
+		/* The message of the cause is not repeated by the error. */
+		assert_eq!(error.to_string(), "could not reconnect to the device");
+
+		let code = std::iter::successors(
+			Some(&error as &(dyn std::error::Error + 'static)),
+			|error| error.source())
+			.filter_map(|error| error.downcast_ref::<Error>()
+				.or_else(|| error.downcast_ref::<Box<Error>>().map(|error| &**error)))
+			.find_map(Error::code);
+		assert_eq!(code, Some(42));
+	}
+
+	#[test]
+	fn client_errors_carry_no_code() {
+		let error = Error::ClientError(ClientError::InvalidReport);
+		assert_eq!(error.code(), None);
+	}
+}
@@ -1,17 +1,36 @@
 /// Handling of errors from the Wacom STU interface.
 mod error;
-pub use error::{Exception, Error};
+pub use error::{Exception, Error, ApiError, ClientError};
 
 /// Code dealing with the handling of reports from the device.
 mod report;
-pub use report::{Queue, Event, TryRecvError};
+pub use report::{Queue, Event, SerializableEvent, ButtonEvent, TryRecvError, InterruptHandle, Smoothing, Calibration};
 
 /// Handles to memory managed by the Wacom STU allocator.
 mod handle;
 
+/// A simulated device, for use without a device attached to the system.
+#[cfg(feature = "simulated")]
+mod simulated;
+
+/// Guidance drawn onto the screen of the device before a signature is taken.
+#[cfg(feature = "overlay")]
+pub mod overlay;
+
+/// Notifications of devices being attached to and detached from the system.
+#[cfg(feature = "watch")]
+mod watch;
+#[cfg(feature = "watch")]
+pub use watch::{DeviceEvent, DeviceWatcher, watch_devices};
+
+/// Friendly names for the types of report a device may support.
+mod report_id;
+pub use report_id::ReportId;
+
 use std::collections::HashSet;
+use std::convert::TryFrom;
 use crate::handle::Handle;
-use crate::error::{InternalError, ClientError};
+use crate::error::InternalError;
 
 use std::sync::{Arc, Mutex};
 
@@ -21,69 +40,194 @@ pub struct Tablet {
 	raw: Arc<RawTabletConnection>,
 	/// The list of reports types supported by this tablet.
 	supported_reports: HashSet<stu_sys::tagWacomGSS_ReportId>,
+	/// The device this tablet is connected to.
+	device: ConnectorDevice,
+	/// The capabilities of the device, once they have been queried.
+	capability: Mutex<Option<Capability>>,
+	/// The calibration handed to the queues opened on this tablet.
+	calibration: Mutex<Calibration>,
+	/// The image last written to the screen of a simulated device.
+	#[cfg(feature = "simulated")]
+	display: Mutex<Option<image::GrayImage>>,
+	/// The brightness level of the backlight of a simulated device.
+	#[cfg(feature = "simulated")]
+	backlight: Mutex<u8>,
 }
 impl Tablet {
 	/// Create a new Tablet instance from the given RawTablet interface.
-	pub(crate) fn wrap(raw: RawTabletConnection) -> Result<Self, Error> {
-		let supported_reports = {
-			let report_list = unsafe {
-				let mut list = std::ptr::null_mut();
-				let mut length = 0;
-
-				let result = raw.dispatch(|interface| {
-					stu_sys::WacomGSS_Interface_getReportCountLengths(
-						interface,
-						&mut length,
-						&mut list)
-				});
-				let result = InternalError::from_wacom_stu(result)
-					.map_err(InternalError::unwrap_to_general);
-
-				match result {
-					Ok(_) => Some(Handle::wrap_slice(list, length as _)),
-					Err(what) => {
-						log::warn!(
-							"tablet does not support getReportCountLengths: {}",
-							what);
-						None
-					}
+	pub(crate) fn wrap(
+		raw: RawTabletConnection,
+		device: ConnectorDevice) -> Result<Self, Error> {
+
+		let supported_reports = Self::query_supported_reports(&raw);
+		Ok(Self {
+			raw: Arc::new(raw),
+			supported_reports,
+			device,
+			capability: Mutex::new(None),
+			calibration: Mutex::new(Default::default()),
+			#[cfg(feature = "simulated")]
+			display: Mutex::new(None),
+			#[cfg(feature = "simulated")]
+			backlight: Mutex::new(simulated::MAX_BACKLIGHT)
+		})
+	}
+
+	/// Get the information about the device this tablet is connected to.
+	pub fn info(&self) -> Information {
+		self.device.info()
+	}
+
+	/// Disconnects from the device, consuming this tablet.
+	///
+	/// Dropping a tablet also disconnects from the device, but has no way to
+	/// report failures in doing so, which this function does. Queues opened on
+	/// this tablet share its connection, and will start failing once this
+	/// function returns.
+	pub fn disconnect(self) -> Result<(), Error> {
+		self.raw.disconnect()
+	}
+
+	/// Drops the current connection to the device and connects to it again.
+	///
+	/// This is meant to bring a tablet back after its device has been
+	/// disconnected and then attached to the system once again. Queues opened
+	/// on this tablet before the call to this function are left connected to
+	/// the old, stale, connection and will keep failing. They must be dropped
+	/// and opened again with [`queue()`] after this function returns.
+	///
+	/// If the device is not attached to the system, this function fails with
+	/// [`ClientError::DeviceNotPresent`]. If the device is attached but the
+	/// connection could not be established, this function fails with
+	/// [`ClientError::ReconnectionFailed`].
+	///
+	/// [`queue()`]: Self::queue
+	pub fn reconnect(&mut self) -> Result<(), Error> {
+		/* Let go of the stale connection. The queues may still be holding on
+		 * to it, so we can't just drop it. */
+		let _ = self.raw.disconnect();
+
+		/* USB devices may come back under a different system path, so look
+		 * them up again, rather than reusing the old description. */
+		let (device, interface) = match &self.device {
+			ConnectorDevice::Usb(_) => {
+				let info = self.info();
+				let mut devices = list_devices()?;
+				let connector = devices
+					.find(|connector| connector.info() == info)
+					.ok_or(Error::ClientError(ClientError::DeviceNotPresent))?;
+
+				let interface = connector.device.open();
+				(connector.device, interface)
+			},
+			ConnectorDevice::Serial { .. } => {
+				let interface = self.device.open();
+				(self.device.clone(), interface)
+			},
+			#[cfg(feature = "simulated")]
+			ConnectorDevice::Simulated => {
+				let interface = self.device.open();
+				(self.device.clone(), interface)
+			}
+		};
+		let interface = interface
+			.map_err(|what| Error::ClientError(ClientError::ReconnectionFailed(
+				Box::new(what))))?;
+
+		let raw = RawTabletConnection {
+			interface: Mutex::new(interface)
+		};
+
+		self.supported_reports = Self::query_supported_reports(&raw);
+		self.raw = Arc::new(raw);
+		self.device = device;
+		*self.capability.get_mut().unwrap() = None;
+
+		Ok(())
+	}
+
+	/// Queries the device for the list of report types it supports.
+	fn query_supported_reports(raw: &RawTabletConnection)
+		-> HashSet<stu_sys::tagWacomGSS_ReportId> {
+
+		/* Simulated devices support no reports at all, which is what keeps the
+		 * rest of the functions from calling into the API on their behalf. */
+		if raw.is_simulated() {
+			return HashSet::new()
+		}
+
+		let report_list = unsafe {
+			let mut list = std::ptr::null_mut();
+			let mut length = 0;
+
+			let result = raw.dispatch(|interface| {
+				stu_sys::WacomGSS_Interface_getReportCountLengths(
+					interface,
+					&mut length,
+					&mut list)
+			});
+			let result = InternalError::from_wacom_stu(result)
+				.map_err(InternalError::unwrap_to_general);
+
+			match result {
+				Ok(_) => Some(Handle::wrap_slice(list, length as _)),
+				Err(what) => {
+					log::warn!(
+						"tablet does not support getReportCountLengths: {}",
+						what);
+					None
 				}
-			};
-
-			let capacity = report_list.as_ref().map(|a| a.len()).unwrap_or(0);
-			let mut supported = HashSet::with_capacity(capacity);
-			if let Some(report_list) = report_list {
-				for i in 0..report_list.len() {
-					if report_list[i] != 0 {
-						/* Mark this report type as being supported. */
-						supported.insert(i as _);
-					}
+			}
+		};
+
+		let capacity = report_list.as_ref().map(|a| a.len()).unwrap_or(0);
+		let mut supported = HashSet::with_capacity(capacity);
+		if let Some(report_list) = report_list {
+			for i in 0..report_list.len() {
+				if report_list[i] != 0 {
+					/* Mark this report type as being supported. */
+					supported.insert(i as _);
 				}
 			}
+		}
 
-			supported
-		};
+		supported
+	}
 
-		Ok(Self {
-			raw: Arc::new(raw),
-			supported_reports
-		})
+	/// Whether the given type of report is supported by this device.
+	pub fn supports(&self, report: ReportId) -> bool {
+		self.supported_reports.contains(&report.raw())
 	}
 
-	/// Checks whether a given Report ID is supported by this device.
-	fn check_support(&self, report_id: stu_sys::tagWacomGSS_ReportId)
-		-> Result<(), Error> {
+	/// The types of report supported by this device, in no particular order.
+	pub fn supported_reports(&self) -> impl Iterator<Item = ReportId> + '_ {
+		self.supported_reports
+			.iter()
+			.map(|report| ReportId::from_raw(*report))
+	}
 
-		if self.supported_reports.contains(&report_id) {
+	/// Checks whether a given Report ID is supported by this device.
+	fn check_support(&self, report_id: ReportId) -> Result<(), Error> {
+		if self.supports(report_id) {
 			Ok(())
 		} else {
 			Err(Error::ClientError(ClientError::UnsupportedReportId { report_id }))
 		}
 	}
 
+	/// Checks whether the device is still connected.
+	///
+	/// A device that has been cleanly disconnected, such as by having its cable
+	/// pulled, reports `false`, while errors in the transport itself are
+	/// reported as errors. This function does not interfere with any queues
+	/// that are open on the device.
+	pub fn is_connected(&self) -> Result<bool, Error> {
+		self.raw.connected()
+	}
+
 	/// Clear the screen of the device.
 	pub fn clear(&self) -> Result<(), Error> {
-		self.check_support(stu_sys::tagWacomGSS_ReportId_WacomGSS_ReportId_ClearScreen)?;
+		self.check_support(ReportId::ClearScreen)?;
 
 		let result = self.raw.dispatch(|interface| unsafe {
 			stu_sys::WacomGSS_Protocol_setClearScreen(interface)
@@ -92,9 +236,31 @@ impl Tablet {
 			.map_err(InternalError::unwrap_to_general)
 	}
 
+	/// Clears the screen of the device, if the device supports doing so.
+	///
+	/// Unlike [`clear()`], devices that do not support clearing their screens
+	/// are left alone, rather than failing with
+	/// [`ClientError::UnsupportedReportId`].
+	///
+	/// [`clear()`]: Self::clear
+	pub fn try_clear(&self) -> Result<(), Error> {
+		unsupported_as_ok(self.clear())
+	}
+
+	/// Changes whether inking on the display is enabled or not, if the device
+	/// supports doing so.
+	///
+	/// Unlike [`inking()`], devices that do not support inking are left alone,
+	/// rather than failing with [`ClientError::UnsupportedReportId`].
+	///
+	/// [`inking()`]: Self::inking
+	pub fn try_inking(&self, enabled: bool) -> Result<(), Error> {
+		unsupported_as_ok(self.inking(enabled))
+	}
+
 	/// Changes whether inking on the display is enabled or not.
 	pub fn inking(&self, enabled: bool) -> Result<(), Error> {
-		self.check_support(stu_sys::tagWacomGSS_ReportId_WacomGSS_ReportId_InkingMode)?;
+		self.check_support(ReportId::InkingMode)?;
 
 		let mode = if enabled {
 			stu_sys::tagWacomGSS_InkingMode_WacomGSS_InkingMode_On
@@ -108,42 +274,807 @@ impl Tablet {
 			.map_err(InternalError::unwrap_to_general)
 	}
 
-	/// Get information on the layout and the capabilities of the device.
-	pub fn capability(&self) -> Result<Capability, Error> {
-		self.check_support(stu_sys::tagWacomGSS_ReportId_WacomGSS_ReportId_Capability)?;
-		let capability = unsafe {
-			let mut capability = std::mem::zeroed();
+	/// Whether inking on the display is currently enabled or not.
+	///
+	/// See [`inking()`] for changing this state.
+	///
+	/// [`inking()`]: Self::inking
+	pub fn inking_state(&self) -> Result<bool, Error> {
+		self.check_support(ReportId::InkingMode)?;
+
+		let mut mode = 0;
+		let result = self.raw.dispatch(|interface| unsafe {
+			stu_sys::WacomGSS_Protocol_getInkingMode(
+				interface,
+				&mut mode)
+		});
+		InternalError::from_wacom_stu(result)
+			.map_err(InternalError::unwrap_to_general)?;
+
+		Ok(u32::from(mode) != stu_sys::tagWacomGSS_InkingMode_WacomGSS_InkingMode_Off as u32)
+	}
+
+	/// Get the current brightness level of the backlight of the screen.
+	///
+	/// See [`set_backlight()`] for the meaning of the values.
+	///
+	/// [`set_backlight()`]: Self::set_backlight
+	pub fn backlight(&self) -> Result<u8, Error> {
+		#[cfg(feature = "simulated")]
+		{
+			if let ConnectorDevice::Simulated = self.device {
+				return Ok(*self.backlight.lock().unwrap())
+			}
+		}
+
+		self.check_support(ReportId::BacklightBrightness)?;
+
+		let mut level = 0;
+		let result = self.raw.dispatch(|interface| unsafe {
+			stu_sys::WacomGSS_Protocol_getBacklightBrightness(
+				interface,
+				&mut level)
+		});
+		InternalError::from_wacom_stu(result)
+			.map_err(InternalError::unwrap_to_general)?;
+
+		Ok(u8::try_from(level).unwrap_or(u8::MAX))
+	}
+
+	/// The greatest brightness level the backlight of the screen can be set
+	/// to.
+	///
+	/// The maximum is looked up from the model of the device. Models this
+	/// crate does not know the maximum for report [`u8::MAX`], and leave it up
+	/// to the device to reject levels that are too high.
+	pub fn max_backlight(&self) -> Result<u8, Error> {
+		#[cfg(feature = "simulated")]
+		{
+			if let ConnectorDevice::Simulated = self.device {
+				return Ok(simulated::MAX_BACKLIGHT)
+			}
+		}
+
+		self.check_support(ReportId::BacklightBrightness)?;
+		Ok(self.info().max_backlight().unwrap_or(u8::MAX))
+	}
+
+	/// Changes the brightness level of the backlight of the screen.
+	///
+	/// A level of `0` turns the backlight off, on devices that support doing
+	/// so, and greater values make for a brighter screen. The maximum level is
+	/// specific to each device, and is given by [`max_backlight()`]. Levels
+	/// above it are rejected with [`ClientError::BacklightOutOfRange`].
+	///
+	/// [`max_backlight()`]: Self::max_backlight
+	pub fn set_backlight(&self, level: u8) -> Result<(), Error> {
+		let max = self.max_backlight()?;
+		if level > max {
+			return Err(Error::ClientError(ClientError::BacklightOutOfRange {
+				level,
+				max
+			}))
+		}
+
+		#[cfg(feature = "simulated")]
+		{
+			if let ConnectorDevice::Simulated = self.device {
+				*self.backlight.lock().unwrap() = level;
+				return Ok(())
+			}
+		}
+
+		let result = self.raw.dispatch(|interface| unsafe {
+			stu_sys::WacomGSS_Protocol_setBacklightBrightness(
+				interface,
+				u16::from(level))
+		});
+		InternalError::from_wacom_stu(result)
+			.map_err(InternalError::unwrap_to_general)
+	}
+
+	/// Get information on the layout and the capabilities of the device.
+	///
+	/// The device is only asked for its capabilities the first time this
+	/// function is called, with the answer being kept around for later calls.
+	/// See [`refresh_capability()`] for asking the device again.
+	///
+	/// [`refresh_capability()`]: Self::refresh_capability
+	pub fn capability(&self) -> Result<Capability, Error> {
+		let mut cached = self.capability.lock().unwrap();
+		match *cached {
+			Some(capability) => Ok(capability),
+			None => {
+				let capability = self.query_capability()?;
+				*cached = Some(capability);
+
+				Ok(capability)
+			}
+		}
+	}
+
+	/// Asks the device for its capabilities again, replacing the ones kept
+	/// around by [`capability()`].
+	///
+	/// [`capability()`]: Self::capability
+	pub fn refresh_capability(&self) -> Result<Capability, Error> {
+		let capability = self.query_capability()?;
+		*self.capability.lock().unwrap() = Some(capability);
+
+		Ok(capability)
+	}
+
+	/// Asks the device for information on its layout and its capabilities.
+	fn query_capability(&self) -> Result<Capability, Error> {
+		#[cfg(feature = "simulated")]
+		{
+			if let ConnectorDevice::Simulated = self.device {
+				return Ok(simulated::CAPABILITY)
+			}
+		}
+
+		self.check_support(ReportId::Capability)?;
+		let capability = unsafe {
+			let mut capability = std::mem::zeroed();
+
+			let result = self.raw.dispatch(|interface| {
+				stu_sys::WacomGSS_Protocol_getCapability(
+					interface,
+					std::mem::size_of::<stu_sys::WacomGSS_Capability>() as _,
+					&mut capability)
+			});
+			InternalError::from_wacom_stu(result)
+				.map_err(InternalError::unwrap_to_general)?;
+
+			Handle::wrap(capability)
+		};
+
+		Ok(Capability {
+			display_width: u32::from(capability.screenWidth),
+			display_height: u32::from(capability.screenHeight),
+			input_width: u32::from(capability.tabletMaxX),
+			input_height: u32::from(capability.tabletMaxY),
+			input_depth: u32::from(capability.tabletMaxPressure),
+			input_resolution: u32::from(capability.resolution),
+			encoding_flags: capability.encodingFlag as u8
+		})
+	}
+
+	/// Get the name of the model of the device, as reported by the device.
+	pub fn model_name(&self) -> Result<String, Error> {
+		let information = self.information()?;
+		Ok(string_from_c_chars(&information.modelName[..]))
+	}
+
+	/// Get the version of the firmware running on the device, as a pair of
+	/// major and minor version numbers.
+	pub fn firmware_version(&self) -> Result<(u16, u16), Error> {
+		let information = self.information()?;
+		Ok((
+			u16::from(information.firmwareMajorVersion),
+			u16::from(information.firmwareMinorVersion)))
+	}
+
+	/// Get the information block reported by the device.
+	fn information(&self) -> Result<stu_sys::WacomGSS_Information, Error> {
+		self.check_support(ReportId::Information)?;
+		unsafe {
+			let mut information: stu_sys::WacomGSS_Information = std::mem::zeroed();
+
+			let result = self.raw.dispatch(|interface| {
+				stu_sys::WacomGSS_Protocol_getInformation(
+					interface,
+					std::mem::size_of::<stu_sys::WacomGSS_Information>() as _,
+					&mut information)
+			});
+			InternalError::from_wacom_stu(result)
+				.map_err(InternalError::unwrap_to_general)?;
+
+			Ok(information)
+		}
+	}
+
+	/// Get the serial number of the device.
+	pub fn serial_number(&self) -> Result<String, Error> {
+		self.check_support(ReportId::Uid2)?;
+		let uid = unsafe {
+			let mut uid: stu_sys::WacomGSS_Uid2 = std::mem::zeroed();
+
+			let result = self.raw.dispatch(|interface| {
+				stu_sys::WacomGSS_Protocol_getUid2(
+					interface,
+					std::mem::size_of::<stu_sys::WacomGSS_Uid2>() as _,
+					&mut uid)
+			});
+			InternalError::from_wacom_stu(result)
+				.map_err(InternalError::unwrap_to_general)?;
+
+			uid
+		};
+
+		Ok(string_from_c_chars(&uid.uid2[..]))
+	}
+
+	/// Displays the given image on the screen of the device.
+	///
+	/// The image must have the exact same dimensions as the screen of the
+	/// device, as reported by [`capability()`], and will be encoded in the
+	/// given format before being sent to the device.
+	///
+	/// Simulated devices keep the image as it was given, which can then be
+	/// read back with [`read_display()`].
+	///
+	/// [`capability()`]: Self::capability
+	/// [`read_display()`]: Self::read_display
+	pub fn write_image(
+		&self,
+		image: &image::GrayImage,
+		encoding: ImageEncoding) -> Result<(), Error> {
+
+		#[cfg(feature = "simulated")]
+		{
+			if let ConnectorDevice::Simulated = self.device {
+				self.check_image_dimensions(image)?;
+				*self.display.lock().unwrap() = Some(image.clone());
+
+				return Ok(())
+			}
+		}
+
+		self.check_support(ReportId::StartImageData)?;
+
+		self.check_image_dimensions(image)?;
+
+		let data = encoding.encode(image);
+		let result = self.raw.dispatch(|interface| unsafe {
+			stu_sys::WacomGSS_ProtocolHelper_writeImage(
+				interface,
+				encoding.mode() as _,
+				data.as_ptr(),
+				data.len() as _)
+		});
+		InternalError::from_wacom_stu(result)
+			.map_err(InternalError::unwrap_to_general)
+	}
+
+	/// Reads back the image currently shown on the screen of the device.
+	///
+	/// The protocol has no report through which the contents of the screen can
+	/// be read back, so this function always fails with
+	/// [`ClientError::UnsupportedReportId`] on real devices, naming the report
+	/// through which images get written. Applications that need to know what
+	/// is being displayed on them must keep a copy of the last image they
+	/// wrote. Simulated devices, on the other hand, hand back the last image
+	/// written to them with [`write_image()`] or [`write_dynamic_image()`], or
+	/// a blank screen if there was none.
+	///
+	/// [`ClientError::UnsupportedReportId`]: ClientError::UnsupportedReportId
+	/// [`write_image()`]: Self::write_image
+	/// [`write_dynamic_image()`]: Self::write_dynamic_image
+	pub fn read_display(&self) -> Result<image::GrayImage, Error> {
+		#[cfg(feature = "simulated")]
+		{
+			if let ConnectorDevice::Simulated = self.device {
+				let caps = self.capability()?;
+				let display = self.display.lock().unwrap();
+
+				return Ok(display.clone().unwrap_or_else(|| {
+					image::GrayImage::from_pixel(
+						caps.width(),
+						caps.height(),
+						image::Luma([u8::MAX]))
+				}))
+			}
+		}
+
+		Err(Error::ClientError(ClientError::UnsupportedReportId {
+			report_id: ReportId::StartImageData
+		}))
+	}
+
+	/// Checks that the given image has the same dimensions as the screen of
+	/// the device.
+	fn check_image_dimensions(&self, image: &image::GrayImage) -> Result<(), Error> {
+		let caps = self.capability()?;
+		if image.width() != caps.width() || image.height() != caps.height() {
+			return Err(Error::ClientError(ClientError::ImageDimensionMismatch {
+				expected: (caps.width(), caps.height()),
+				found: (image.width(), image.height())
+			}))
+		}
+
+		Ok(())
+	}
+
+	/// Displays the given image on the screen of the device, converting it to
+	/// a format the device can display.
+	///
+	/// The image must have the exact same dimensions as the screen of the
+	/// device, as reported by [`capability()`]. When no encoding is given, the
+	/// one preferred by the device is used, as reported by
+	/// [`Capability::preferred_encoding()`]. Unlike [`write_image()`], colors
+	/// are kept on devices with color screens, and images sent to monochrome
+	/// screens are dithered, rather than just cut off at half intensity.
+	///
+	/// Simulated devices keep a grayscale copy of the image, which can then be
+	/// read back with [`read_display()`].
+	///
+	/// [`capability()`]: Self::capability
+	/// [`Capability::preferred_encoding()`]: Capability::preferred_encoding
+	/// [`write_image()`]: Self::write_image
+	/// [`read_display()`]: Self::read_display
+	pub fn write_dynamic_image(
+		&self,
+		image: &image::DynamicImage,
+		encoding: Option<ImageEncoding>) -> Result<(), Error> {
+
+		#[cfg(feature = "simulated")]
+		{
+			if let ConnectorDevice::Simulated = self.device {
+				let image = image.to_luma8();
+				self.check_image_dimensions(&image)?;
+				*self.display.lock().unwrap() = Some(image);
+
+				return Ok(())
+			}
+		}
+
+		self.check_support(ReportId::StartImageData)?;
+
+		let caps = self.capability()?;
+		if image.width() != caps.width() || image.height() != caps.height() {
+			return Err(Error::ClientError(ClientError::ImageDimensionMismatch {
+				expected: (caps.width(), caps.height()),
+				found: (image.width(), image.height())
+			}))
+		}
+
+		let encoding = encoding.unwrap_or_else(|| caps.preferred_encoding());
+		let data = encoding.encode_dynamic(image);
+
+		/* The device paints garbage when handed the wrong amount of data, so
+		 * make sure we got it right before sending it over. */
+		let expected = encoding.encoded_len(image.width(), image.height());
+		if data.len() != expected {
+			return Err(Error::ClientError(ClientError::ImageDataLengthMismatch {
+				expected,
+				found: data.len()
+			}))
+		}
+
+		let result = self.raw.dispatch(|interface| unsafe {
+			stu_sys::WacomGSS_ProtocolHelper_writeImage(
+				interface,
+				encoding.mode() as _,
+				data.as_ptr(),
+				data.len() as _)
+		});
+		InternalError::from_wacom_stu(result)
+			.map_err(InternalError::unwrap_to_general)
+	}
+
+	/// Blanks out the given region of the screen of the device, leaving the
+	/// rest of the screen untouched.
+	///
+	/// The region is given in display pixels, with the origin at the top left
+	/// corner of the screen, and must lie entirely within the screen of the
+	/// device, as reported by [`capability()`]. Clearing an empty region does
+	/// nothing.
+	///
+	/// [`capability()`]: Self::capability
+	pub fn clear_region(&self, region: Rect) -> Result<(), Error> {
+		self.check_support(ReportId::StartImageDataArea)?;
+
+		let caps = self.capability()?;
+		if !region.fits_within(caps.width(), caps.height()) {
+			return Err(Error::ClientError(ClientError::RegionOutOfBounds {
+				region,
+				bounds: (caps.width(), caps.height())
+			}))
+		}
+		if region.is_empty() {
+			return Ok(())
+		}
+
+		let area = region.to_raw();
+
+		let encoding = ImageEncoding::Monochrome;
+		let blank = image::GrayImage::from_pixel(
+			u32::from(region.width),
+			u32::from(region.height),
+			image::Luma([u8::MAX]));
+		let data = encoding.encode(&blank);
+
+		let result = self.raw.dispatch(|interface| unsafe {
+			stu_sys::WacomGSS_ProtocolHelper_writeImageArea(
+				interface,
+				encoding.mode() as _,
+				&area,
+				data.as_ptr(),
+				data.len() as _)
+		});
+		InternalError::from_wacom_stu(result)
+			.map_err(InternalError::unwrap_to_general)
+	}
+
+	/// Confines the inking done by the device itself to the given region of its
+	/// screen.
+	///
+	/// The region is given in display pixels, with the origin at the top left
+	/// corner of the screen, and must lie entirely within the screen of the
+	/// device, as reported by [`capability()`], and must not be empty. Pen
+	/// data is still reported for the whole screen, regardless of this region.
+	///
+	/// [`capability()`]: Self::capability
+	pub fn set_handwriting_display_area(&self, region: Rect) -> Result<(), Error> {
+		self.check_support(ReportId::HandwritingDisplayArea)?;
+
+		let caps = self.capability()?;
+		if !region.fits_within(caps.width(), caps.height()) || region.is_empty() {
+			return Err(Error::ClientError(ClientError::RegionOutOfBounds {
+				region,
+				bounds: (caps.width(), caps.height())
+			}))
+		}
+
+		let area = region.to_raw();
+		let result = self.raw.dispatch(|interface| unsafe {
+			stu_sys::WacomGSS_Protocol_setHandwritingDisplayArea(
+				interface,
+				std::mem::size_of::<stu_sys::WacomGSS_Rectangle>() as _,
+				&area)
+		});
+		InternalError::from_wacom_stu(result)
+			.map_err(InternalError::unwrap_to_general)
+	}
+
+	/// Changes the color and the thickness of the pen strokes the device draws
+	/// on its own screen while inking.
+	///
+	/// The color is given as its red, green and blue components, and is only
+	/// supported by devices with color screens. The meaning of the thickness
+	/// is specific to each device, which do not report the thickest stroke
+	/// they are able to draw, and trying to set a thickness above it will cause
+	/// the device to reject the command.
+	pub fn set_ink_style(&self, color: [u8; 3], thickness: u8) -> Result<(), Error> {
+		self.check_support(ReportId::HandwritingThicknessColor24)?;
+
+		let style = stu_sys::WacomGSS_HandwritingThicknessColor24 {
+			penColor: pack_color24(color) as _,
+			penThickness: thickness as _,
+		};
+		let result = self.raw.dispatch(|interface| unsafe {
+			stu_sys::WacomGSS_Protocol_setHandwritingThicknessColor24(
+				interface,
+				std::mem::size_of::<stu_sys::WacomGSS_HandwritingThicknessColor24>() as _,
+				&style)
+		});
+		InternalError::from_wacom_stu(result)
+			.map_err(InternalError::unwrap_to_general)
+	}
+
+	/// Get the mode in which the device currently reports pen data.
+	///
+	/// See [`set_pen_data_mode()`] for changing this mode.
+	///
+	/// [`set_pen_data_mode()`]: Self::set_pen_data_mode
+	pub fn pen_data_mode(&self) -> Result<PenDataMode, Error> {
+		self.check_support(ReportId::PenDataOptionMode)?;
+
+		let mut mode = 0;
+		let result = self.raw.dispatch(|interface| unsafe {
+			stu_sys::WacomGSS_Protocol_getPenDataOptionMode(
+				interface,
+				&mut mode)
+		});
+		InternalError::from_wacom_stu(result)
+			.map_err(InternalError::unwrap_to_general)?;
+
+		PenDataMode::from_raw(mode.into())
+			.ok_or(Error::ClientError(ClientError::InvalidReport))
+	}
+
+	/// Changes the mode in which the device reports pen data.
+	///
+	/// Queues understand pen data reported in every one of these modes, but
+	/// the timing and sequencing information carried by an [event] is only
+	/// available when the device is in a mode that reports it.
+	///
+	/// [event]: Event
+	pub fn set_pen_data_mode(&self, mode: PenDataMode) -> Result<(), Error> {
+		self.check_support(ReportId::PenDataOptionMode)?;
+
+		let result = self.raw.dispatch(|interface| unsafe {
+			stu_sys::WacomGSS_Protocol_setPenDataOptionMode(
+				interface,
+				mode.raw() as _)
+		});
+		InternalError::from_wacom_stu(result)
+			.map_err(InternalError::unwrap_to_general)
+	}
+
+	/// The calibration applied to the position of the pen by the queues opened
+	/// on this tablet.
+	pub fn calibration(&self) -> Calibration {
+		*self.calibration.lock().unwrap()
+	}
+
+	/// Changes the calibration applied to the position of the pen by the
+	/// queues opened on this tablet from now on.
+	///
+	/// Queues that are already open keep the calibration they were opened
+	/// with, and must be changed through [`Queue::set_calibration()`].
+	pub fn set_calibration(&self, calibration: Calibration) {
+		*self.calibration.lock().unwrap() = calibration;
+	}
+
+	/// Opens a queue with which to receive events from the tablet.
+	pub fn queue(&self) -> Result<Queue, Error> {
+		#[cfg(feature = "simulated")]
+		{
+			if let ConnectorDevice::Simulated = self.device {
+				return Ok(simulated::queue(self.calibration()))
+			}
+		}
+
+		let caps = self.capability()?;
+		Queue::new(self, caps)
+	}
+}
+
+/// Turns a failure caused by the device not supporting an operation into a
+/// success, leaving every other result untouched.
+fn unsupported_as_ok(result: Result<(), Error>) -> Result<(), Error> {
+	match result {
+		Err(Error::ClientError(ClientError::UnsupportedReportId { .. })) => Ok(()),
+		result => result
+	}
+}
+
+/// A rectangular region of the screen of the device, in display pixels.
+///
+/// The coordinates have the same range as the ones in the rectangles used by
+/// the Wacom STU API.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Rect {
+	/// The position of the left edge of the region.
+	pub x: u16,
+	/// The position of the top edge of the region.
+	pub y: u16,
+	/// The width of the region.
+	pub width: u16,
+	/// The height of the region.
+	pub height: u16,
+}
+impl Rect {
+	/// Creates a new region with its top left corner at the given position and
+	/// with the given dimensions.
+	pub fn new(x: u16, y: u16, width: u16, height: u16) -> Self {
+		Self { x, y, width, height }
+	}
+
+	/// Creates a new region covering the whole of the screen of a device with
+	/// the given capabilities.
+	pub fn full(caps: &Capability) -> Self {
+		Self::new(0, 0, clamp_u16(caps.width()), clamp_u16(caps.height()))
+	}
+
+	/// Whether this region covers no pixels at all.
+	pub fn is_empty(&self) -> bool {
+		self.width == 0 || self.height == 0
+	}
+
+	/// Whether the pixel at the given position lies within this region.
+	pub fn contains(&self, x: u16, y: u16) -> bool {
+		x >= self.x
+			&& y >= self.y
+			&& u32::from(x) < self.right()
+			&& u32::from(y) < self.bottom()
+	}
+
+	/// The region covered by both this and the given region, if they overlap
+	/// in at least one pixel.
+	pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+		let left = self.x.max(other.x);
+		let top = self.y.max(other.y);
+		let right = self.right().min(other.right());
+		let bottom = self.bottom().min(other.bottom());
+
+		if u32::from(left) >= right || u32::from(top) >= bottom {
+			return None
+		}
+
+		/* Both are at most as large as the dimensions of either region. */
+		Some(Rect::new(
+			left,
+			top,
+			clamp_u16(right - u32::from(left)),
+			clamp_u16(bottom - u32::from(top))))
+	}
+
+	/// Shrinks this region so that it lies entirely within the screen of a
+	/// device with the given capabilities.
+	///
+	/// Regions lying entirely outside of the screen become empty regions at
+	/// the closest corner or edge of the screen.
+	pub fn clamp_to(&self, caps: &Capability) -> Rect {
+		match self.intersect(&Rect::full(caps)) {
+			Some(region) => region,
+			None => Rect::new(
+				self.x.min(clamp_u16(caps.width())),
+				self.y.min(clamp_u16(caps.height())),
+				0,
+				0)
+		}
+	}
+
+	/// The position one past the right edge of this region.
+	fn right(&self) -> u32 {
+		u32::from(self.x) + u32::from(self.width)
+	}
+
+	/// The position one past the bottom edge of this region.
+	fn bottom(&self) -> u32 {
+		u32::from(self.y) + u32::from(self.height)
+	}
+
+	/// Whether this region lies entirely within a screen of the given size.
+	fn fits_within(&self, width: u32, height: u32) -> bool {
+		self.right() <= width && self.bottom() <= height
+	}
+
+	/// Converts this region into the rectangle used by the Wacom STU API.
+	///
+	/// The region must not be empty, as the bounds of those rectangles are
+	/// inclusive on both ends, and must not reach past the last coordinate
+	/// those rectangles can hold.
+	fn to_raw(&self) -> stu_sys::WacomGSS_Rectangle {
+		stu_sys::WacomGSS_Rectangle {
+			upperLeftXpixel: self.x,
+			upperLeftYpixel: self.y,
+			lowerRightXpixel: self.x + (self.width - 1),
+			lowerRightYpixel: self.y + (self.height - 1),
+		}
+	}
+}
+
+/// Converts the given value into a 16-bit value, saturating at its maximum.
+fn clamp_u16(value: u32) -> u16 {
+	u16::try_from(value).unwrap_or(u16::MAX)
+}
+
+/// The formats in which image data can be sent to the screen of the device.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ImageEncoding {
+	/// Monochrome image data, in which every pixel takes up a single bit.
+	///
+	/// Pixels darker than half of the maximum intensity are painted in the
+	/// color of the ink, and all other pixels are left blank.
+	Monochrome,
+	/// Color image data, in which every pixel takes up 16 bits, in RGB565.
+	Rgb565,
+}
+impl ImageEncoding {
+	/// The encoding mode value used by the Wacom STU API for this encoding.
+	fn mode(&self) -> stu_sys::tagWacomGSS_EncodingMode {
+		match self {
+			Self::Monochrome =>
+				stu_sys::tagWacomGSS_EncodingMode_WacomGSS_EncodingMode_1bit,
+			Self::Rgb565 =>
+				stu_sys::tagWacomGSS_EncodingMode_WacomGSS_EncodingMode_16bit,
+		}
+	}
+
+	/// The number of bytes an image with the given dimensions takes up once
+	/// encoded in this format.
+	pub fn encoded_len(&self, width: u32, height: u32) -> usize {
+		match self {
+			Self::Monochrome => (width as usize + 7) / 8 * height as usize,
+			Self::Rgb565 => width as usize * height as usize * 2,
+		}
+	}
+
+	/// Encodes the given image into a buffer in this format, dithering or
+	/// converting its colors as needed.
+	fn encode_dynamic(&self, image: &image::DynamicImage) -> Vec<u8> {
+		match self {
+			Self::Monochrome => self.encode(&dither(image.to_luma8())),
+			Self::Rgb565 => {
+				let image = image.to_rgb8();
+				let mut data = Vec::with_capacity(
+					self.encoded_len(image.width(), image.height()));
+
+				for pixel in image.pixels() {
+					let color = rgb565(pixel.0);
+					data.extend_from_slice(&color.to_le_bytes());
+				}
+
+				data
+			}
+		}
+	}
+
+	/// Encodes the given image into a buffer in this format.
+	fn encode(&self, image: &image::GrayImage) -> Vec<u8> {
+		match self {
+			Self::Monochrome => {
+				/* Rows are packed with the most significant bit first, and
+				 * are padded out to a whole number of bytes. */
+				let stride = (image.width() as usize + 7) / 8;
+				let mut data = vec![0u8; stride * image.height() as usize];
+
+				for (x, y, pixel) in image.enumerate_pixels() {
+					if pixel.0[0] < 128 {
+						let index = y as usize * stride + x as usize / 8;
+						data[index] |= 0x80 >> (x % 8);
+					}
+				}
+
+				data
+			},
+			Self::Rgb565 => {
+				let mut data = Vec::with_capacity(
+					self.encoded_len(image.width(), image.height()));
+
+				for pixel in image.pixels() {
+					let luma = pixel.0[0];
+					let color = rgb565([luma, luma, luma]);
+
+					data.extend_from_slice(&color.to_le_bytes());
+				}
+
+				data
+			}
+		}
+	}
+}
+
+/// Packs a 24-bit RGB color into the `0x00RRGGBB` layout used by the Wacom STU
+/// API for its 24-bit colors.
+fn pack_color24([r, g, b]: [u8; 3]) -> u32 {
+	u32::from(r) << 16 | u32::from(g) << 8 | u32::from(b)
+}
 
-			let result = self.raw.dispatch(|interface| {
-				stu_sys::WacomGSS_Protocol_getCapability(
-					interface,
-					std::mem::size_of::<stu_sys::WacomGSS_Capability>() as _,
-					&mut capability)
-			});
-			InternalError::from_wacom_stu(result)
-				.map_err(InternalError::unwrap_to_general)?;
+/// Packs a 24-bit RGB color into a 16-bit RGB565 color.
+fn rgb565([r, g, b]: [u8; 3]) -> u16 {
+	(u16::from(r) >> 3) << 11 | (u16::from(g) >> 2) << 5 | (u16::from(b) >> 3)
+}
 
-			Handle::wrap(capability)
-		};
+/// Reduces the given image down to pure black and white pixels, using
+/// Floyd-Steinberg dithering to keep shades of gray recognizable.
+fn dither(mut image: image::GrayImage) -> image::GrayImage {
+	let width = image.width() as usize;
+	let height = image.height() as usize;
 
-		Ok(Capability {
-			display_width: u32::from(capability.screenWidth),
-			display_height: u32::from(capability.screenHeight),
-			input_width: u32::from(capability.tabletMaxX),
-			input_height: u32::from(capability.tabletMaxY),
-			input_depth: u32::from(capability.tabletMaxPressure)
-		})
-	}
+	/* Carry the errors of the current and of the next row around, rather than
+	 * writing them back into the image, where they would get clamped. */
+	let mut current = vec![0i32; width + 2];
+	let mut next = vec![0i32; width + 2];
 
-	/// Opens a queue with which to receive events from the tablet.
-	pub fn queue(&self) -> Result<Queue, Error> {
-		let caps = self.capability()?;
-		Queue::new(self, caps)
+	for y in 0..height {
+		for x in 0..width {
+			let pixel = image.get_pixel_mut(x as u32, y as u32);
+			let value = i32::from(pixel.0[0]) + current[x + 1] / 16;
+			let target = if value < 128 { 0 } else { 255 };
+			pixel.0[0] = target as u8;
+
+			let error = value - target;
+			current[x + 2] += error * 7;
+			next[x] += error * 3;
+			next[x + 1] += error * 5;
+			next[x + 2] += error;
+		}
+
+		std::mem::swap(&mut current, &mut next);
+		next.iter_mut().for_each(|error| *error = 0);
 	}
+
+	image
 }
 
 /// The set of capabilities reported by the device.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Capability {
 	/// Width of the display screen, in pixels.
 	display_width: u32,
@@ -155,6 +1086,12 @@ pub struct Capability {
 	input_height: u32,
 	/// The depth (of pressures) of the input polling grid.
 	input_depth: u32,
+	/// The number of cells of the input polling grid in an inch.
+	input_resolution: u32,
+	/// The image encodings supported by the display screen, as a combination
+	/// of Wacom STU API encoding flags.
+	#[cfg_attr(feature = "serde", serde(default))]
+	encoding_flags: u8,
 }
 impl Capability {
 	/// Width of the display screen, in pixels.
@@ -167,6 +1104,25 @@ impl Capability {
 		self.display_height
 	}
 
+	/// Whether the display screen is able to show colors.
+	///
+	/// Devices too old to report which encodings they support are assumed to
+	/// have monochrome screens.
+	pub fn supports_color(&self) -> bool {
+		let color = stu_sys::tagWacomGSS_EncodingFlag_WacomGSS_EncodingFlag_16bit as u8
+			| stu_sys::tagWacomGSS_EncodingFlag_WacomGSS_EncodingFlag_24bit as u8;
+		self.encoding_flags & color != 0
+	}
+
+	/// The encoding in which images are best sent to the display screen.
+	pub fn preferred_encoding(&self) -> ImageEncoding {
+		if self.supports_color() {
+			ImageEncoding::Rgb565
+		} else {
+			ImageEncoding::Monochrome
+		}
+	}
+
 	/// The width of the input grid.
 	///
 	/// The input grid is the grid whose cells are the smallest possible for
@@ -192,11 +1148,61 @@ impl Capability {
 	pub fn input_grid_pressure(&self) -> u32 {
 		self.input_depth
 	}
+
+	/// The physical width of the input grid, in millimeters.
+	///
+	/// This value is derived from the resolution of the input grid, and is
+	/// not available for devices that do not report it.
+	pub fn physical_width_mm(&self) -> Option<f64> {
+		self.input_grid_mm(self.input_width)
+	}
+
+	/// The physical height of the input grid, in millimeters.
+	///
+	/// This value is derived from the resolution of the input grid, and is
+	/// not available for devices that do not report it.
+	pub fn physical_height_mm(&self) -> Option<f64> {
+		self.input_grid_mm(self.input_height)
+	}
+
+	/// The density of pixels in the display screen, in dots per inch.
+	///
+	/// This value is derived from the physical width of the input grid, which
+	/// covers the whole of the screen, and is not available for devices that
+	/// do not report it.
+	pub fn dpi(&self) -> Option<f64> {
+		let width = self.physical_width_mm()?;
+		if width <= 0.0 { return None }
+
+		Some(f64::from(self.display_width) / (width / MILLIMETERS_PER_INCH))
+	}
+
+	/// Converts the given number of input grid cells into millimeters.
+	fn input_grid_mm(&self, cells: u32) -> Option<f64> {
+		if self.input_resolution == 0 { return None }
+
+		let inches = f64::from(cells) / f64::from(self.input_resolution);
+		Some(inches * MILLIMETERS_PER_INCH)
+	}
+}
+
+/// Converts a fixed-size, possibly unterminated, C string into a string.
+fn string_from_c_chars(chars: &[std::os::raw::c_char]) -> String {
+	let bytes = chars.iter()
+		.map(|c| *c as u8)
+		.take_while(|c| *c != 0)
+		.collect::<Vec<_>>();
+
+	String::from_utf8_lossy(&bytes[..]).trim().to_string()
 }
 
+/// The number of millimeters in an inch.
+const MILLIMETERS_PER_INCH: f64 = 25.4;
+
 /// A wrapper around a a handle to an interface.
 struct RawTabletConnection {
-	interface: Mutex<stu_sys::WacomGSS_Interface>,
+	/// The handle to the interface, which simulated devices go without.
+	interface: Mutex<Option<stu_sys::WacomGSS_Interface>>,
 }
 impl RawTabletConnection {
 	/// Dispatch the given functor with a raw handle to the interface.
@@ -206,38 +1212,229 @@ impl RawTabletConnection {
 		where F: FnOnce(stu_sys::WacomGSS_Interface) -> T {
 
 		let interface = self.interface.lock().unwrap();
-		fun(*interface)
+		let interface = interface.expect(
+			"Tried to call into the Wacom STU API on behalf of a simulated \
+			device. Simulated devices support no reports, and calls on their \
+			behalf must be checked against the supported reports first");
+		fun(interface)
+	}
+
+	/// Whether this connection belongs to a simulated device, in which case it
+	/// has no interface.
+	fn is_simulated(&self) -> bool {
+		self.interface.lock().unwrap().is_none()
+	}
+
+	/// Checks whether the interface is still connected to the device.
+	fn connected(&self) -> Result<bool, Error> {
+		if self.is_simulated() {
+			return Ok(true)
+		}
+
+		let mut connected = 0;
+		let result = self.dispatch(|interface| unsafe {
+			stu_sys::WacomGSS_Interface_isConnected(
+				interface,
+				&mut connected)
+		});
+		InternalError::from_wacom_stu(result)
+			.map_err(InternalError::unwrap_to_general)?;
+
+		Ok(connected != 0)
+	}
+
+	/// Disconnects the interface from the device.
+	///
+	/// The interface itself is only freed once this structure is dropped, so
+	/// this may be called any number of times before then.
+	fn disconnect(&self) -> Result<(), Error> {
+		if self.is_simulated() {
+			return Ok(())
+		}
+
+		let result = self.dispatch(|interface| unsafe {
+			stu_sys::WacomGSS_Interface_disconnect(interface)
+		});
+		InternalError::from_wacom_stu(result)
+			.map_err(InternalError::unwrap_to_general)
 	}
 }
 impl Drop for RawTabletConnection {
 	fn drop(&mut self) {
-		self.dispatch(|interface| unsafe {
-			let _ = stu_sys::WacomGSS_Interface_disconnect(interface);
-			let _ = stu_sys::WacomGSS_Interface_free(interface);
-		});
+		if let Some(interface) = *self.interface.get_mut().unwrap() {
+			unsafe {
+				let _ = stu_sys::WacomGSS_Interface_disconnect(interface);
+				let _ = stu_sys::WacomGSS_Interface_free(interface);
+			}
+		}
 	}
 }
 
 /// The structure containing information about a device.
-#[derive(Debug, Eq, PartialEq, Hash)]
+///
+/// Two values compare equal, and hash to the same value, when their vendor,
+/// product, revision, port and transport all match. This makes it usable as
+/// the key of a map that tracks devices across successive calls to
+/// [`list_devices()`], though two attached units of the same model on USB
+/// remain indistinguishable.
+///
+/// [`list_devices()`]: list_devices
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct Information {
-	id_vendor: u16,
-	id_product: u16,
-	bcd_device: u16,
+	id_vendor: Option<u16>,
+	id_product: Option<u16>,
+	bcd_device: Option<u16>,
+	port: Option<String>,
+	transport: Transport,
 }
 impl Information {
 	/// Vendor identification number of this device.
-	pub fn vendor(&self) -> u16 {
+	///
+	/// Devices attached through a serial port have no such number.
+	pub fn vendor(&self) -> Option<u16> {
 		self.id_vendor
 	}
 
 	/// Product identification number of this device.
-	pub fn product(&self) -> u16 {
+	///
+	/// Devices attached through a serial port have no such number.
+	pub fn product(&self) -> Option<u16> {
 		self.id_product
 	}
 
-	/// The device identification number.
-	pub fn device(&self) -> u16 { self.bcd_device }
+	/// The human-readable name of the model of this device.
+	///
+	/// The name is derived from the product identification number of the
+	/// device, and is a generic placeholder for products that are not known.
+	pub fn device(&self) -> &'static str {
+		#[cfg(feature = "simulated")]
+		{
+			if self.transport == Transport::Simulated {
+				return "Simulated STU"
+			}
+		}
+
+		let (vendor, product) = match (self.id_vendor, self.id_product) {
+			(Some(vendor), Some(product)) => (vendor, product),
+			_ => return "Wacom STU"
+		};
+		if vendor != WACOM_VENDOR_ID {
+			return "Unknown Device"
+		}
+
+		match product {
+			0x00a1 => "STU-500",
+			0x00a2 => "STU-300",
+			0x00a3 => "STU-520",
+			0x00a4 => "STU-430",
+			0x00a5 => "STU-530",
+			0x00a6 => "STU-430V",
+			0x00a8 => "STU-540",
+			0x00a9 => "STU-541",
+			_ => "Wacom STU"
+		}
+	}
+
+	/// The greatest brightness level of the backlight of the screen, for the
+	/// models of devices it is known for.
+	fn max_backlight(&self) -> Option<u8> {
+		if self.id_vendor != Some(WACOM_VENDOR_ID) {
+			return None
+		}
+
+		match self.id_product? {
+			0x00a5 | 0x00a8 | 0x00a9 => Some(3),
+			_ => None
+		}
+	}
+
+	/// The release number of the device, in binary-coded decimal.
+	///
+	/// Devices attached through a serial port have no such number.
+	pub fn revision(&self) -> Option<u16> { self.bcd_device }
+
+	/// The name of the serial port the device is attached to, if any.
+	pub fn port(&self) -> Option<&str> {
+		self.port.as_deref()
+	}
+
+	/// The transport through which the device is attached to the system.
+	pub fn transport(&self) -> Transport {
+		self.transport
+	}
+}
+impl std::fmt::Display for Information {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.device())?;
+
+		if let (Some(vendor), Some(product)) = (self.id_vendor, self.id_product) {
+			write!(f, " - {:04x}:{:04x}", vendor, product)?;
+		}
+		if let Some(port) = &self.port {
+			write!(f, " - {}", port)?;
+		}
+
+		Ok(())
+	}
+}
+
+/// The USB vendor identification number assigned to Wacom.
+const WACOM_VENDOR_ID: u16 = 0x056a;
+
+/// The baud rate used by default by the serial models of the tablets.
+pub const DEFAULT_SERIAL_BAUD_RATE: u32 = 128000;
+
+/// The modes in which a device may report pen data.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum PenDataMode {
+	/// Pen data carries only the position, pressure and touch state of the pen.
+	None,
+	/// Pen data also carries the time at which it was sampled.
+	TimeCount,
+	/// Pen data also carries a sequence number, with which lost reports can be
+	/// detected.
+	SequenceNumber,
+	/// Pen data carries both the time at which it was sampled and a sequence
+	/// number.
+	TimeCountSequence,
+}
+impl PenDataMode {
+	/// The modes and their raw values.
+	const RAW: [(Self, stu_sys::tagWacomGSS_PenDataOptionMode); 4] = [
+		(Self::None, stu_sys::tagWacomGSS_PenDataOptionMode_WacomGSS_PenDataOptionMode_None),
+		(Self::TimeCount, stu_sys::tagWacomGSS_PenDataOptionMode_WacomGSS_PenDataOptionMode_TimeCount),
+		(Self::SequenceNumber, stu_sys::tagWacomGSS_PenDataOptionMode_WacomGSS_PenDataOptionMode_SequenceNumber),
+		(Self::TimeCountSequence, stu_sys::tagWacomGSS_PenDataOptionMode_WacomGSS_PenDataOptionMode_TimeCountSequence),
+	];
+
+	/// Wraps the given raw mode, if it is one we know of.
+	fn from_raw(raw: u32) -> Option<Self> {
+		Self::RAW.iter()
+			.find(|(_, mode)| *mode as u32 == raw)
+			.map(|(mode, _)| *mode)
+	}
+
+	/// The raw value of this mode.
+	fn raw(&self) -> stu_sys::tagWacomGSS_PenDataOptionMode {
+		Self::RAW.iter()
+			.find(|(mode, _)| mode == self)
+			.map(|(_, raw)| *raw)
+			.unwrap()
+	}
+}
+
+/// The means through which a device is attached to the system.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Transport {
+	/// The device is attached through USB.
+	Usb,
+	/// The device is attached through a serial port. This includes devices
+	/// attached through Bluetooth, which are exposed as serial ports.
+	Serial,
+	/// The device is not attached to the system at all, and is instead
+	/// simulated by this crate. Only available with the `simulated` feature.
+	#[cfg(feature = "simulated")]
+	Simulated,
 }
 
 /// A connector to a tablet device.
@@ -247,36 +1444,214 @@ impl Information {
 /// the system. This structure also provides a means to identify the device
 /// before a connection is established.
 pub struct Connector {
-	device: stu_sys::WacomGSS_UsbDevice,
+	device: ConnectorDevice,
 }
 impl Connector {
+	/// Create a connector to a device attached to the given serial port.
+	///
+	/// Devices attached through serial ports that the Wacom STU API is able to
+	/// discover are already listed by the [`list_devices()`] function. This
+	/// function is for the ones it can't, with connectors to them being created
+	/// from the name of the port they are attached to, such as `COM3` or
+	/// `/dev/ttyS0`.
+	///
+	/// [`list_devices()`]: list_devices
+	pub fn serial(port: &str, baud_rate: u32) -> Self {
+		Self {
+			device: ConnectorDevice::Serial {
+				port: port.to_string(),
+				baud_rate
+			}
+		}
+	}
+
 	/// Get the information about the device this connector is targeting.
 	pub fn info(&self) -> Information {
-		Information {
-			id_vendor: self.device.usbDevice.idVendor,
-			id_product: self.device.usbDevice.idProduct,
-			bcd_device: self.device.usbDevice.bcdDevice
-		}
+		self.device.info()
+	}
+
+	/// The transport through which the device this connector is targeting is
+	/// attached to the system.
+	pub fn transport(&self) -> Transport {
+		self.device.info().transport()
 	}
 
 	/// Try to connect to the device this connector is targeting.
+	///
+	/// This makes a single attempt at connecting to the device. For a version
+	/// of this function that keeps trying for a while, see [`connect_with()`].
+	///
+	/// [`connect_with()`]: Self::connect_with
 	pub fn connect(self) -> Result<Tablet, Error> {
-		let interface = unsafe {
-			let mut interface = std::mem::zeroed();
-			InternalError::from_wacom_stu({
-				stu_sys::WacomGSS_UsbInterface_create_1(
-					std::mem::size_of::<stu_sys::WacomGSS_UsbDevice>() as _,
-					&self.device,
-					true as _,
-					&mut interface)
-			}).map_err(InternalError::unwrap_to_general)?;
-
-			interface
+		self.connect_with(ConnectOptions::once())
+	}
+
+	/// Try to connect to the device this connector is targeting, retrying
+	/// failed attempts for as long as the given options allow.
+	///
+	/// Devices that were just attached to the system are often not ready to be
+	/// opened for a short while, as the system is still setting them up. Only
+	/// failures reported by the Wacom STU API are retried, and the error of the
+	/// last attempt is the one returned once the timeout runs out.
+	pub fn connect_with(self, options: ConnectOptions) -> Result<Tablet, Error> {
+		let interface = retry(
+			options,
+			|| self.device.open(),
+			|what| matches!(what, Error::ApiError(_)))?;
+		Tablet::wrap(
+			RawTabletConnection {
+				interface: Mutex::new(interface)
+			},
+			self.device)
+	}
+}
+
+/// Makes attempts at an operation for as long as the given options allow,
+/// until one of them either succeeds or fails with an error that is not worth
+/// retrying.
+fn retry<T, E, A, R>(options: ConnectOptions, mut attempt: A, retryable: R) -> Result<T, E>
+	where A: FnMut() -> Result<T, E>,
+		R: Fn(&E) -> bool {
+
+	let interval = options.retry_interval.max(ConnectOptions::MIN_RETRY_INTERVAL);
+	let deadline = std::time::Instant::now() + options.timeout;
+	loop {
+		match attempt() {
+			Ok(value) => return Ok(value),
+			Err(what) if retryable(&what) => {
+				if std::time::Instant::now() + interval > deadline {
+					return Err(what)
+				}
+				std::thread::sleep(interval);
+			},
+			Err(what) => return Err(what)
+		}
+	}
+}
+
+/// The options controlling how a [connector] tries to connect to its device.
+///
+/// [connector]: Connector
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ConnectOptions {
+	/// The total amount of time spent trying to connect to the device before
+	/// giving up.
+	pub timeout: std::time::Duration,
+	/// The amount of time to wait after a failed attempt before trying again.
+	///
+	/// Intervals shorter than [`MIN_RETRY_INTERVAL`] are waited for as if they
+	/// were that long, so that retrying never spins in a tight loop.
+	///
+	/// [`MIN_RETRY_INTERVAL`]: Self::MIN_RETRY_INTERVAL
+	pub retry_interval: std::time::Duration,
+}
+impl ConnectOptions {
+	/// The shortest amount of time waited for between two attempts.
+	pub const MIN_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+
+	/// Options that make a single attempt at connecting, with no retries.
+	pub fn once() -> Self {
+		Self {
+			timeout: std::time::Duration::ZERO,
+			retry_interval: std::time::Duration::ZERO,
+		}
+	}
+}
+impl Default for ConnectOptions {
+	fn default() -> Self {
+		Self {
+			timeout: std::time::Duration::from_secs(5),
+			retry_interval: std::time::Duration::from_millis(250),
+		}
+	}
+}
+
+/// The description of the device a [connector] is targeting.
+///
+/// [connector]: Connector
+#[derive(Clone)]
+pub(crate) enum ConnectorDevice {
+	/// A device attached through USB.
+	Usb(stu_sys::WacomGSS_UsbDevice),
+	/// A device attached through a serial port.
+	Serial {
+		/// The name of the port the device is attached to.
+		port: String,
+		/// The baud rate with which to communicate with the device.
+		baud_rate: u32,
+	},
+	/// A device simulated by this crate, which is not attached to the system.
+	#[cfg(feature = "simulated")]
+	Simulated,
+}
+impl ConnectorDevice {
+	/// Get the information about this device.
+	fn info(&self) -> Information {
+		match self {
+			ConnectorDevice::Usb(device) => Information {
+				id_vendor: Some(device.usbDevice.idVendor),
+				id_product: Some(device.usbDevice.idProduct),
+				bcd_device: Some(device.usbDevice.bcdDevice),
+				port: None,
+				transport: Transport::Usb
+			},
+			ConnectorDevice::Serial { port, .. } => Information {
+				id_vendor: None,
+				id_product: None,
+				bcd_device: None,
+				port: Some(port.clone()),
+				transport: Transport::Serial
+			},
+			#[cfg(feature = "simulated")]
+			ConnectorDevice::Simulated => Information {
+				id_vendor: None,
+				id_product: None,
+				bcd_device: None,
+				port: None,
+				transport: Transport::Simulated
+			}
+		}
+	}
+
+	/// Creates a new interface connected to this device.
+	///
+	/// Simulated devices have no interface, and always succeed with `None`.
+	fn open(&self) -> Result<Option<stu_sys::WacomGSS_Interface>, Error> {
+		let interface = match self {
+			ConnectorDevice::Usb(device) => unsafe {
+				let mut interface = std::mem::zeroed();
+				InternalError::from_wacom_stu({
+					stu_sys::WacomGSS_UsbInterface_create_1(
+						std::mem::size_of::<stu_sys::WacomGSS_UsbDevice>() as _,
+						device,
+						true as _,
+						&mut interface)
+				}).map_err(InternalError::unwrap_to_general)?;
+
+				interface
+			},
+			ConnectorDevice::Serial { port, baud_rate } => unsafe {
+				let name = std::ffi::CString::new(port.as_bytes())
+					.map_err(|_| Error::ClientError(ClientError::InvalidSerialPort {
+						port: port.clone()
+					}))?;
+
+				let mut interface = std::mem::zeroed();
+				InternalError::from_wacom_stu({
+					stu_sys::WacomGSS_SerialInterface_create(
+						name.as_ptr(),
+						*baud_rate,
+						true as _,
+						&mut interface)
+				}).map_err(InternalError::into_error)?;
+
+				interface
+			},
+			#[cfg(feature = "simulated")]
+			ConnectorDevice::Simulated => return Ok(None)
 		};
 
-		Tablet::wrap(RawTabletConnection {
-			interface: Mutex::new(interface)
-		})
+		Ok(Some(interface))
 	}
 }
 
@@ -288,28 +1663,35 @@ impl Connector {
 /// [connectors]: Connector
 /// [`list_devices()`]: list_devices
 pub struct Connectors {
-	values: Handle<[stu_sys::WacomGSS_UsbDevice]>,
-	index: usize,
+	devices: std::vec::IntoIter<ConnectorDevice>,
+}
+impl Connectors {
+	/// Whether there are no more connectors left in this iterator.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
 }
 impl Iterator for Connectors {
 	type Item = Connector;
 	fn next(&mut self) -> Option<Self::Item> {
-		let val = self.values.get(self.index)
-			.map(|device| Connector {
-				device: *device
-			});
-		self.index = self.index.saturating_add(1);
+		self.devices.next().map(|device| Connector { device })
+	}
 
-		val
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.devices.size_hint()
 	}
 }
+impl ExactSizeIterator for Connectors {}
 
 /// List all of the currently available devices.
 ///
-/// # Panic
-/// This function panics if USB devices are not supported by the system.
-pub fn list_devices() -> Connectors {
-	let devices = unsafe {
+/// Devices attached through USB are listed first, followed by the ones attached
+/// through serial ports and, with the `simulated` feature, by a simulated
+/// device. This function fails if USB devices are not supported
+/// by the system. Failing to discover the devices attached through serial ports
+/// only gets logged, since most devices are attached through USB.
+pub fn list_devices() -> Result<Connectors, Error> {
+	let usb = unsafe {
 		let mut count = 0;
 		let mut devices = std::ptr::null_mut();
 		InternalError::from_wacom_stu({
@@ -317,13 +1699,302 @@ pub fn list_devices() -> Connectors {
 				std::mem::size_of::<stu_sys::WacomGSS_UsbDevice>() as _,
 				&mut count,
 				&mut devices)
-		}).unwrap();
+		}).map_err(InternalError::into_error)?;
+
+		Handle::wrap_slice(devices, count as _)
+	};
+
+	let mut devices = usb.iter()
+		.map(|device| ConnectorDevice::Usb(*device))
+		.collect::<Vec<_>>();
+	match list_serial_devices() {
+		Ok(serial) => devices.extend(serial),
+		Err(what) => log::warn!("could not list serial devices: {}", what)
+	}
+	#[cfg(feature = "simulated")]
+	devices.push(ConnectorDevice::Simulated);
+
+	Ok(Connectors {
+		devices: devices.into_iter()
+	})
+}
+
+/// List the devices attached through serial ports that the Wacom STU API is
+/// able to discover.
+fn list_serial_devices() -> Result<Vec<ConnectorDevice>, Error> {
+	let devices = unsafe {
+		let mut count = 0;
+		let mut devices = std::ptr::null_mut();
+		InternalError::from_wacom_stu({
+			stu_sys::WacomGSS_getSerialDevices(
+				std::mem::size_of::<stu_sys::WacomGSS_SerialDevice>() as _,
+				&mut count,
+				&mut devices)
+		}).map_err(InternalError::into_error)?;
 
 		Handle::wrap_slice(devices, count as _)
 	};
 
-	Connectors {
-		values: devices,
-		index: 0
+	Ok(devices.iter()
+		.map(|device| ConnectorDevice::Serial {
+			port: string_from_c_chars(&device.fileName[..]),
+			baud_rate: DEFAULT_SERIAL_BAUD_RATE
+		})
+		.collect())
+}
+
+/// List the currently available devices whose information satisfies the given
+/// predicate.
+///
+/// This function fails if USB devices are not supported by the system.
+pub fn find_devices<P>(mut predicate: P)
+	-> Result<impl Iterator<Item = Connector>, Error>
+	where P: FnMut(&Information) -> bool {
+
+	Ok(list_devices()?.filter(move |connector| predicate(&connector.info())))
+}
+
+/// Find the first currently available device with the given vendor and product
+/// identification numbers, if there is one.
+///
+/// This function fails if USB devices are not supported by the system.
+pub fn find_device(vendor: u16, product: u16) -> Result<Option<Connector>, Error> {
+	let mut devices = find_devices(|info| {
+		info.vendor() == Some(vendor) && info.product() == Some(product)
+	})?;
+	Ok(devices.next())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::{Duration, Instant};
+
+	/// Options that allow for plenty of quick retries.
+	const PATIENT: ConnectOptions = ConnectOptions {
+		timeout: Duration::from_secs(5),
+		retry_interval: Duration::from_millis(1),
+	};
+
+	#[test]
+	fn retry_succeeds_after_failing_a_few_times() {
+		let mut attempts = 0;
+		let result = retry(PATIENT, || {
+			attempts += 1;
+			if attempts <= 3 { Err(attempts) } else { Ok("connected") }
+		}, |_| true);
+
+		assert_eq!(result, Ok("connected"));
+		assert_eq!(attempts, 4);
+	}
+
+	#[test]
+	fn retry_gives_up_on_errors_not_worth_retrying() {
+		let mut attempts = 0;
+		let result: Result<(), _> = retry(PATIENT, || {
+			attempts += 1;
+			Err(attempts)
+		}, |what| *what < 2);
+
+		assert_eq!(result, Err(2));
+		assert_eq!(attempts, 2);
+	}
+
+	#[test]
+	fn retry_returns_the_last_error_once_the_timeout_runs_out() {
+		let options = ConnectOptions {
+			timeout: Duration::from_millis(50),
+			retry_interval: Duration::ZERO,
+		};
+
+		let start = Instant::now();
+		let mut attempts = 0u32;
+		let result: Result<(), _> = retry(options, || {
+			attempts += 1;
+			Err(attempts)
+		}, |_| true);
+
+		assert_eq!(result, Err(attempts));
+		assert!(start.elapsed() < Duration::from_secs(1));
+
+		/* A zero interval still waits between attempts, rather than spinning. */
+		let most = options.timeout.as_millis() / ConnectOptions::MIN_RETRY_INTERVAL.as_millis() + 1;
+		assert!(u128::from(attempts) <= most, "{} attempts were made", attempts);
+	}
+
+	#[test]
+	fn retry_makes_a_single_attempt_once() {
+		let mut attempts = 0;
+		let result: Result<(), _> = retry(ConnectOptions::once(), || {
+			attempts += 1;
+			Err(())
+		}, |_| true);
+
+		assert_eq!(result, Err(()));
+		assert_eq!(attempts, 1);
+	}
+	/// The capabilities of a device with a screen of 800 by 480 pixels.
+	const SCREEN: Capability = Capability {
+		display_width: 800,
+		display_height: 480,
+		input_width: 9600,
+		input_height: 6000,
+		input_depth: 1023,
+		input_resolution: 2540,
+		encoding_flags: 0,
+	};
+
+	#[test]
+	fn intersect_finds_the_overlap() {
+		let a = Rect::new(10, 10, 100, 50);
+		let b = Rect::new(60, 40, 100, 100);
+
+		assert_eq!(a.intersect(&b), Some(Rect::new(60, 40, 50, 20)));
+		assert_eq!(b.intersect(&a), a.intersect(&b));
+		assert_eq!(a.intersect(&a), Some(a));
+	}
+
+	#[test]
+	fn intersect_rejects_regions_that_only_touch() {
+		let a = Rect::new(0, 0, 10, 10);
+
+		assert_eq!(a.intersect(&Rect::new(10, 0, 10, 10)), None);
+		assert_eq!(a.intersect(&Rect::new(0, 10, 10, 10)), None);
+		assert_eq!(a.intersect(&Rect::new(2, 2, 0, 5)), None);
+	}
+
+	#[test]
+	fn intersect_handles_regions_at_the_end_of_the_range() {
+		let a = Rect::new(u16::MAX, u16::MAX, u16::MAX, u16::MAX);
+		let b = Rect::new(0, 0, u16::MAX, u16::MAX);
+
+		assert_eq!(a.intersect(&a), Some(a));
+		assert_eq!(a.intersect(&b), None);
+	}
+
+	#[test]
+	fn clamp_to_shrinks_regions_into_the_screen() {
+		assert_eq!(
+			Rect::new(700, 400, 200, 200).clamp_to(&SCREEN),
+			Rect::new(700, 400, 100, 80));
+		assert_eq!(
+			Rect::new(10, 20, 30, 40).clamp_to(&SCREEN),
+			Rect::new(10, 20, 30, 40));
+		assert_eq!(Rect::full(&SCREEN), Rect::new(0, 0, 800, 480));
+	}
+
+	#[test]
+	fn clamp_to_empties_regions_outside_of_the_screen() {
+		let clamped = Rect::new(900, 100, 50, 50).clamp_to(&SCREEN);
+
+		assert!(clamped.is_empty());
+		assert_eq!((clamped.x, clamped.y), (800, 100));
+	}
+
+	#[test]
+	fn regions_past_the_screen_do_not_fit() {
+		assert!(Rect::full(&SCREEN).fits_within(800, 480));
+		assert!(Rect::new(799, 479, 1, 1).fits_within(800, 480));
+		assert!(!Rect::new(799, 0, 2, 1).fits_within(800, 480));
+		assert!(!Rect::new(0, 400, 1, 81).fits_within(800, 480));
+		assert!(!Rect::new(800, 480, 0, 0).fits_within(799, 479));
+	}
+
+	#[test]
+	fn regions_wrapping_around_do_not_fit() {
+		/* These would wrap back around to zero in 16 bits. */
+		assert!(!Rect::new(u16::MAX, 0, 2, 1).fits_within(800, 480));
+		assert!(!Rect::new(0, u16::MAX, 1, u16::MAX).fits_within(800, 480));
+		assert!(!Rect::new(u16::MAX, 0, u16::MAX, 1).fits_within(1 << 16, 1));
+	}
+
+	#[test]
+	fn handwriting_areas_must_be_in_the_screen_and_not_empty() {
+		let valid = |region: Rect| region.fits_within(800, 480) && !region.is_empty();
+
+		assert!(valid(Rect::new(100, 100, 600, 280)));
+		assert!(valid(Rect::full(&SCREEN)));
+		assert!(!valid(Rect::new(100, 100, 0, 280)));
+		assert!(!valid(Rect::new(100, 100, 600, 0)));
+		assert!(!valid(Rect::new(100, 100, 701, 280)));
+		assert!(!valid(Rect::new(u16::MAX, 100, 2, 280)));
+	}
+	#[test]
+	fn rgb565_keeps_the_high_bits_of_each_channel() {
+		assert_eq!(rgb565([0, 0, 0]), 0x0000);
+		assert_eq!(rgb565([255, 255, 255]), 0xffff);
+		assert_eq!(rgb565([255, 0, 0]), 0xf800);
+		assert_eq!(rgb565([0, 255, 0]), 0x07e0);
+		assert_eq!(rgb565([0, 0, 255]), 0x001f);
+		assert_eq!(rgb565([0x12, 0x34, 0x56]), 0x11aa);
+	}
+
+	#[test]
+	fn dither_leaves_only_black_and_white() {
+		let image = image::GrayImage::from_fn(37, 23, |x, y|
+			image::Luma([(x * 7 + y * 3) as u8]));
+		let dithered = dither(image);
+
+		assert_eq!(dithered.dimensions(), (37, 23));
+		assert!(dithered.pixels().all(|pixel| pixel.0 == [0] || pixel.0 == [255]));
+		assert!(dithered.pixels().any(|pixel| pixel.0 == [0]));
+		assert!(dithered.pixels().any(|pixel| pixel.0 == [255]));
+	}
+
+	#[test]
+	fn encoded_images_have_the_expected_length() {
+		let image = image::DynamicImage::ImageRgb8(
+			image::RgbImage::from_fn(37, 23, |x, y|
+				image::Rgb([x as u8, y as u8, (x + y) as u8])));
+
+		for &encoding in [ImageEncoding::Monochrome, ImageEncoding::Rgb565].iter() {
+			assert_eq!(
+				encoding.encode_dynamic(&image).len(),
+				encoding.encoded_len(37, 23),
+				"{:?}", encoding);
+		}
+	}
+
+	#[test]
+	fn colors_are_packed_as_rgb() {
+		assert_eq!(pack_color24([0x12, 0x34, 0x56]), 0x123456);
+		assert_eq!(pack_color24([0xff, 0xff, 0xff]), 0xffffff);
+	}
+
+	#[test]
+	fn unsupported_reports_are_not_errors_when_trying() {
+		let unsupported = Err(Error::ClientError(ClientError::UnsupportedReportId {
+			report_id: ReportId::ClearScreen
+		}));
+		assert!(unsupported_as_ok(unsupported).is_ok());
+		assert!(unsupported_as_ok(Ok(())).is_ok());
+
+		let invalid = Err(Error::ClientError(ClientError::InvalidReport));
+		assert!(matches!(
+			unsupported_as_ok(invalid),
+			Err(Error::ClientError(ClientError::InvalidReport))));
 	}
-}
\ No newline at end of file
+	/// Hashes the given value with the default hasher.
+	fn hash<T: std::hash::Hash>(value: &T) -> u64 {
+		use std::hash::Hasher;
+
+		let mut hasher = std::collections::hash_map::DefaultHasher::new();
+		value.hash(&mut hasher);
+		hasher.finish()
+	}
+
+	#[test]
+	fn information_on_the_same_device_is_equal() {
+		let serial = |port: &str| ConnectorDevice::Serial {
+			port: port.to_string(),
+			baud_rate: DEFAULT_SERIAL_BAUD_RATE
+		};
+
+		let a = serial("COM3").info();
+		let b = serial("COM3").info();
+		assert_eq!(a, b);
+		assert_eq!(hash(&a), hash(&b));
+
+		assert_ne!(a, serial("COM4").info());
+	}
+}
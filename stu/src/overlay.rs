@@ -0,0 +1,226 @@
+use crate::Capability;
+pub use rusttype::Font;
+
+/// The thickness of the baseline, in pixels.
+const BASELINE_THICKNESS: u32 = 2;
+
+/// The thickness of the strokes of the "X" marker, in pixels.
+const MARKER_THICKNESS: u32 = 2;
+
+/// The intensity of the pixels making up the guidance, where `0` is black.
+const INK: u8 = 0;
+
+/// The intensity of the background of the prompt, where `255` is white.
+const PAPER: u8 = 255;
+
+/// A prompt asking for a signature, to be shown on the screen of the device.
+///
+/// The prompt is made up of a baseline for the signature to sit on, an "X"
+/// marking where the signature should start and, optionally, a caption under
+/// the baseline, usually carrying the name of whoever is signing. It renders
+/// into an image with the same dimensions as the screen of the device, ready
+/// to be handed over to [`Tablet::write_image()`].
+///
+/// Since drawing the caption requires a font, this module is only available
+/// with the `overlay` feature enabled, and the font must be provided by the
+/// application.
+///
+/// [`Tablet::write_image()`]: crate::Tablet::write_image
+#[derive(Clone)]
+pub struct SignaturePrompt {
+	/// The dimensions of the screen the prompt is drawn for.
+	dimensions: (u32, u32),
+	/// The vertical position of the baseline, in pixels from the top.
+	baseline: u32,
+	/// The horizontal space left around the baseline, in pixels.
+	margin: u32,
+	/// Whether the "X" marker gets drawn.
+	marker: bool,
+	/// The caption drawn under the baseline, if any.
+	caption: Option<Caption>,
+}
+impl SignaturePrompt {
+	/// Creates a new prompt for the screen of a device with the given
+	/// capabilities, with the baseline at three quarters of the height of the
+	/// screen, a marker and no caption.
+	pub fn new(caps: &Capability) -> Self {
+		let (width, height) = (caps.width(), caps.height());
+		Self {
+			dimensions: (width, height),
+			baseline: height / 4 * 3,
+			margin: width / 10,
+			marker: true,
+			caption: None
+		}
+	}
+
+	/// Changes the vertical position of the baseline, in pixels from the top
+	/// of the screen.
+	pub fn baseline(mut self, y: u32) -> Self {
+		self.baseline = y;
+		self
+	}
+
+	/// Changes the horizontal space left between the ends of the baseline and
+	/// the edges of the screen, in pixels.
+	pub fn margin(mut self, margin: u32) -> Self {
+		self.margin = margin;
+		self
+	}
+
+	/// Changes whether the "X" marker gets drawn.
+	pub fn marker(mut self, marker: bool) -> Self {
+		self.marker = marker;
+		self
+	}
+
+	/// Draws the given text under the baseline, in the given font and at the
+	/// given height, in pixels.
+	pub fn caption(mut self, text: impl Into<String>, font: Font<'static>, size: f32) -> Self {
+		self.caption = Some(Caption { text: text.into(), font, size });
+		self
+	}
+
+	/// Draws this prompt into an image with the dimensions of the screen.
+	///
+	/// Anything that falls outside of the screen, such as a baseline placed
+	/// below its bottom edge, is cut off.
+	pub fn render(&self) -> image::GrayImage {
+		let (width, height) = self.dimensions;
+		let mut image = image::GrayImage::from_pixel(width, height, image::Luma([PAPER]));
+
+		let left = self.margin.min(width);
+		let right = width.saturating_sub(self.margin).max(left);
+
+		/* The baseline. */
+		for y in self.baseline..self.baseline.saturating_add(BASELINE_THICKNESS) {
+			for x in left..right {
+				put(&mut image, x, y, INK);
+			}
+		}
+
+		/* The marker, sitting on the baseline at its start. */
+		if self.marker {
+			let size = (height / 8).max(1);
+			let top = self.baseline.saturating_sub(size + BASELINE_THICKNESS * 2);
+			for i in 0..size {
+				for t in 0..MARKER_THICKNESS {
+					put(&mut image, left + i + t, top + i, INK);
+					put(&mut image, left + size - 1 - i + t, top + i, INK);
+				}
+			}
+		}
+
+		/* The caption, hanging under the baseline. */
+		if let Some(caption) = &self.caption {
+			let scale = rusttype::Scale::uniform(caption.size);
+			let ascent = caption.font.v_metrics(scale).ascent;
+			let origin = rusttype::point(
+				left as f32,
+				self.baseline.saturating_add(BASELINE_THICKNESS * 2) as f32 + ascent);
+
+			for glyph in caption.font.layout(&caption.text, scale, origin) {
+				let bounds = match glyph.pixel_bounding_box() {
+					Some(bounds) => bounds,
+					None => continue
+				};
+
+				glyph.draw(|x, y, coverage| {
+					let x = bounds.min.x + x as i32;
+					let y = bounds.min.y + y as i32;
+					if x < 0 || y < 0 { return }
+
+					/* Blend the glyph in, so that its edges stay smooth. */
+					let coverage = coverage.clamp(0.0, 1.0);
+					let value = f32::from(PAPER) * (1.0 - coverage) + f32::from(INK) * coverage;
+					put_darker(&mut image, x as u32, y as u32, value.round() as u8);
+				});
+			}
+		}
+
+		image
+	}
+}
+impl std::fmt::Debug for SignaturePrompt {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("SignaturePrompt")
+			.field("dimensions", &self.dimensions)
+			.field("baseline", &self.baseline)
+			.field("margin", &self.margin)
+			.field("marker", &self.marker)
+			.field("caption", &self.caption.as_ref().map(|caption| &caption.text))
+			.finish()
+	}
+}
+
+/// A line of text drawn under the baseline of a [prompt].
+///
+/// [prompt]: SignaturePrompt
+#[derive(Clone)]
+struct Caption {
+	/// The text of the caption.
+	text: String,
+	/// The font the caption is drawn in.
+	font: Font<'static>,
+	/// The height of the caption, in pixels.
+	size: f32,
+}
+
+/// Paints the given pixel, if it lies within the image.
+fn put(image: &mut image::GrayImage, x: u32, y: u32, value: u8) {
+	if x < image.width() && y < image.height() {
+		image.put_pixel(x, y, image::Luma([value]));
+	}
+}
+
+/// Paints the given pixel, if it lies within the image, unless it is already
+/// darker than the given value.
+fn put_darker(image: &mut image::GrayImage, x: u32, y: u32, value: u8) {
+	if x < image.width() && y < image.height() {
+		let pixel = image.get_pixel_mut(x, y);
+		pixel.0[0] = pixel.0[0].min(value);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// The capabilities of a device with a screen of 800 by 480 pixels.
+	const SCREEN: Capability = Capability {
+		display_width: 800,
+		display_height: 480,
+		input_width: 9600,
+		input_height: 6000,
+		input_depth: 1023,
+		input_resolution: 2540,
+		encoding_flags: 0,
+	};
+
+	#[test]
+	fn baseline_is_drawn_between_the_margins() {
+		let image = SignaturePrompt::new(&SCREEN)
+			.baseline(300)
+			.margin(50)
+			.marker(false)
+			.render();
+		assert_eq!(image.dimensions(), (800, 480));
+
+		for (x, y, pixel) in image.enumerate_pixels() {
+			let on_baseline = (300..300 + BASELINE_THICKNESS).contains(&y)
+				&& (50..750).contains(&x);
+			let expected = if on_baseline { INK } else { PAPER };
+
+			assert_eq!(pixel.0[0], expected, "pixel at {}, {}", x, y);
+		}
+	}
+
+	#[test]
+	fn baselines_past_the_screen_are_cut_off() {
+		let image = SignaturePrompt::new(&SCREEN)
+			.baseline(u32::MAX - 1)
+			.render();
+
+		assert!(image.pixels().all(|pixel| pixel.0[0] == PAPER));
+	}
+}
@@ -2,8 +2,9 @@ use crate::{Tablet, Error, Capability, RawTabletConnection};
 use crate::error::{InternalError, ClientError};
 use crate::handle::Handle;
 use std::collections::VecDeque;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 /// An input event coming from a tablet device.
 ///
@@ -24,10 +25,22 @@ pub struct Event {
 	/// that a value of `0.0` is the minimum allowed value and `1.0` is the
 	/// maximum allowed value for each coordinate.
 	position: (f64, f64, f64),
+	/// The position and pressure of the pen on the screen as an X,Y,Z
+	/// coordinate tuple, exactly as reported by the device.
+	raw: (u16, u16, u16),
+	/// The resolution of the input grid of the device in each of the three
+	/// axes, against which the raw coordinates were normalized.
+	resolution: (u32, u32, u32),
 	/// Whether the pen is touching the surface.
 	touching: bool,
 	/// Whether the pen is in proximity of the surface.
 	close: bool,
+	/// The optional value reported along with the pen data, if any.
+	option: Option<u16>,
+	/// The time count reported along with the pen data, if any.
+	time_count: Option<u16>,
+	/// The sequence number reported along with the pen data, if any.
+	sequence: Option<u16>,
 }
 impl Event {
 	/// The point in time in which this event was generated.
@@ -68,6 +81,46 @@ impl Event {
 		self.position.2
 	}
 
+	/// The position of the pen in the horizontal axis when this event was
+	/// generated, in the raw units of the input grid of the device.
+	///
+	/// Unlike [`x()`], this value is not clamped, and may lie beyond the
+	/// resolution of the input grid if the device is poorly calibrated.
+	///
+	/// [`x()`]: Self::x
+	pub fn raw_x(&self) -> u16 {
+		self.raw.0
+	}
+
+	/// The position of the pen in the vertical axis when this event was
+	/// generated, in the raw units of the input grid of the device.
+	///
+	/// Unlike [`y()`], this value is not clamped, and may lie beyond the
+	/// resolution of the input grid if the device is poorly calibrated.
+	///
+	/// [`y()`]: Self::y
+	pub fn raw_y(&self) -> u16 {
+		self.raw.1
+	}
+
+	/// The pressure being applied to the screen with the pen when this event
+	/// was generated, in the raw units of the device.
+	///
+	/// Unlike [`pressure()`], this value is not clamped, and may lie beyond
+	/// the maximum pressure of the device if it is poorly calibrated.
+	///
+	/// [`pressure()`]: Self::pressure
+	pub fn raw_pressure(&self) -> u16 {
+		self.raw.2
+	}
+
+	/// The resolution of the input grid of the device in the horizontal,
+	/// vertical and pressure axes, against which the raw coordinates of this
+	/// event get normalized.
+	pub fn raw_resolution(&self) -> (u32, u32, u32) {
+		self.resolution
+	}
+
 	/// Whether the pen was touching the screen when this event was generated.
 	pub fn touching(&self) -> bool {
 		self.touching
@@ -78,18 +131,251 @@ impl Event {
 	pub fn hovering(&self) -> bool {
 		self.close
 	}
+
+	/// The optional value reported by the device along with the pen data when
+	/// this event was generated.
+	///
+	/// This value is only reported by devices that support the pen data option
+	/// reports, such as the STU-430 and the STU-530, while they are configured
+	/// to send them. Its meaning depends on how the device was configured, but
+	/// it is usually a counter that can be used to tell whether any reports
+	/// were lost along the way. On all other devices, this is [`None`].
+	pub fn option(&self) -> Option<u16> {
+		self.option
+	}
+
+	/// The time count reported by the device along with the pen data when
+	/// this event was generated, in milliseconds.
+	///
+	/// Unlike [`time()`], this is the time at which the device generated the
+	/// report, as measured by its own clock, which wraps around. This value is
+	/// only reported by devices that support the time count and sequence pen
+	/// data reports, such as the STU-540 and the STU-541, while they are
+	/// configured to send them. On all other devices, this is [`None`].
+	///
+	/// [`time()`]: Self::time
+	pub fn time_count(&self) -> Option<u16> {
+		self.time_count
+	}
+
+	/// The sequence number reported by the device along with the pen data
+	/// when this event was generated.
+	///
+	/// Sequence numbers go up by one with every report, wrapping around, and
+	/// are used by the queue to detect reports that got lost along the way.
+	/// They are available on the same devices as [`time_count()`], and are
+	/// [`None`] on all other devices.
+	///
+	/// [`time_count()`]: Self::time_count
+	pub fn sequence(&self) -> Option<u16> {
+		self.sequence
+	}
+
+	/// Converts this event into a form that can be stored, with its timestamp
+	/// recorded relative to the given point in time.
+	///
+	/// Events generated before the given point in time are recorded as having
+	/// happened exactly at it.
+	pub fn to_serializable(&self, epoch: Instant) -> SerializableEvent {
+		SerializableEvent {
+			offset: self.timestamp.saturating_duration_since(epoch),
+			position: self.position,
+			raw: self.raw,
+			resolution: self.resolution,
+			touching: self.touching,
+			close: self.close,
+			option: self.option,
+			time_count: self.time_count,
+			sequence: self.sequence
+		}
+	}
+
+	/// Converts a stored event back into an event, with its timestamp placed
+	/// relative to the given point in time.
+	pub fn from_serializable(event: &SerializableEvent, epoch: Instant) -> Self {
+		Self {
+			timestamp: epoch + event.offset,
+			position: event.position,
+			raw: event.raw,
+			resolution: event.resolution,
+			touching: event.touching,
+			close: event.close,
+			option: event.option,
+			time_count: event.time_count,
+			sequence: event.sequence
+		}
+	}
+}
+
+/// An [event] in a form that can be stored and loaded back.
+///
+/// Since points in time cannot be meaningfully stored, the timestamps of these
+/// events are instead kept as offsets from some point in time picked by whoever
+/// creates them, usually the time of the first event in a recording. All of
+/// the other data in the event is kept exactly as it was.
+///
+/// With the `serde` feature enabled, this structure and [`Capability`] can be
+/// serialized. The names and types of their fields are considered part of the
+/// public interface of this crate, and will only change along with a change
+/// in its major version.
+///
+/// [event]: Event
+/// [`Capability`]: crate::Capability
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SerializableEvent {
+	/// The amount of time between the point of reference of the recording and
+	/// the point in time in which this event was generated.
+	pub offset: Duration,
+	/// The normalized position and pressure of the pen on the screen.
+	pub position: (f64, f64, f64),
+	/// The position and pressure of the pen on the screen, exactly as
+	/// reported by the device.
+	pub raw: (u16, u16, u16),
+	/// The resolution of the input grid of the device in each of the three
+	/// axes.
+	pub resolution: (u32, u32, u32),
+	/// Whether the pen is touching the surface.
+	pub touching: bool,
+	/// Whether the pen is in proximity of the surface.
+	pub close: bool,
+	/// The optional value reported along with the pen data, if any.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub option: Option<u16>,
+	/// The time count reported along with the pen data, if any.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub time_count: Option<u16>,
+	/// The sequence number reported along with the pen data, if any.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub sequence: Option<u16>,
+}
+
+/// The parameters of the smoothing applied to the position of the pen.
+///
+/// Pen data is jittery, especially while little pressure is being applied to
+/// the screen, which makes for ragged strokes. Smoothing runs the position of
+/// the pen through an exponential moving average, which only ever looks at
+/// past events, so it can be applied as events come in. Smoothing starts over
+/// whenever the pen touches or leaves the screen, so that the strokes still
+/// start and end where the pen actually did, and it never touches the
+/// pressure, the touch state or the raw coordinates of an event.
+///
+/// Since smoothing alters the data coming from the device, it is best kept to
+/// previews, rather than to the signatures that actually get stored.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Smoothing {
+	/// How much of each new position makes it into the smoothed position, in
+	/// the range from `0.0` (exclusive) to `1.0` (inclusive).
+	///
+	/// Lower values smooth the position out more, at the cost of having the
+	/// smoothed position lag further behind the pen. A value of `1.0` turns
+	/// smoothing off.
+	pub factor: f64,
+}
+impl Smoothing {
+	/// Smooths the given position, given the last smoothed position.
+	fn apply(&self, last: (f64, f64), position: (f64, f64)) -> (f64, f64) {
+		let factor = if self.factor > 0.0 { self.factor.min(1.0) } else { 1.0 };
+		(
+			last.0 + (position.0 - last.0) * factor,
+			last.1 + (position.1 - last.1) * factor,
+		)
+	}
+}
+impl Default for Smoothing {
+	fn default() -> Self {
+		Self { factor: 0.5 }
+	}
+}
+
+/// The correction applied to the position of the pen on a given device.
+///
+/// Some devices consistently report the pen a little off from where it really
+/// is, due to manufacturing tolerances. Calibration corrects for that by
+/// scaling the normalized position of the pen and then offsetting it, with the
+/// result being clamped back to the screen. Just like smoothing, it never
+/// touches the raw coordinates of an event.
+///
+/// With the `serde` feature enabled, calibrations can be stored away and
+/// restored along with whatever identifies the device they belong to.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Calibration {
+	/// The offset added to the scaled position of the pen, in each axis.
+	pub offset: (f64, f64),
+	/// The factor by which the position of the pen is scaled, in each axis.
+	pub scale: (f64, f64),
+}
+impl Calibration {
+	/// Corrects the given normalized position.
+	fn apply(&self, position: (f64, f64)) -> (f64, f64) {
+		(
+			(position.0 * self.scale.0 + self.offset.0).clamp(0.0, 1.0),
+			(position.1 * self.scale.1 + self.offset.1).clamp(0.0, 1.0),
+		)
+	}
+}
+impl Default for Calibration {
+	fn default() -> Self {
+		Self {
+			offset: (0.0, 0.0),
+			scale: (1.0, 1.0),
+		}
+	}
+}
+
+/// A press of one of the on-screen buttons of a tablet device.
+///
+/// These events are only generated by devices that support a dedicated
+/// signature capture mode, such as the STU-540 and STU-541, and only while the
+/// device is operating in that mode. The pen data generated while in that mode
+/// is still delivered as regular [events].
+///
+/// [events]: Event
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ButtonEvent {
+	/// The point in time in which this event was processed by the queue.
+	timestamp: Instant,
+	/// The value of the key that was pressed.
+	key: u8,
+}
+impl ButtonEvent {
+	/// The point in time in which this event was generated.
+	///
+	/// Just like with pen [events], this is the time at which this event was
+	/// processed by the queue, rather than exactly when it was generated.
+	///
+	/// [events]: Event
+	pub fn time(&self) -> Instant {
+		self.timestamp
+	}
+
+	/// The value of the button that was pressed, as configured in the device
+	/// when its signature capture mode was set up.
+	pub fn key(&self) -> u8 {
+		self.key
+	}
 }
 
 /// A report queue connected to a tablet device.
 pub struct Queue {
-	/// The device this queue is polling update data off of.
-	_device: Arc<RawTabletConnection>,
-	/// The queue backing this structure.
-	queue: RawQueue,
+	/// The source of the reports this queue reads events from.
+	source: Box<dyn ReportSource>,
 	/// The report handler used by this instance of the queue.
 	handler: ReportHandler,
+	/// The flag through which blocking receives get interrupted, if anyone
+	/// has asked for a way to interrupt them.
+	interrupt: Option<Arc<AtomicBool>>,
+	/// The number of reports that were dropped for having failed to parse.
+	malformed_reports: u64,
 }
 impl Queue {
+	/// The interval between checks for new reports and for interruptions in
+	/// [`recv()`], once this queue can be interrupted.
+	///
+	/// [`recv()`]: Self::recv
+	const INTERRUPTIBLE_POLL_INTERVAL: Duration = Duration::from_millis(2);
+
 	/// Creates a new queue for this tablet device.
 	pub(crate) fn new(device: &Tablet, caps: Capability) -> Result<Self, Error> {
 		let queue = RawQueue(unsafe {
@@ -105,25 +391,332 @@ impl Queue {
 
 			queue
 		});
-		let handler = ReportHandler {
-			resolution:	(
-				caps.input_grid_width(),
-				caps.input_grid_height(),
-				caps.input_grid_pressure()),
-			queue: Default::default()
+		let mut handler = ReportHandler::new((
+			caps.input_grid_width(),
+			caps.input_grid_height(),
+			caps.input_grid_pressure()));
+		handler.set_calibration(device.calibration());
+
+		Ok(Self::with_source(
+			Box::new(InterfaceQueue {
+				queue,
+				_device: device.raw.clone()
+			}),
+			handler))
+	}
+
+	/// Creates a new queue reading reports from the given source.
+	pub(crate) fn with_source(
+		source: Box<dyn ReportSource>,
+		handler: ReportHandler) -> Self {
+
+		Self {
+			source,
+			handler,
+			interrupt: None,
+			malformed_reports: 0
+		}
+	}
+
+	/// Changes the smoothing applied to the position of the pen in the events
+	/// coming out of this queue, or turns it off, with [`None`].
+	///
+	/// Smoothing is off by default, and only applies to the events that get
+	/// read from the device after this function is called.
+	pub fn set_smoothing(&mut self, smoothing: Option<Smoothing>) {
+		self.handler.set_smoothing(smoothing);
+	}
+
+	/// Changes the calibration applied to the position of the pen in the events
+	/// coming out of this queue.
+	///
+	/// Queues start out with the calibration of the tablet they were opened on,
+	/// as set by [`Tablet::set_calibration()`].
+	///
+	/// [`Tablet::set_calibration()`]: crate::Tablet::set_calibration
+	pub fn set_calibration(&mut self, calibration: Calibration) {
+		self.handler.set_calibration(calibration);
+	}
+
+	/// Changes the pressure the pen must be applying to the screen for it to be
+	/// considered as touching it.
+	///
+	/// Light brushes against the screen, such as by the palm of the hand, get
+	/// reported as touches with very little pressure, which show up as stray
+	/// dots in signatures. Events whose [pressure] is at or below the given
+	/// threshold are reported as not touching the screen, even if the device
+	/// says otherwise. The threshold is normalized just like the pressure, and
+	/// defaults to `0.0`, which leaves the touch state as reported by the
+	/// device.
+	///
+	/// [pressure]: Event::pressure
+	pub fn set_touch_threshold(&mut self, threshold: f64) {
+		self.handler.set_touch_threshold(threshold);
+	}
+
+	/// The number of reports coming from the device that were dropped by this
+	/// queue for having failed to parse.
+	///
+	/// Malformed reports yield no events, so they would otherwise go unnoticed.
+	/// A steadily growing count usually means the firmware of the device and
+	/// the version of the Wacom STU API don't agree on the format of reports.
+	pub fn malformed_reports(&self) -> u64 {
+		self.malformed_reports
+	}
+
+	/// Creates a handle through which a blocking call to [`recv()`] on this
+	/// queue can be interrupted from another thread.
+	///
+	/// The Wacom STU API offers no way to wake up a thread waiting on a queue,
+	/// so, once this function has been called, [`recv()`] stops waiting on the
+	/// API and instead polls this queue for new reports every few milliseconds,
+	/// checking for interruptions in between. All handles created for the same
+	/// queue are interchangeable.
+	///
+	/// [`recv()`]: Self::recv
+	pub fn interrupt_handle(&mut self) -> InterruptHandle {
+		let flag = self.interrupt
+			.get_or_insert_with(|| Arc::new(AtomicBool::new(false)))
+			.clone();
+		InterruptHandle { flag }
+	}
+
+	/// Tries to pull a single report out of the source and handle it, without
+	/// blocking, returning the number of events it generated, if there was a
+	/// report to be handled.
+	fn try_handle(&mut self) -> Result<Option<usize>, Error> {
+		self.check_handler();
+
+		let buttons = self.handler.buttons.len();
+		let complete = self.source.try_handle_report(&mut self.handler)?;
+		Ok(complete.map(|complete| self.handled(complete, buttons)))
+	}
+
+	/// Pulls a single report out of the source and handles it, blocking until
+	/// one arrives or until the queue gets interrupted, and returns the number
+	/// of events it generated.
+	fn wait_handle(&mut self) -> Result<usize, Error> {
+		let interrupt = match &self.interrupt {
+			Some(interrupt) => interrupt.clone(),
+			None => {
+				self.check_handler();
+
+				let buttons = self.handler.buttons.len();
+				let complete = self.source.wait_handle_report(&mut self.handler)?;
+				return Ok(self.handled(complete, buttons))
+			}
 		};
 
-		Ok(Self { _device: device.raw.clone(), queue, handler })
+		loop {
+			if interrupt.swap(false, Ordering::AcqRel) {
+				return Err(Error::ClientError(ClientError::Interrupted))
+			}
+			if let Some(events) = self.try_handle()? {
+				return Ok(events)
+			}
+			std::thread::sleep(Self::INTERRUPTIBLE_POLL_INTERVAL);
+		}
 	}
 
-	/// Handles a report using the internal report handler in this queue.
-	fn handle(&mut self, report: Handle<[u8]>) -> Result<usize, Error> {
+	/// Makes sure no events are left over in the handler before a new report
+	/// gets handled.
+	fn check_handler(&self) {
 		assert_eq!(
 			self.handler.queue.len(),
 			0,
 			"Event queue must have been empty at the start of the handle \
 			function, but instead, it is not. ReportHandler queues must get \
-			emptied before every call to the Queue::handle() function");
+			emptied before every report gets handled by a Queue");
+	}
+
+	/// Wraps up the handling of a report, given whether it was handled in full
+	/// and how many button presses there were before it got handled, returning
+	/// the number of events it generated.
+	///
+	/// The data generated by reports that were not handled in full is invalid,
+	/// so it gets thrown away, and the report gets counted as malformed.
+	fn handled(&mut self, complete: bool, buttons: usize) -> usize {
+		if complete {
+			self.handler.queue.len()
+		} else {
+			self.malformed_reports += 1;
+
+			self.handler.queue.clear();
+			self.handler.buttons.truncate(buttons);
+
+			0
+		}
+	}
+
+	/// Tries to receive an event from the device.
+	///
+	/// This function returns immediately, regardless of whether a message is
+	/// available or not. If you wish to have blocking behavior, use [`recv()`]
+	/// instead.
+	///
+	/// [`recv()`]: Self::recv
+	pub fn try_recv(&mut self) -> Result<Event, TryRecvError> {
+		if let Some(event) = self.handler.pop_event() {
+			/* Don't bother calling the device for more info if we already have
+			 * data to feed our client with right away. */
+			return Ok(event)
+		}
+
+		self.try_handle()
+			.map_err(TryRecvError::Failed)?
+			.ok_or(TryRecvError::Empty)?;
+
+		self.handler.queue.pop_front()
+			.ok_or(TryRecvError::Empty)
+	}
+
+	/// Discards all of the events currently available in this queue, returning
+	/// how many of them were dropped.
+	///
+	/// This function returns immediately once there are no more events to be
+	/// read, and is useful for throwing away the events that piled up while
+	/// nobody was listening to the queue.
+	pub fn flush(&mut self) -> Result<usize, Error> {
+		let mut dropped = self.handler.queue.len();
+		self.handler.queue.clear();
+
+		while let Some(events) = self.try_handle()? {
+			dropped += events;
+			self.handler.queue.clear();
+		}
+
+		Ok(dropped)
+	}
+
+	/// Tries to receive an event from the device.
+	///
+	/// This function returns immediately if a message is already available and
+	/// blocks, waiting for a message to arrive, otherwise. If you wish to have
+	/// non-blocking behavior, use [`try_recv()`] instead.
+	///
+	/// A thread blocked in this function can be woken up by another thread
+	/// through an [`InterruptHandle`], created with [`interrupt_handle()`], in
+	/// which case this function fails with [`ClientError::Interrupted`].
+	///
+	/// [`try_recv()`]: Self::try_recv
+	/// [`interrupt_handle()`]: Self::interrupt_handle
+	/// [`ClientError::Interrupted`]: ClientError::Interrupted
+	pub fn recv(&mut self) -> Result<Event, Error> {
+		if let Some(event) = self.handler.pop_event() {
+			/* Don't bother calling the device for more info if we already have
+			 * data to feed our client with right away. */
+			return Ok(event)
+		}
+
+		loop {
+			let buttons = self.handler.buttons.len();
+			self.wait_handle()?;
+			if let Some(event) = self.handler.pop_event() {
+				return Ok(event)
+			}
+
+			/* Reports carrying nothing but button presses are fine, and we
+			 * should just keep waiting for pen data. */
+			if self.handler.buttons.len() == buttons {
+				return Err(Error::ClientError(ClientError::InvalidReport))
+			}
+		}
+	}
+
+	/// Checks whether any reports were lost since the last time this function
+	/// was called.
+	///
+	/// Lost reports can only be detected on devices that number their reports,
+	/// as described in [`Event::sequence()`]. When reports were lost, this
+	/// function fails with [`ClientError::DroppedReports`], describing the
+	/// first gap in the sequence numbers. On all other devices, this function
+	/// always succeeds.
+	///
+	/// [`Event::sequence()`]: Event::sequence
+	/// [`ClientError::DroppedReports`]: ClientError::DroppedReports
+	pub fn check_sequence(&mut self) -> Result<(), Error> {
+		match self.handler.gap.take() {
+			Some((expected, got)) =>
+				Err(Error::ClientError(ClientError::DroppedReports { expected, got })),
+			None => Ok(())
+		}
+	}
+
+	/// Takes all of the button presses that have been received so far.
+	///
+	/// Button presses are picked up as a side effect of reading the reports
+	/// coming from the device with [`try_recv()`], [`recv()`] and [`flush()`],
+	/// and are kept around until this function is called. Refer to the
+	/// documentation of [`ButtonEvent`] for which devices generate them.
+	///
+	/// [`try_recv()`]: Self::try_recv
+	/// [`recv()`]: Self::recv
+	/// [`flush()`]: Self::flush
+	/// [`ButtonEvent`]: ButtonEvent
+	pub fn buttons(&mut self) -> impl Iterator<Item = ButtonEvent> + '_ {
+		self.handler.buttons.drain(..)
+	}
+}
+
+/// A handle through which a thread blocked in [`Queue::recv()`] can be woken
+/// up from another thread.
+///
+/// Interrupting a queue that is not being waited on makes the next call to
+/// [`Queue::recv()`] fail right away, so that a thread that is about to start
+/// waiting on the queue when it gets interrupted still gets woken up. Every
+/// interruption wakes up a single call.
+///
+/// [`Queue::recv()`]: Queue::recv
+#[derive(Debug, Clone)]
+pub struct InterruptHandle {
+	/// The flag shared with the queue, raised when it should be interrupted.
+	flag: Arc<AtomicBool>,
+}
+impl InterruptHandle {
+	/// Interrupts the queue this handle was created for.
+	pub fn cancel(&self) {
+		self.flag.store(true, Ordering::Release);
+	}
+}
+
+/// A source of the reports coming from a device, which parses them and feeds
+/// the data in them to a [report handler].
+///
+/// This is what lets the buffering logic in a [`Queue`] be driven by sources
+/// other than the Wacom STU API.
+///
+/// [report handler]: ReportHandler
+/// [`Queue`]: Queue
+pub(crate) trait ReportSource {
+	/// Tries to pull a single report out of this source and feed it to the
+	/// given handler, without blocking.
+	///
+	/// Returns `None` if there were no reports available, and otherwise whether
+	/// the report was handled in full.
+	fn try_handle_report(&mut self, handler: &mut ReportHandler)
+		-> Result<Option<bool>, Error>;
+
+	/// Pulls a single report out of this source and feeds it to the given
+	/// handler, blocking until one arrives.
+	///
+	/// Returns whether the report was handled in full.
+	fn wait_handle_report(&mut self, handler: &mut ReportHandler)
+		-> Result<bool, Error>;
+}
+
+/// The source of reports backed by a Wacom STU API queue.
+struct InterfaceQueue {
+	/// The queue reports are pulled out of. This must be dropped before the
+	/// device it belongs to.
+	queue: RawQueue,
+	/// The device this queue is polling update data off of.
+	_device: Arc<RawTabletConnection>,
+}
+impl InterfaceQueue {
+	/// Feeds the given report to the given handler, returning whether it was
+	/// handled in full.
+	fn handle(report: Handle<[u8]>, handler: &mut ReportHandler)
+		-> Result<bool, Error> {
 
 		let mut pointer = std::ptr::null();
 		let mut returned = 0;
@@ -132,7 +725,7 @@ impl Queue {
 			stu_sys::WacomGSS_ReportHandler_handleReport(
 				std::mem::size_of::<stu_sys::WacomGSS_ReportHandlerFunctionTable>() as _,
 				&REPORT_HANDLER_FUNCTIONS,
-				&mut self.handler as *mut ReportHandler as *mut _,
+				handler as *mut ReportHandler as *mut _,
 				report.as_ptr() as *const u8,
 				report.len() as _,
 				&mut pointer,
@@ -145,27 +738,24 @@ impl Queue {
 			 * a pointer that doesn't align with the expected end of the buffer
 			 * means that the handling was incomplete and that the data we
 			 * might have generated is invalid. */
-			self.handler.queue.clear();
+			let consumed = (pointer as usize)
+				.saturating_sub(report.as_ptr() as usize);
+			log::warn!(
+				"dropping malformed report: {} of {} bytes were handled, and \
+				the handler returned {}",
+				consumed,
+				report.len(),
+				returned);
 
-			Ok(0)
+			Ok(false)
 		} else {
-			Ok(self.handler.queue.len())
+			Ok(true)
 		}
 	}
-
-	/// Tries to receive an event from the device.
-	///
-	/// This function returns immediately, regardless of whether a message is
-	/// available or not. If you wish to have blocking behavior, use [`recv()`]
-	/// instead.
-	///
-	/// [`recv()`]: Self::recv
-	pub fn try_recv(&mut self) -> Result<Event, TryRecvError> {
-		if let Some(event) = self.handler.pop_event() {
-			/* Don't bother calling the device for more info if we already have
-			 * data to feed our client with right away. */
-			return Ok(event)
-		}
+}
+impl ReportSource for InterfaceQueue {
+	fn try_handle_report(&mut self, handler: &mut ReportHandler)
+		-> Result<Option<bool>, Error> {
 
 		let report = unsafe {
 			let mut report = std::ptr::null_mut();
@@ -178,40 +768,19 @@ impl Queue {
 					&mut report,
 					&mut length,
 					&mut available)
-			}).map_err(InternalError::unwrap_to_general)
-				.map_err(TryRecvError::Failed)?;
+			}).map_err(InternalError::unwrap_to_general)?;
 
-			if available != 0 {
-				Some(Handle::wrap_slice(report, length as _))
-			} else {
-				None
+			if available == 0 {
+				return Ok(None)
 			}
+			Handle::wrap_slice(report, length as _)
 		};
 
-		report
-			.ok_or(TryRecvError::Empty)
-			.and_then(|report| {
-				self.handle(report)
-					.map_err(TryRecvError::Failed)?;
-
-				self.handler.queue.pop_front()
-					.ok_or(TryRecvError::Empty)
-			})
+		Self::handle(report, handler).map(Some)
 	}
 
-	/// Tries to receive an event from the device.
-	///
-	/// This function returns immediately if a message is already available and
-	/// blocks, waiting for a message to arrive, otherwise. If you wish to have
-	/// non-blocking behavior, use [`try_recv()`] instead.
-	///
-	/// [`try_recv()`]: Self::try_recv
-	pub fn recv(&mut self) -> Result<Event, Error> {
-		if let Some(event) = self.handler.pop_event() {
-			/* Don't bother calling the device for more info if we already have
-			 * data to feed our client with right away. */
-			return Ok(event)
-		}
+	fn wait_handle_report(&mut self, handler: &mut ReportHandler)
+		-> Result<bool, Error> {
 
 		let report = unsafe {
 			let mut report = std::ptr::null_mut();
@@ -227,9 +796,7 @@ impl Queue {
 			Handle::wrap_slice(report, length as _)
 		};
 
-		self.handle(report)?;
-		self.handler.queue.pop_front()
-			.ok_or(Error::ClientError(ClientError::InvalidReport))
+		Self::handle(report, handler)
 	}
 }
 
@@ -246,18 +813,18 @@ impl Drop for RawQueue {
 /// The table of report handler functions.
 const REPORT_HANDLER_FUNCTIONS: stu_sys::WacomGSS_ReportHandlerFunctionTable = stu_sys::WacomGSS_ReportHandlerFunctionTable {
 	onPenData: Some(on_pen_data),
-	onPenDataOption: None,
+	onPenDataOption: Some(on_pen_data_option),
 	onPenDataEncrypted: None,
 	onPenDataEncryptedOption: None,
 	onDevicePublicKey: None,
 	decrypt: None,
-	onPenDataTimeCountSequence: None,
+	onPenDataTimeCountSequence: Some(on_pen_data_time_count_sequence),
 	onPenDataTimeCountSequenceEncrypted: None,
 	onEncryptionStatus: None,
 	onEventData: None,
 	onEventDataPinPad: None,
 	onEventDataKeyPad: None,
-	onEventDataSignature: None,
+	onEventDataSignature: Some(on_event_data_signature),
 	onEventDataEncrypted: None,
 	onEventDataPinPadEncrypted: None,
 	onEventDataKeyPadEncrypted: None,
@@ -270,13 +837,63 @@ const REPORT_HANDLER_FUNCTIONS: stu_sys::WacomGSS_ReportHandlerFunctionTable = s
 ///
 /// [queue]: Queue
 #[derive(Debug)]
-struct ReportHandler {
+pub(crate) struct ReportHandler {
 	/// The resolution of this screen in each of the three axes.
 	resolution: (u32, u32, u32),
 	/// The internal queue of converted events.
 	queue: VecDeque<Event>,
+	/// The internal queue of button presses.
+	buttons: VecDeque<ButtonEvent>,
+	/// The sequence number of the last report that carried one.
+	last_sequence: Option<u16>,
+	/// The first gap in the sequence numbers of the reports found since it
+	/// was last checked for, as the expected and the received numbers.
+	gap: Option<(u16, u16)>,
+	/// The smoothing applied to the position of the pen, if any.
+	smoothing: Option<Smoothing>,
+	/// The last smoothed position of the pen and whether it was touching the
+	/// screen at the time.
+	smoothed: Option<((f64, f64), bool)>,
+	/// The pressure at or below which the pen is not considered to be
+	/// touching the screen, if any.
+	touch_threshold: Option<f64>,
+	/// The calibration applied to the position of the pen.
+	calibration: Calibration,
 }
 impl ReportHandler {
+	/// Creates a new handler for a device with the given input resolution.
+	pub(crate) fn new(resolution: (u32, u32, u32)) -> Self {
+		Self {
+			resolution,
+			queue: Default::default(),
+			buttons: Default::default(),
+			last_sequence: None,
+			gap: None,
+			smoothing: None,
+			smoothed: None,
+			touch_threshold: None,
+			calibration: Default::default(),
+		}
+	}
+
+	/// Changes the calibration applied to the position of the pen.
+	pub(crate) fn set_calibration(&mut self, calibration: Calibration) {
+		self.calibration = calibration;
+		self.smoothed = None;
+	}
+
+	/// Changes the pressure at or below which the pen is not considered to be
+	/// touching the screen.
+	pub(crate) fn set_touch_threshold(&mut self, threshold: f64) {
+		self.touch_threshold = if threshold > 0.0 { Some(threshold) } else { None };
+	}
+
+	/// Changes the smoothing applied to the position of the pen.
+	pub(crate) fn set_smoothing(&mut self, smoothing: Option<Smoothing>) {
+		self.smoothing = smoothing;
+		self.smoothed = None;
+	}
+
 	/// Enqueue a new event on this handler.
 	pub fn push_event(&mut self, event: Event) {
 		self.queue.push_back(event)
@@ -286,6 +903,78 @@ impl ReportHandler {
 	pub fn pop_event(&mut self) -> Option<Event> {
 		self.queue.pop_front()
 	}
+
+	/// Converts the given pen data into an event and enqueues it.
+	///
+	/// This is kept separate from the C callback so that the conversion can be
+	/// driven by synthetic pen data, without needing a connection to a device.
+	pub(crate) fn on_pen_data(
+		&mut self,
+		pen_data: &stu_sys::WacomGSS_PenData,
+		extras: PenDataExtras) {
+
+		assert_ne!(self.resolution.0, 0);
+		assert_ne!(self.resolution.1, 0);
+		assert_ne!(self.resolution.2, 0);
+
+		let pressure = (f64::from(pen_data.pressure) / f64::from(self.resolution.2))
+			.clamp(0.0, 1.0);
+		let touching = match self.touch_threshold {
+			Some(threshold) => pen_data.sw != 0 && pressure > threshold,
+			None => pen_data.sw != 0
+		};
+		let position = (
+			(f64::from(pen_data.x) / f64::from(self.resolution.0)).clamp(0.0, 1.0),
+			(f64::from(pen_data.y) / f64::from(self.resolution.1)).clamp(0.0, 1.0),
+		);
+		let position = self.calibration.apply(position);
+		let position = match (self.smoothing, self.smoothed) {
+			(Some(smoothing), Some((last, was_touching))) if was_touching == touching =>
+				smoothing.apply(last, position),
+			_ => position
+		};
+		if self.smoothing.is_some() {
+			self.smoothed = Some((position, touching));
+		}
+
+		self.push_event(Event {
+			timestamp: Instant::now(),
+			position: (position.0, position.1, pressure),
+			raw: (pen_data.x, pen_data.y, pen_data.pressure),
+			resolution: self.resolution,
+			touching,
+			close: pen_data.rdy != 0,
+			option: extras.option,
+			time_count: extras.time_count,
+			sequence: extras.sequence
+		});
+
+		if let Some(sequence) = extras.sequence {
+			if let Some(last) = self.last_sequence {
+				let expected = last.wrapping_add(1);
+				if sequence != expected {
+					log::warn!(
+						"pen data reports were lost: expected sequence number \
+						{}, got {}",
+						expected,
+						sequence);
+					self.gap.get_or_insert((expected, sequence));
+				}
+			}
+			self.last_sequence = Some(sequence);
+		}
+	}
+}
+
+/// The data some reports carry along with the pen data.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub(crate) struct PenDataExtras {
+	/// The optional value, from pen data option reports.
+	pub option: Option<u16>,
+	/// The time count, from time count and sequence reports.
+	pub time_count: Option<u16>,
+	/// The sequence number, from time count and sequence reports.
+	pub sequence: Option<u16>,
 }
 
 /// Generic handler for pen data callbacks.
@@ -295,30 +984,462 @@ unsafe extern "C" fn on_pen_data(
 	pen_data: *const stu_sys::WacomGSS_PenData) -> std::os::raw::c_int {
 
 	let this = &mut *(handler as *mut ReportHandler);
-	assert_ne!(this.resolution.0, 0);
-	assert_ne!(this.resolution.1, 0);
-	assert_ne!(this.resolution.2, 0);
+	this.on_pen_data(&*pen_data, PenDataExtras::default());
+
+	0
+}
+
+/// Handler for pen data callbacks carrying an optional value.
+unsafe extern "C" fn on_pen_data_option(
+	handler: *mut std::os::raw::c_void,
+	_size_of_pen_data_option: stu_sys::size_t,
+	pen_data_option: *const stu_sys::WacomGSS_PenDataOption) -> std::os::raw::c_int {
+
+	let this = &mut *(handler as *mut ReportHandler);
+	let pen_data_option = *pen_data_option;
+
+	let mut pen_data: stu_sys::WacomGSS_PenData = std::mem::zeroed();
+	pen_data.rdy = pen_data_option.rdy;
+	pen_data.sw = pen_data_option.sw;
+	pen_data.pressure = pen_data_option.pressure;
+	pen_data.x = pen_data_option.x;
+	pen_data.y = pen_data_option.y;
+	this.on_pen_data(&pen_data, PenDataExtras {
+		option: Some(pen_data_option.option),
+		..Default::default()
+	});
+
+	0
+}
+
+/// Handler for pen data callbacks carrying a time count and a sequence number.
+unsafe extern "C" fn on_pen_data_time_count_sequence(
+	handler: *mut std::os::raw::c_void,
+	_size_of_pen_data: stu_sys::size_t,
+	pen_data_tcs: *const stu_sys::WacomGSS_PenDataTimeCountSequence) -> std::os::raw::c_int {
+
+	let this = &mut *(handler as *mut ReportHandler);
+	let pen_data_tcs = *pen_data_tcs;
 
-	let pen_data = *pen_data;
-	this.push_event(Event {
+	let mut pen_data: stu_sys::WacomGSS_PenData = std::mem::zeroed();
+	pen_data.rdy = pen_data_tcs.rdy;
+	pen_data.sw = pen_data_tcs.sw;
+	pen_data.pressure = pen_data_tcs.pressure;
+	pen_data.x = pen_data_tcs.x;
+	pen_data.y = pen_data_tcs.y;
+	this.on_pen_data(&pen_data, PenDataExtras {
+		time_count: Some(pen_data_tcs.timeCount),
+		sequence: Some(pen_data_tcs.sequence),
+		..Default::default()
+	});
+
+	0
+}
+
+/// Handler for the button presses made while in signature capture mode.
+unsafe extern "C" fn on_event_data_signature(
+	handler: *mut std::os::raw::c_void,
+	_size_of_event_data: stu_sys::size_t,
+	event_data: *const stu_sys::WacomGSS_EventDataSignature) -> std::os::raw::c_int {
+
+	let this = &mut *(handler as *mut ReportHandler);
+	this.buttons.push_back(ButtonEvent {
 		timestamp: Instant::now(),
-		position: (
-			(f64::from(pen_data.x) / f64::from(this.resolution.0)).clamp(0.0, 1.0),
-			(f64::from(pen_data.y) / f64::from(this.resolution.1)).clamp(0.0, 1.0),
-			(f64::from(pen_data.pressure) / f64::from(this.resolution.2)).clamp(0.0, 1.0),
-		),
-		touching: pen_data.sw != 0,
-		close: pen_data.rdy != 0
+		key: (*event_data).keyValue
 	});
 
 	0
 }
 
 /// This structure enumerates the reasons why an event may not be available.
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum TryRecvError {
 	/// The interface is valid, but there are still no more events to be read.
+	#[error("there are no events available to be read")]
 	Empty,
 	/// The interface has returned an error and should be considered invalid.
-	Failed(Error)
-}
\ No newline at end of file
+	#[error("could not read events from the device")]
+	Failed(#[source] Error)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// The resolution of the input grid of the fake device.
+	const RESOLUTION: (u32, u32, u32) = (1000, 500, 1023);
+
+	/// A source of reports feeding synthetic pen data to the handler, in which
+	/// reports that are `None` are malformed.
+	struct FakeSource {
+		reports: VecDeque<Option<stu_sys::WacomGSS_PenData>>,
+	}
+	impl ReportSource for FakeSource {
+		fn try_handle_report(&mut self, handler: &mut ReportHandler)
+			-> Result<Option<bool>, Error> {
+
+			Ok(self.reports.pop_front().map(|report| match report {
+				Some(pen_data) => {
+					handler.on_pen_data(&pen_data, PenDataExtras::default());
+					true
+				},
+				None => false
+			}))
+		}
+
+		fn wait_handle_report(&mut self, handler: &mut ReportHandler)
+			-> Result<bool, Error> {
+
+			self.try_handle_report(handler)?
+				.ok_or(Error::ClientError(ClientError::Interrupted))
+		}
+	}
+
+	/// Creates pen data with the given raw values.
+	fn pen_data(x: u16, y: u16, pressure: u16, sw: u8, rdy: u8)
+		-> stu_sys::WacomGSS_PenData {
+
+		let mut pen_data: stu_sys::WacomGSS_PenData = unsafe { std::mem::zeroed() };
+		pen_data.x = x;
+		pen_data.y = y;
+		pen_data.pressure = pressure;
+		pen_data.sw = sw as _;
+		pen_data.rdy = rdy as _;
+		pen_data
+	}
+
+	/// Creates a queue that reads the given reports.
+	fn queue(reports: Vec<Option<stu_sys::WacomGSS_PenData>>) -> Queue {
+		Queue::with_source(
+			Box::new(FakeSource { reports: reports.into() }),
+			ReportHandler::new(RESOLUTION))
+	}
+
+	#[test]
+	fn pen_data_is_normalized() {
+		let mut handler = ReportHandler::new(RESOLUTION);
+		handler.on_pen_data(&pen_data(250, 500, 1023, 1, 1), PenDataExtras::default());
+
+		let event = handler.pop_event().unwrap();
+		assert_eq!(event.x(), 0.25);
+		assert_eq!(event.y(), 1.0);
+		assert_eq!(event.pressure(), 1.0);
+		assert_eq!(event.raw_x(), 250);
+		assert_eq!(event.raw_resolution(), RESOLUTION);
+	}
+
+	#[test]
+	fn pen_data_beyond_the_grid_is_clamped() {
+		let mut handler = ReportHandler::new(RESOLUTION);
+		handler.on_pen_data(&pen_data(2000, 1000, 2046, 1, 1), PenDataExtras::default());
+
+		let event = handler.pop_event().unwrap();
+		assert_eq!((event.x(), event.y(), event.pressure()), (1.0, 1.0, 1.0));
+	}
+
+	#[test]
+	fn touch_and_hover_flags_follow_the_pen_data() {
+		let mut handler = ReportHandler::new(RESOLUTION);
+		handler.on_pen_data(&pen_data(0, 0, 512, 1, 1), PenDataExtras::default());
+		handler.on_pen_data(&pen_data(0, 0, 0, 0, 1), PenDataExtras::default());
+		handler.on_pen_data(&pen_data(0, 0, 0, 0, 0), PenDataExtras::default());
+
+		let touching = handler.pop_event().unwrap();
+		assert!(touching.touching() && touching.hovering());
+		let hovering = handler.pop_event().unwrap();
+		assert!(!hovering.touching() && hovering.hovering());
+		let away = handler.pop_event().unwrap();
+		assert!(!away.touching() && !away.hovering());
+	}
+
+	#[test]
+	fn queue_reads_events_in_order() {
+		let mut queue = queue(vec![
+			Some(pen_data(100, 0, 0, 1, 1)),
+			Some(pen_data(200, 0, 0, 1, 1)),
+		]);
+
+		assert_eq!(queue.try_recv().unwrap().raw_x(), 100);
+		assert_eq!(queue.recv().unwrap().raw_x(), 200);
+		assert!(matches!(queue.try_recv(), Err(TryRecvError::Empty)));
+	}
+
+	#[test]
+	fn malformed_reports_are_counted_and_dropped() {
+		let mut queue = queue(vec![
+			None,
+			Some(pen_data(100, 0, 0, 1, 1)),
+		]);
+
+		assert!(matches!(queue.try_recv(), Err(TryRecvError::Empty)));
+		assert_eq!(queue.malformed_reports(), 1);
+		assert_eq!(queue.try_recv().unwrap().raw_x(), 100);
+	}
+
+	#[test]
+	fn sequence_gaps_are_reported_once() {
+		let mut handler = ReportHandler::new(RESOLUTION);
+		for sequence in [1, 2, 4, 5, 7].iter().copied() {
+			handler.on_pen_data(&pen_data(0, 0, 0, 1, 1), PenDataExtras {
+				sequence: Some(sequence),
+				..Default::default()
+			});
+		}
+		let mut queue = Queue::with_source(
+			Box::new(FakeSource { reports: VecDeque::new() }),
+			handler);
+
+		assert!(matches!(
+			queue.check_sequence(),
+			Err(Error::ClientError(ClientError::DroppedReports { expected: 3, got: 4 }))));
+		assert!(queue.check_sequence().is_ok());
+	}
+
+	#[test]
+	fn sequence_numbers_wrap_around() {
+		let mut handler = ReportHandler::new(RESOLUTION);
+		for sequence in [u16::MAX - 1, u16::MAX, 0, 1].iter().copied() {
+			handler.on_pen_data(&pen_data(0, 0, 0, 1, 1), PenDataExtras {
+				sequence: Some(sequence),
+				..Default::default()
+			});
+		}
+
+		assert_eq!(handler.gap, None);
+	}
+
+	#[test]
+	fn light_touches_are_below_the_threshold() {
+		let mut handler = ReportHandler::new(RESOLUTION);
+		handler.set_touch_threshold(0.1);
+		handler.on_pen_data(&pen_data(0, 0, 50, 1, 1), PenDataExtras::default());
+		handler.on_pen_data(&pen_data(0, 0, 512, 1, 1), PenDataExtras::default());
+
+		assert!(!handler.pop_event().unwrap().touching());
+		assert!(handler.pop_event().unwrap().touching());
+
+		/* A threshold of zero leaves the touch state to the device. */
+		handler.set_touch_threshold(0.0);
+		handler.on_pen_data(&pen_data(0, 0, 1, 1, 1), PenDataExtras::default());
+		assert!(handler.pop_event().unwrap().touching());
+	}
+
+	#[test]
+	fn smoothing_restarts_when_the_touch_state_changes() {
+		let mut handler = ReportHandler::new(RESOLUTION);
+		handler.set_smoothing(Some(Smoothing { factor: 0.5 }));
+		handler.on_pen_data(&pen_data(0, 0, 512, 1, 1), PenDataExtras::default());
+		handler.on_pen_data(&pen_data(1000, 0, 512, 1, 1), PenDataExtras::default());
+		handler.on_pen_data(&pen_data(1000, 0, 0, 0, 1), PenDataExtras::default());
+
+		assert_eq!(handler.pop_event().unwrap().x(), 0.0);
+		assert_eq!(handler.pop_event().unwrap().x(), 0.5);
+
+		/* Lifting the pen jumps straight to where it really is. */
+		let lifted = handler.pop_event().unwrap();
+		assert!(!lifted.touching());
+		assert_eq!(lifted.x(), 1.0);
+	}
+
+	#[test]
+	fn calibrated_positions_are_clamped_to_the_screen() {
+		let mut handler = ReportHandler::new(RESOLUTION);
+		handler.set_calibration(Calibration {
+			offset: (0.1, -0.1),
+			scale: (1.2, 1.0),
+		});
+		handler.on_pen_data(&pen_data(1000, 0, 0, 1, 1), PenDataExtras::default());
+		handler.on_pen_data(&pen_data(250, 250, 0, 1, 1), PenDataExtras::default());
+
+		let clamped = handler.pop_event().unwrap();
+		assert_eq!((clamped.x(), clamped.y()), (1.0, 0.0));
+		assert_eq!((clamped.raw_x(), clamped.raw_y()), (1000, 0));
+
+		let corrected = handler.pop_event().unwrap();
+		assert!((corrected.x() - 0.4).abs() < 1e-9);
+		assert!((corrected.y() - 0.4).abs() < 1e-9);
+	}
+
+	#[test]
+	fn flush_drops_every_pending_event() {
+		let mut queue = queue((0..5)
+			.map(|x| Some(pen_data(x, 0, 0, 1, 1)))
+			.collect());
+
+		assert_eq!(queue.flush().unwrap(), 5);
+		assert!(matches!(queue.try_recv(), Err(TryRecvError::Empty)));
+	}
+
+	#[test]
+	fn interrupting_wakes_up_a_blocked_receive() {
+		let mut queue = queue(vec![]);
+		let interrupt = queue.interrupt_handle();
+
+		let canceller = std::thread::spawn(move || {
+			std::thread::sleep(Duration::from_millis(20));
+			interrupt.cancel();
+		});
+		assert!(matches!(
+			queue.recv(),
+			Err(Error::ClientError(ClientError::Interrupted))));
+		canceller.join().unwrap();
+
+		/* Interruptions made before waiting still get noticed. */
+		queue.interrupt_handle().cancel();
+		assert!(matches!(
+			queue.recv(),
+			Err(Error::ClientError(ClientError::Interrupted))));
+	}
+
+	#[test]
+	fn signature_events_become_button_presses() {
+		let mut handler = ReportHandler::new(RESOLUTION);
+
+		let mut event_data: stu_sys::WacomGSS_EventDataSignature =
+			unsafe { std::mem::zeroed() };
+		event_data.keyValue = 3;
+		let result = unsafe {
+			on_event_data_signature(
+				&mut handler as *mut ReportHandler as *mut _,
+				std::mem::size_of::<stu_sys::WacomGSS_EventDataSignature>() as _,
+				&event_data)
+		};
+		assert_eq!(result, 0);
+		handler.on_pen_data(&pen_data(250, 0, 512, 1, 1), PenDataExtras::default());
+
+		let mut queue = Queue::with_source(
+			Box::new(FakeSource { reports: VecDeque::new() }),
+			handler);
+
+		let buttons = queue.buttons().collect::<Vec<_>>();
+		assert_eq!(buttons.len(), 1);
+		assert_eq!(buttons[0].key(), 3);
+		assert_eq!(queue.buttons().count(), 0);
+
+		let event = queue.try_recv().unwrap();
+		assert_eq!(event.raw_x(), 250);
+		assert!(event.touching());
+	}
+
+	#[test]
+	fn pen_data_extras_are_decoded() {
+		let mut handler = ReportHandler::new(RESOLUTION);
+		let handler_ptr = &mut handler as *mut ReportHandler as *mut _;
+
+		let mut option: stu_sys::WacomGSS_PenDataOption = unsafe { std::mem::zeroed() };
+		option.x = 100;
+		option.sw = 1 as _;
+		option.rdy = 1 as _;
+		option.option = 42;
+
+		let mut tcs: stu_sys::WacomGSS_PenDataTimeCountSequence =
+			unsafe { std::mem::zeroed() };
+		tcs.x = 200;
+		tcs.timeCount = 1234;
+		tcs.sequence = 7;
+
+		unsafe {
+			on_pen_data_option(
+				handler_ptr,
+				std::mem::size_of::<stu_sys::WacomGSS_PenDataOption>() as _,
+				&option);
+			on_pen_data_time_count_sequence(
+				handler_ptr,
+				std::mem::size_of::<stu_sys::WacomGSS_PenDataTimeCountSequence>() as _,
+				&tcs);
+			on_pen_data(
+				handler_ptr,
+				std::mem::size_of::<stu_sys::WacomGSS_PenData>() as _,
+				&pen_data(300, 0, 0, 0, 1));
+		}
+
+		let event = handler.pop_event().unwrap();
+		assert_eq!(event.raw_x(), 100);
+		assert!(event.touching());
+		assert_eq!(event.option(), Some(42));
+		assert_eq!(event.time_count(), None);
+		assert_eq!(event.sequence(), None);
+
+		let event = handler.pop_event().unwrap();
+		assert_eq!(event.raw_x(), 200);
+		assert_eq!(event.option(), None);
+		assert_eq!(event.time_count(), Some(1234));
+		assert_eq!(event.sequence(), Some(7));
+
+		/* Plain pen data carries none of them. */
+		let event = handler.pop_event().unwrap();
+		assert_eq!(event.raw_x(), 300);
+		assert_eq!(event.option(), None);
+		assert_eq!(event.time_count(), None);
+		assert_eq!(event.sequence(), None);
+	}
+
+	/// The variance of the given values.
+	fn variance(values: &[f64]) -> f64 {
+		let mean = values.iter().sum::<f64>() / values.len() as f64;
+		values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / values.len() as f64
+	}
+
+	#[test]
+	fn smoothing_reduces_jitter() {
+		let mut handler = ReportHandler::new(RESOLUTION);
+		handler.set_smoothing(Some(Smoothing { factor: 0.5 }));
+
+		/* The pen holds still, give or take a few units of jitter. */
+		let raw = (0..40)
+			.map(|i| if i % 2 == 0 { 550 } else { 450 })
+			.collect::<Vec<u16>>();
+		for &x in &raw {
+			handler.on_pen_data(&pen_data(x, 0, 512, 1, 1), PenDataExtras::default());
+		}
+
+		let input = raw.iter()
+			.map(|x| f64::from(*x) / f64::from(RESOLUTION.0))
+			.collect::<Vec<_>>();
+		let output = std::iter::from_fn(|| handler.pop_event())
+			.map(|event| event.x())
+			.collect::<Vec<_>>();
+
+		/* Leave the first few events out, while the average settles. */
+		assert!(variance(&output[10..]) < variance(&input[10..]) / 2.0);
+	}
+
+	#[test]
+	fn smoothing_catches_up_with_the_pen() {
+		let mut handler = ReportHandler::new(RESOLUTION);
+		handler.set_smoothing(Some(Smoothing { factor: 0.5 }));
+
+		/* The pen jumps across the screen and stays there. */
+		handler.on_pen_data(&pen_data(0, 0, 512, 1, 1), PenDataExtras::default());
+		for _ in 0..8 {
+			handler.on_pen_data(&pen_data(1000, 0, 512, 1, 1), PenDataExtras::default());
+		}
+
+		let output = std::iter::from_fn(|| handler.pop_event())
+			.map(|event| event.x())
+			.collect::<Vec<_>>();
+		assert!(output.windows(2).all(|pair| pair[0] <= pair[1]));
+
+		/* Half of the way is left to go after every event. */
+		for (i, x) in output.iter().enumerate() {
+			let lag = 1.0 - x;
+			assert!(lag <= 0.5f64.powi(i as i32) + 1e-9, "lag of {} after {} events", lag, i);
+		}
+		assert!(1.0 - output[output.len() - 1] < 0.01);
+	}
+
+	#[test]
+	fn try_recv_errors_describe_themselves() {
+		use std::error::Error as _;
+
+		let empty = TryRecvError::Empty;
+		assert_eq!(empty.to_string(), "there are no events available to be read");
+		assert!(empty.source().is_none());
+
+		let failed = TryRecvError::Failed(Error::ClientError(ClientError::InvalidReport));
+		assert_eq!(failed.to_string(), "could not read events from the device");
+		assert_eq!(
+			failed.source().unwrap().to_string(),
+			ClientError::InvalidReport.to_string());
+	}
+}
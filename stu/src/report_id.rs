@@ -0,0 +1,165 @@
+/// The types of report a device may support.
+///
+/// Every feature of a device is accessed through one or more reports, so
+/// checking for support of the reports behind a feature is a way to know ahead
+/// of time whether the device is able to perform it.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ReportId {
+	/// Pen data, generated as the pen moves over the screen.
+	PenData,
+	/// The current status of the device.
+	Status,
+	/// Resetting of the device.
+	Reset,
+	/// Model name and firmware version of the device, used by
+	/// [`Tablet::model_name()`] and [`Tablet::firmware_version()`].
+	///
+	/// [`Tablet::model_name()`]: crate::Tablet::model_name
+	/// [`Tablet::firmware_version()`]: crate::Tablet::firmware_version
+	Information,
+	/// Screen and input grid dimensions, used by [`Tablet::capability()`].
+	///
+	/// [`Tablet::capability()`]: crate::Tablet::capability
+	Capability,
+	/// Serial number of the device, used by [`Tablet::serial_number()`].
+	///
+	/// [`Tablet::serial_number()`]: crate::Tablet::serial_number
+	Uid2,
+	/// Clearing of the screen, used by [`Tablet::clear()`].
+	///
+	/// [`Tablet::clear()`]: crate::Tablet::clear
+	ClearScreen,
+	/// Drawing of the pen strokes on the screen, used by [`Tablet::inking()`].
+	///
+	/// [`Tablet::inking()`]: crate::Tablet::inking
+	InkingMode,
+	/// Brightness of the backlight, used by [`Tablet::backlight()`] and
+	/// [`Tablet::set_backlight()`].
+	///
+	/// [`Tablet::backlight()`]: crate::Tablet::backlight
+	/// [`Tablet::set_backlight()`]: crate::Tablet::set_backlight
+	BacklightBrightness,
+	/// Writing of full screen images, used by [`Tablet::write_image()`].
+	///
+	/// [`Tablet::write_image()`]: crate::Tablet::write_image
+	StartImageData,
+	/// Writing of images to regions of the screen, used by
+	/// [`Tablet::clear_region()`].
+	///
+	/// [`Tablet::clear_region()`]: crate::Tablet::clear_region
+	StartImageDataArea,
+	/// Confinement of inking to a region of the screen, used by
+	/// [`Tablet::set_handwriting_display_area()`].
+	///
+	/// [`Tablet::set_handwriting_display_area()`]: crate::Tablet::set_handwriting_display_area
+	HandwritingDisplayArea,
+	/// Color and thickness of the pen strokes drawn by the device itself,
+	/// used by [`Tablet::set_ink_style()`].
+	///
+	/// [`Tablet::set_ink_style()`]: crate::Tablet::set_ink_style
+	HandwritingThicknessColor24,
+	/// Mode in which pen data is reported, used by
+	/// [`Tablet::pen_data_mode()`] and [`Tablet::set_pen_data_mode()`].
+	///
+	/// [`Tablet::pen_data_mode()`]: crate::Tablet::pen_data_mode
+	/// [`Tablet::set_pen_data_mode()`]: crate::Tablet::set_pen_data_mode
+	PenDataOptionMode,
+	/// Any other type of report, by its raw identifier.
+	Other(stu_sys::tagWacomGSS_ReportId),
+}
+impl ReportId {
+	/// The report types with a name of their own, and their raw identifiers.
+	const NAMED: [(Self, stu_sys::tagWacomGSS_ReportId); 14] = [
+		(Self::PenData, stu_sys::tagWacomGSS_ReportId_WacomGSS_ReportId_PenData),
+		(Self::Status, stu_sys::tagWacomGSS_ReportId_WacomGSS_ReportId_Status),
+		(Self::Reset, stu_sys::tagWacomGSS_ReportId_WacomGSS_ReportId_Reset),
+		(Self::Information, stu_sys::tagWacomGSS_ReportId_WacomGSS_ReportId_Information),
+		(Self::Capability, stu_sys::tagWacomGSS_ReportId_WacomGSS_ReportId_Capability),
+		(Self::Uid2, stu_sys::tagWacomGSS_ReportId_WacomGSS_ReportId_Uid2),
+		(Self::ClearScreen, stu_sys::tagWacomGSS_ReportId_WacomGSS_ReportId_ClearScreen),
+		(Self::InkingMode, stu_sys::tagWacomGSS_ReportId_WacomGSS_ReportId_InkingMode),
+		(Self::BacklightBrightness, stu_sys::tagWacomGSS_ReportId_WacomGSS_ReportId_BacklightBrightness),
+		(Self::StartImageData, stu_sys::tagWacomGSS_ReportId_WacomGSS_ReportId_StartImageData),
+		(Self::StartImageDataArea, stu_sys::tagWacomGSS_ReportId_WacomGSS_ReportId_StartImageDataArea),
+		(Self::HandwritingDisplayArea, stu_sys::tagWacomGSS_ReportId_WacomGSS_ReportId_HandwritingDisplayArea),
+		(Self::HandwritingThicknessColor24, stu_sys::tagWacomGSS_ReportId_WacomGSS_ReportId_HandwritingThicknessColor24),
+		(Self::PenDataOptionMode, stu_sys::tagWacomGSS_ReportId_WacomGSS_ReportId_PenDataOptionMode),
+	];
+
+	/// Wraps the given raw report identifier.
+	pub(crate) fn from_raw(raw: stu_sys::tagWacomGSS_ReportId) -> Self {
+		Self::NAMED.iter()
+			.find(|(_, id)| *id == raw)
+			.map(|(report, _)| *report)
+			.unwrap_or(Self::Other(raw))
+	}
+
+	/// The name of this report type, as used by the Wacom STU API, if it has
+	/// one of its own.
+	pub fn name(&self) -> Option<&'static str> {
+		let name = match self {
+			Self::PenData => "PenData",
+			Self::Status => "Status",
+			Self::Reset => "Reset",
+			Self::Information => "Information",
+			Self::Capability => "Capability",
+			Self::Uid2 => "Uid2",
+			Self::ClearScreen => "ClearScreen",
+			Self::InkingMode => "InkingMode",
+			Self::BacklightBrightness => "BacklightBrightness",
+			Self::StartImageData => "StartImageData",
+			Self::StartImageDataArea => "StartImageDataArea",
+			Self::HandwritingDisplayArea => "HandwritingDisplayArea",
+			Self::HandwritingThicknessColor24 => "HandwritingThicknessColor24",
+			Self::PenDataOptionMode => "PenDataOptionMode",
+			Self::Other(_) => return None
+		};
+		Some(name)
+	}
+
+	/// The raw identifier of this report type.
+	pub(crate) fn raw(&self) -> stu_sys::tagWacomGSS_ReportId {
+		match self {
+			Self::Other(raw) => *raw,
+			report => Self::NAMED.iter()
+				.find(|(named, _)| named == report)
+				.map(|(_, id)| *id)
+				.unwrap()
+		}
+	}
+}
+impl std::fmt::Display for ReportId {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self.name() {
+			Some(name) => write!(f, "{}", name),
+			None => write!(f, "0x{:02x}", self.raw())
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn named_reports_round_trip_through_raw() {
+		for (report, raw) in ReportId::NAMED.iter().copied() {
+			assert_eq!(ReportId::from_raw(raw), report);
+			assert_eq!(report.raw(), raw);
+			assert_eq!(report.to_string(), report.name().unwrap());
+		}
+	}
+
+	#[test]
+	fn other_reports_round_trip_through_raw() {
+		let raw = (0..=0xff)
+			.find(|raw| ReportId::NAMED.iter().all(|(_, id)| id != raw))
+			.unwrap();
+		let report = ReportId::from_raw(raw);
+
+		assert_eq!(report, ReportId::Other(raw));
+		assert_eq!(report.raw(), raw);
+		assert_eq!(report.name(), None);
+		assert_eq!(report.to_string(), format!("0x{:02x}", raw));
+	}
+}
@@ -0,0 +1,269 @@
+use crate::{Error, Capability, Calibration, Queue};
+use crate::report::{ReportHandler, ReportSource, PenDataExtras};
+use std::time::{Duration, Instant};
+
+/// The resolution of the input grid of the simulated device.
+const RESOLUTION: (u32, u32, u32) = (9600, 6000, 1023);
+
+/// The amount of time between two consecutive simulated reports.
+const REPORT_INTERVAL: Duration = Duration::from_millis(5);
+
+/// The number of reports in a single run of the simulated signature.
+const REPORTS: u32 = 1200;
+
+/// The capabilities of the simulated device, which are those of an STU-530.
+pub(crate) const CAPABILITY: Capability = Capability {
+	display_width: 800,
+	display_height: 480,
+	input_width: RESOLUTION.0,
+	input_height: RESOLUTION.1,
+	input_depth: RESOLUTION.2,
+	input_resolution: 2540,
+	encoding_flags: 0,
+};
+
+/// The greatest brightness level of the backlight of the simulated device.
+pub(crate) const MAX_BACKLIGHT: u8 = 3;
+
+/// Opens a queue on the simulated device, with the given calibration.
+///
+/// The signature is a wave drawn across the screen in three strokes, with the
+/// pen hovering between them, and gets drawn over and over again for as long
+/// as the queue is being read from. Events become available in real time, at
+/// the rate a real device would generate them, and go through the same
+/// conversion from reports to events as the ones of a real device.
+pub(crate) fn queue(calibration: Calibration) -> Queue {
+	let mut handler = ReportHandler::new(RESOLUTION);
+	handler.set_calibration(calibration);
+
+	Queue::with_source(Box::new(Simulation::new()), handler)
+}
+
+/// The source of the reports of the simulated signature.
+struct Simulation {
+	/// The point in time in which the simulation started.
+	start: Instant,
+	/// The index of the next report to be generated.
+	next: u64,
+}
+impl Simulation {
+	/// Starts a new simulation.
+	fn new() -> Self {
+		Self {
+			start: Instant::now(),
+			next: 0
+		}
+	}
+
+	/// The point in time at which the next report is due.
+	fn due(&self) -> Instant {
+		self.start + REPORT_INTERVAL * (self.next + 1) as u32
+	}
+
+	/// Feeds the next report to the given handler.
+	fn generate(&mut self, handler: &mut ReportHandler) {
+		handler.on_pen_data(&pen_data(self.next), PenDataExtras::default());
+		self.next += 1;
+	}
+}
+impl ReportSource for Simulation {
+	fn try_handle_report(&mut self, handler: &mut ReportHandler)
+		-> Result<Option<bool>, Error> {
+
+		if Instant::now() < self.due() {
+			return Ok(None)
+		}
+
+		self.generate(handler);
+		Ok(Some(true))
+	}
+
+	fn wait_handle_report(&mut self, handler: &mut ReportHandler)
+		-> Result<bool, Error> {
+
+		std::thread::sleep(self.due().saturating_duration_since(Instant::now()));
+
+		self.generate(handler);
+		Ok(true)
+	}
+}
+
+/// Generates the pen data of the report with the given index.
+fn pen_data(index: u64) -> stu_sys::WacomGSS_PenData {
+	let t = f64::from((index % u64::from(REPORTS)) as u32) / f64::from(REPORTS);
+
+	/* Lift the pen between the strokes. */
+	let touching = (t * 3.0).fract() < 0.9 && t < 0.99;
+
+	let x = 0.1 + 0.8 * t;
+	let y = 0.5 + 0.25 * (t * std::f64::consts::PI * 6.0).sin();
+	let pressure = if touching {
+		0.4 + 0.4 * (t * std::f64::consts::PI * 3.0).sin().abs()
+	} else {
+		0.0
+	};
+
+	let mut pen_data: stu_sys::WacomGSS_PenData = unsafe { std::mem::zeroed() };
+	pen_data.rdy = 1;
+	pen_data.sw = touching as _;
+	pen_data.x = (x * f64::from(RESOLUTION.0)) as _;
+	pen_data.y = (y * f64::from(RESOLUTION.1)) as _;
+	pen_data.pressure = (pressure * f64::from(RESOLUTION.2)) as _;
+
+	pen_data
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{Connector, ConnectorDevice, Transport};
+
+	#[test]
+	fn simulated_device_is_listed() {
+		let simulated = crate::list_devices()
+			.unwrap()
+			.filter(|connector| connector.transport() == Transport::Simulated)
+			.count();
+		assert_eq!(simulated, 1);
+	}
+
+	#[test]
+	fn connectors_know_how_many_are_left() {
+		let mut connectors = crate::Connectors {
+			devices: vec![
+				ConnectorDevice::Simulated,
+				ConnectorDevice::Serial {
+					port: "COM3".to_string(),
+					baud_rate: crate::DEFAULT_SERIAL_BAUD_RATE
+				},
+				ConnectorDevice::Simulated,
+			].into_iter()
+		};
+
+		for left in (0..3).rev() {
+			assert!(!connectors.is_empty());
+			assert!(connectors.next().is_some());
+			assert_eq!(connectors.len(), left);
+		}
+		assert!(connectors.is_empty());
+		assert!(connectors.next().is_none());
+
+		let listed = crate::list_devices().unwrap();
+		let len = listed.len();
+		assert_eq!(listed.count(), len);
+	}
+
+	#[test]
+	fn simulated_queue_yields_the_scripted_events() {
+		let tablet = Connector { device: ConnectorDevice::Simulated }
+			.connect()
+			.unwrap();
+		assert_eq!(tablet.capability().unwrap(), CAPABILITY);
+
+		let mut queue = tablet.queue().unwrap();
+		for index in 0..10 {
+			let expected = pen_data(index);
+			let event = queue.recv().unwrap();
+
+			assert_eq!(event.raw_x(), expected.x);
+			assert_eq!(event.raw_y(), expected.y);
+			assert_eq!(event.raw_pressure(), expected.pressure);
+			assert_eq!(event.touching(), expected.sw != 0);
+		}
+	}
+
+	#[test]
+	fn simulated_display_echoes_the_written_image() {
+		let tablet = Connector { device: ConnectorDevice::Simulated }
+			.connect()
+			.unwrap();
+
+		let blank = tablet.read_display().unwrap();
+		assert!(blank.pixels().all(|pixel| pixel.0 == [u8::MAX]));
+
+		let pattern = image::GrayImage::from_fn(
+			CAPABILITY.width(),
+			CAPABILITY.height(),
+			|x, y| image::Luma([if (x / 8 + y / 8) % 2 == 0 { 0 } else { 255 }]));
+		tablet.write_image(&pattern, crate::ImageEncoding::Monochrome).unwrap();
+		assert_eq!(tablet.read_display().unwrap(), pattern);
+
+		let wrong = image::GrayImage::new(1, 1);
+		assert!(tablet.write_image(&wrong, crate::ImageEncoding::Monochrome).is_err());
+		assert_eq!(tablet.read_display().unwrap(), pattern);
+	}
+
+	#[test]
+	fn simulated_display_echoes_dynamic_images() {
+		let tablet = Connector { device: ConnectorDevice::Simulated }
+			.connect()
+			.unwrap();
+
+		let pattern = image::GrayImage::from_fn(
+			CAPABILITY.width(),
+			CAPABILITY.height(),
+			|x, _| image::Luma([if x < 400 { 0 } else { 255 }]));
+		let dynamic = image::DynamicImage::ImageLuma8(pattern.clone());
+		tablet.write_dynamic_image(&dynamic, None).unwrap();
+		assert_eq!(tablet.read_display().unwrap(), pattern);
+
+		let wrong = image::DynamicImage::new_rgb8(1, 1);
+		assert!(tablet.write_dynamic_image(&wrong, None).is_err());
+		assert_eq!(tablet.read_display().unwrap(), pattern);
+	}
+
+	#[test]
+	fn simulated_backlight_round_trips() {
+		let tablet = Connector { device: ConnectorDevice::Simulated }
+			.connect()
+			.unwrap();
+		assert_eq!(tablet.max_backlight().unwrap(), MAX_BACKLIGHT);
+		assert_eq!(tablet.backlight().unwrap(), MAX_BACKLIGHT);
+
+		for level in 0..=MAX_BACKLIGHT {
+			tablet.set_backlight(level).unwrap();
+			assert_eq!(tablet.backlight().unwrap(), level);
+		}
+
+		tablet.set_backlight(1).unwrap();
+		assert!(matches!(
+			tablet.set_backlight(MAX_BACKLIGHT + 1),
+			Err(Error::ClientError(crate::ClientError::BacklightOutOfRange {
+				level,
+				max: MAX_BACKLIGHT
+			})) if level == MAX_BACKLIGHT + 1));
+		assert_eq!(tablet.backlight().unwrap(), 1);
+	}
+
+	#[test]
+	fn capability_is_cached_until_reconnecting() {
+		let mut tablet = Connector { device: ConnectorDevice::Simulated }
+			.connect()
+			.unwrap();
+		assert_eq!(*tablet.capability.lock().unwrap(), None);
+		assert_eq!(tablet.capability().unwrap(), CAPABILITY);
+		assert_eq!(*tablet.capability.lock().unwrap(), Some(CAPABILITY));
+
+		/* Plant a capability the device would never report, so that we can
+		 * tell whether it gets asked again. */
+		let planted = Capability { display_width: 1, ..CAPABILITY };
+		*tablet.capability.lock().unwrap() = Some(planted);
+		assert_eq!(tablet.capability().unwrap(), planted);
+		assert_eq!(tablet.capability().unwrap(), planted);
+
+		tablet.reconnect().unwrap();
+		assert_eq!(tablet.capability().unwrap(), CAPABILITY);
+	}
+
+	#[test]
+	fn simulated_tablet_supports_no_reports() {
+		let tablet = Connector { device: ConnectorDevice::Simulated }
+			.connect()
+			.unwrap();
+
+		assert_eq!(tablet.supported_reports().count(), 0);
+		assert!(tablet.is_connected().unwrap());
+		assert!(tablet.try_clear().is_ok());
+		assert!(tablet.clear().is_err());
+	}
+}
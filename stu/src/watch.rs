@@ -0,0 +1,175 @@
+use crate::{Information, Error};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A change in the set of devices attached to the system.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum DeviceEvent {
+	/// A device has been attached to the system.
+	Added(Information),
+	/// A device has been detached from the system.
+	Removed(Information),
+}
+
+/// A watcher that reports devices as they get attached to and detached from
+/// the system.
+///
+/// The watcher polls the list of available devices in the background, at the
+/// interval given to [`watch_devices()`], and reports the difference between
+/// two consecutive lists as a sequence of [`DeviceEvent`]s. Devices that were
+/// already attached when the watcher was created are reported as having been
+/// added, the first time the list gets polled. Polling stops once the watcher
+/// is dropped.
+///
+/// This watcher is only available with the `watch` feature enabled.
+///
+/// [`watch_devices()`]: watch_devices
+pub struct DeviceWatcher {
+	/// The channel through which the background thread sends its events.
+	events: Receiver<DeviceEvent>,
+	/// Whether the background thread should stop polling.
+	stop: Arc<AtomicBool>,
+}
+impl DeviceWatcher {
+	/// Returns the next change in the set of devices if there is one pending,
+	/// without blocking.
+	///
+	/// Once the device list fails to be polled, which gets logged as a
+	/// warning, no further changes are ever reported.
+	pub fn try_recv(&self) -> Option<DeviceEvent> {
+		self.events.try_recv().ok()
+	}
+
+	/// Waits for the next change in the set of devices for at most the given
+	/// amount of time, returning `None` if there was no change in that time or
+	/// if the device list could no longer be polled.
+	pub fn recv_timeout(&self, timeout: Duration) -> Option<DeviceEvent> {
+		self.events.recv_timeout(timeout).ok()
+	}
+}
+impl Drop for DeviceWatcher {
+	fn drop(&mut self) {
+		self.stop.store(true, Ordering::Relaxed);
+	}
+}
+
+/// Starts watching for devices being attached to and detached from the system,
+/// polling the list of available devices at the given interval.
+///
+/// This function is only available with the `watch` feature enabled.
+pub fn watch_devices(interval: Duration) -> DeviceWatcher {
+	let (sender, events) = mpsc::channel();
+	let stop = Arc::new(AtomicBool::new(false));
+
+	let thread_stop = stop.clone();
+	std::thread::spawn(move || poll(
+		|| Ok(crate::list_devices()?
+			.map(|connector| connector.info())
+			.collect()),
+		sender,
+		&thread_stop,
+		interval));
+
+	DeviceWatcher { events, stop }
+}
+
+/// Polls the given device list until either the list fails, the receiving end
+/// of the channel goes away or polling is stopped, sending the changes in the
+/// list through the channel.
+fn poll<L>(
+	mut list: L,
+	sender: Sender<DeviceEvent>,
+	stop: &AtomicBool,
+	interval: Duration)
+	where L: FnMut() -> Result<Vec<Information>, Error> {
+
+	let mut known = HashSet::new();
+	while !stop.load(Ordering::Relaxed) {
+		let current = match list() {
+			Ok(current) => current.into_iter().collect::<HashSet<_>>(),
+			Err(what) => {
+				log::warn!("stopped watching for devices: {}", what);
+				return
+			}
+		};
+
+		for event in changes(&known, &current) {
+			if sender.send(event).is_err() {
+				return
+			}
+		}
+		known = current;
+
+		std::thread::sleep(interval);
+	}
+}
+
+/// The events that take the set of known devices to the current one.
+fn changes(
+	known: &HashSet<Information>,
+	current: &HashSet<Information>) -> Vec<DeviceEvent> {
+
+	known.difference(current)
+		.cloned()
+		.map(DeviceEvent::Removed)
+		.chain(current.difference(known)
+			.cloned()
+			.map(DeviceEvent::Added))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ClientError, Transport};
+
+	/// The information of a device attached through a USB port.
+	fn usb(product: u16) -> Information {
+		Information {
+			id_vendor: Some(crate::WACOM_VENDOR_ID),
+			id_product: Some(product),
+			bcd_device: Some(0x0100),
+			port: None,
+			transport: Transport::Usb
+		}
+	}
+
+	#[test]
+	fn poll_reports_the_changes_between_lists() {
+		let (a, b) = (usb(0x00a5), usb(0x00a8));
+		let mut lists = vec![
+			vec![a.clone()],
+			vec![a.clone(), b.clone()],
+			vec![a.clone(), b.clone()],
+			vec![b.clone()],
+			vec![],
+		].into_iter();
+
+		let (sender, events) = mpsc::channel();
+		let stop = AtomicBool::new(false);
+		poll(
+			|| lists.next().ok_or(Error::ClientError(ClientError::InvalidReport)),
+			sender,
+			&stop,
+			Duration::from_millis(0));
+
+		assert_eq!(events.try_iter().collect::<Vec<_>>(), vec![
+			DeviceEvent::Added(a.clone()),
+			DeviceEvent::Added(b.clone()),
+			DeviceEvent::Removed(a),
+			DeviceEvent::Removed(b),
+		]);
+	}
+
+	#[test]
+	fn poll_stops_when_asked_to() {
+		let (sender, events) = mpsc::channel();
+		let stop = AtomicBool::new(true);
+		poll(|| Ok(vec![usb(0x00a5)]), sender, &stop, Duration::from_millis(0));
+
+		assert!(events.try_recv().is_err());
+	}
+}